@@ -0,0 +1,1197 @@
+//! Textual representation of [`BindingBoxTree`]s
+//!
+//! [`box_tree_to_text`] pretty-prints a tree and [`box_tree_from_text`] parses it back.
+//! Both directions are lossless, so the text can be used instead of the JSON representation (e.g., for reviewing constraints).
+//!
+//! Variables are named like in CEL expressions (`e1`, `e2`, ... for event variables and `o1`, `o2`, ... for object variables).
+//! Filters, size filters and constraints use the names of the corresponding enum variants.
+//! Optional values are written as `_`, CEL code is usually written as a raw string (`r#"..."#`).
+//!
+//! ```text
+//! // Every order must be paid within 2 weeks
+//! node 0 {
+//!     o1: {"orders"};
+//!     e1: {"place order"};
+//!     filter O2E(o1, e1, _);
+//!     children 1 as "A";
+//!     constraint size NumChilds("A", 1, _);
+//! }
+//! node 1 {
+//!     e2: {"pay order"};
+//!     filter O2E(o1, e2, _);
+//!     filter TimeBetweenEvents(e1, e2, 0.0, 1209600.0);
+//! }
+//! node 2 = NOT(1);
+//! edge 2 -> 1 as "B";
+//! ```
+//!
+//! Box nodes contain (in any order) variable declarations, `filter`, `size` and `constraint` statements,
+//! as well as `children` statements (with optional edge names).
+//! `OR`, `AND` and `NOT` nodes are written as `node <index> = OR(<left>, <right>);` (resp. `AND`, `NOT`).
+//! Names of edges which are not a child edge of a box node are declared with top-level `edge` statements.
+//! Comments start with `//`.
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{Display, Write},
+};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::cel::{ev_var_to_name, ob_var_to_name};
+
+use super::structs::{
+    BindingBox, BindingBoxTree, BindingBoxTreeNode, Constraint, EventVariable, Filter,
+    ObjectValueFilterTimepoint, ObjectVariable, SizeFilter, ValueFilter, Variable,
+};
+
+const INDENT: &str = "    ";
+
+/// Error while parsing the textual representation of a [`BindingBoxTree`]
+///
+/// Lines and columns start at 1
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoxTreeParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Display for BoxTreeParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for BoxTreeParseError {}
+
+#[derive(Serialize, Deserialize)]
+pub struct BoxTreeFromTextRequest {
+    pub text: String,
+}
+
+///
+/// Pretty-print a [`BindingBoxTree`] in the textual representation
+///
+/// Variables, object/event types and edge names are sorted, so the output is deterministic
+///
+pub fn box_tree_to_text(tree: &BindingBoxTree) -> String {
+    let mut s = String::new();
+    let mut printed_edges: HashSet<(usize, usize)> = HashSet::new();
+    for (index, node) in tree.nodes.iter().enumerate() {
+        if index > 0 {
+            s.push('\n');
+        }
+        match node {
+            BindingBoxTreeNode::Box(bbox, children) => {
+                writeln!(s, "node {index} {{").unwrap();
+                print_box(&mut s, bbox);
+                if !children.is_empty() {
+                    let children_text = children
+                        .iter()
+                        .map(|c| {
+                            printed_edges.insert((index, *c));
+                            match tree.edge_names.get(&(index, *c)) {
+                                Some(name) => format!("{c} as {}", quote(name)),
+                                None => c.to_string(),
+                            }
+                        })
+                        .join(", ");
+                    writeln!(s, "{INDENT}children {children_text};").unwrap();
+                }
+                s.push_str("}\n");
+            }
+            BindingBoxTreeNode::OR(c1, c2) => {
+                writeln!(s, "node {index} = OR({c1}, {c2});").unwrap()
+            }
+            BindingBoxTreeNode::AND(c1, c2) => {
+                writeln!(s, "node {index} = AND({c1}, {c2});").unwrap()
+            }
+            BindingBoxTreeNode::NOT(c) => writeln!(s, "node {index} = NOT({c});").unwrap(),
+        }
+    }
+    let other_edges = tree
+        .edge_names
+        .iter()
+        .filter(|(edge, _)| !printed_edges.contains(edge))
+        .sorted_by_key(|(edge, _)| **edge)
+        .collect_vec();
+    if !other_edges.is_empty() {
+        s.push('\n');
+        for ((from, to), name) in other_edges {
+            writeln!(s, "edge {from} -> {to} as {};", quote(name)).unwrap();
+        }
+    }
+    s
+}
+
+fn print_box(s: &mut String, bbox: &BindingBox) {
+    for (ev_var, types) in bbox.new_event_vars.iter().sorted_by_key(|(v, _)| **v) {
+        writeln!(
+            s,
+            "{INDENT}{}: {};",
+            ev_var_to_name(ev_var),
+            print_types(types)
+        )
+        .unwrap();
+    }
+    for (ob_var, types) in bbox.new_object_vars.iter().sorted_by_key(|(v, _)| **v) {
+        writeln!(
+            s,
+            "{INDENT}{}: {};",
+            ob_var_to_name(ob_var),
+            print_types(types)
+        )
+        .unwrap();
+    }
+    for filter in &bbox.filters {
+        writeln!(s, "{INDENT}filter {};", print_filter(filter)).unwrap();
+    }
+    for size_filter in &bbox.size_filters {
+        writeln!(s, "{INDENT}size {};", print_size_filter(size_filter)).unwrap();
+    }
+    for constraint in &bbox.constraints {
+        writeln!(s, "{INDENT}constraint {};", print_constraint(constraint)).unwrap();
+    }
+}
+
+fn print_types(types: &HashSet<String>) -> String {
+    format!("{{{}}}", types.iter().sorted().map(|t| quote(t)).join(", "))
+}
+
+fn print_var(var: &Variable) -> String {
+    match var {
+        Variable::Event(ev_var) => ev_var_to_name(ev_var),
+        Variable::Object(ob_var) => ob_var_to_name(ob_var),
+    }
+}
+
+fn print_opt<T>(val: &Option<T>, f: impl Fn(&T) -> String) -> String {
+    match val {
+        Some(v) => f(v),
+        None => "_".to_string(),
+    }
+}
+
+fn print_qualifier(qualifier: &Option<String>) -> String {
+    print_opt(qualifier, |q| quote(q))
+}
+
+/// Debug formatting of floats always includes a decimal point or exponent and round-trips exactly
+fn print_f64(val: &f64) -> String {
+    format!("{val:?}")
+}
+
+fn print_time(time: &DateTime<Utc>) -> String {
+    quote(&time.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+}
+
+fn print_names(names: &[String]) -> String {
+    names.iter().map(|n| quote(n)).join(", ")
+}
+
+fn print_filter(filter: &Filter) -> String {
+    match filter {
+        Filter::O2E {
+            object,
+            event,
+            qualifier,
+        } => format!(
+            "O2E({}, {}, {})",
+            ob_var_to_name(object),
+            ev_var_to_name(event),
+            print_qualifier(qualifier)
+        ),
+        Filter::O2O {
+            object,
+            other_object,
+            qualifier,
+        } => format!(
+            "O2O({}, {}, {})",
+            ob_var_to_name(object),
+            ob_var_to_name(other_object),
+            print_qualifier(qualifier)
+        ),
+        Filter::TimeBetweenEvents {
+            from_event,
+            to_event,
+            min_seconds,
+            max_seconds,
+        } => format!(
+            "TimeBetweenEvents({}, {}, {}, {})",
+            ev_var_to_name(from_event),
+            ev_var_to_name(to_event),
+            print_opt(min_seconds, print_f64),
+            print_opt(max_seconds, print_f64)
+        ),
+        Filter::NotEqual { var_1, var_2 } => {
+            format!("NotEqual({}, {})", print_var(var_1), print_var(var_2))
+        }
+        Filter::EventAttributeValueFilter {
+            event,
+            attribute_name,
+            value_filter,
+        } => format!(
+            "EventAttributeValueFilter({}, {}, {})",
+            ev_var_to_name(event),
+            quote(attribute_name),
+            print_value_filter(value_filter)
+        ),
+        Filter::ObjectAttributeValueFilter {
+            object,
+            attribute_name,
+            at_time,
+            value_filter,
+        } => format!(
+            "ObjectAttributeValueFilter({}, {}, {}, {})",
+            ob_var_to_name(object),
+            quote(attribute_name),
+            match at_time {
+                ObjectValueFilterTimepoint::Always => "Always".to_string(),
+                ObjectValueFilterTimepoint::Sometime => "Sometime".to_string(),
+                ObjectValueFilterTimepoint::AtEvent { event } =>
+                    format!("AtEvent({})", ev_var_to_name(event)),
+            },
+            print_value_filter(value_filter)
+        ),
+        Filter::BasicFilterCEL { cel } => format!("BasicFilterCEL({})", quote_cel(cel)),
+    }
+}
+
+fn print_value_filter(value_filter: &ValueFilter) -> String {
+    match value_filter {
+        ValueFilter::Float { min, max } => format!(
+            "Float({}, {})",
+            print_opt(min, print_f64),
+            print_opt(max, print_f64)
+        ),
+        ValueFilter::Integer { min, max } => format!(
+            "Integer({}, {})",
+            print_opt(min, i64::to_string),
+            print_opt(max, i64::to_string)
+        ),
+        ValueFilter::Boolean { is_true } => format!("Boolean({is_true})"),
+        ValueFilter::String { is_in } => format!("String({})", print_names(is_in)),
+        ValueFilter::Time { from, to } => format!(
+            "Time({}, {})",
+            print_opt(from, print_time),
+            print_opt(to, print_time)
+        ),
+    }
+}
+
+fn print_size_filter(size_filter: &SizeFilter) -> String {
+    match size_filter {
+        SizeFilter::NumChilds {
+            child_name,
+            min,
+            max,
+        } => format!(
+            "NumChilds({}, {}, {})",
+            quote(child_name),
+            print_opt(min, usize::to_string),
+            print_opt(max, usize::to_string)
+        ),
+        SizeFilter::BindingSetEqual { child_names } => {
+            format!("BindingSetEqual({})", print_names(child_names))
+        }
+        SizeFilter::BindingSetProjectionEqual {
+            child_name_with_var_name,
+        } => format!(
+            "BindingSetProjectionEqual({})",
+            child_name_with_var_name
+                .iter()
+                .map(|(name, var)| format!("({}, {})", quote(name), print_var(var)))
+                .join(", ")
+        ),
+        SizeFilter::NumChildsProj {
+            child_name,
+            var_name,
+            min,
+            max,
+        } => format!(
+            "NumChildsProj({}, {}, {}, {})",
+            quote(child_name),
+            print_var(var_name),
+            print_opt(min, usize::to_string),
+            print_opt(max, usize::to_string)
+        ),
+        SizeFilter::AdvancedCEL { cel } => format!("AdvancedCEL({})", quote_cel(cel)),
+    }
+}
+
+fn print_constraint(constraint: &Constraint) -> String {
+    match constraint {
+        Constraint::Filter { filter } => format!("filter {}", print_filter(filter)),
+        Constraint::SizeFilter { filter } => format!("size {}", print_size_filter(filter)),
+        Constraint::SAT { child_names } => format!("SAT({})", print_names(child_names)),
+        Constraint::ANY { child_names } => format!("ANY({})", print_names(child_names)),
+        Constraint::NOT { child_names } => format!("NOT({})", print_names(child_names)),
+        Constraint::OR { child_names } => format!("OR({})", print_names(child_names)),
+        Constraint::AND { child_names } => format!("AND({})", print_names(child_names)),
+    }
+}
+
+/// Quote and escape a string
+fn quote(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len() + 2);
+    ret.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => ret.push_str("\\\""),
+            '\\' => ret.push_str("\\\\"),
+            '\n' => ret.push_str("\\n"),
+            '\r' => ret.push_str("\\r"),
+            '\t' => ret.push_str("\\t"),
+            c if c.is_control() => write!(ret, "\\u{{{:x}}}", c as u32).unwrap(),
+            c => ret.push(c),
+        }
+    }
+    ret.push('"');
+    ret
+}
+
+/// Quote CEL code as raw string (using as many `#` as needed), so that it can be read without escapes
+fn quote_cel(cel: &str) -> String {
+    let mut hashes = 1;
+    while cel.contains(&format!("\"{}", "#".repeat(hashes))) {
+        hashes += 1;
+    }
+    let hashes = "#".repeat(hashes);
+    format!("r{hashes}\"{cel}\"{hashes}")
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Ident(String),
+    Number(String),
+    Str(String),
+    Punct(char),
+    Arrow,
+    Eof,
+}
+
+impl Display for TokenKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenKind::Ident(s) => write!(f, "'{s}'"),
+            TokenKind::Number(n) => write!(f, "number {n}"),
+            TokenKind::Str(s) => write!(f, "string {}", quote(s)),
+            TokenKind::Punct(c) => write!(f, "'{c}'"),
+            TokenKind::Arrow => write!(f, "'->'"),
+            TokenKind::Eof => write!(f, "end of input"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    line: usize,
+    column: usize,
+}
+
+fn unterminated(line: usize, column: usize) -> BoxTreeParseError {
+    BoxTreeParseError {
+        line,
+        column,
+        message: "Unterminated string".to_string(),
+    }
+}
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            chars: input.chars().peekable(),
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> BoxTreeParseError {
+        BoxTreeParseError {
+            line: self.line,
+            column: self.column,
+            message: message.into(),
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn tokenize(mut self) -> Result<Vec<Token>, BoxTreeParseError> {
+        let mut tokens = Vec::new();
+        loop {
+            // Skip whitespace and comments
+            while let Some(c) = self.peek() {
+                if c.is_whitespace() {
+                    self.next_char();
+                } else if c == '/' {
+                    let mut ahead = self.chars.clone();
+                    ahead.next();
+                    if ahead.peek() != Some(&'/') {
+                        break;
+                    }
+                    while self.peek().is_some_and(|c| c != '\n') {
+                        self.next_char();
+                    }
+                } else {
+                    break;
+                }
+            }
+            let (line, column) = (self.line, self.column);
+            let c = match self.peek() {
+                Some(c) => c,
+                None => {
+                    tokens.push(Token {
+                        kind: TokenKind::Eof,
+                        line,
+                        column,
+                    });
+                    return Ok(tokens);
+                }
+            };
+            let kind = if c == '"' {
+                self.next_char();
+                TokenKind::Str(self.lex_string(line, column)?)
+            } else if c == 'r' && {
+                let mut ahead = self.chars.clone();
+                ahead.next();
+                matches!(ahead.peek(), Some('#') | Some('"'))
+            } {
+                self.next_char();
+                TokenKind::Str(self.lex_raw_string(line, column)?)
+            } else if c.is_ascii_alphabetic() || c == '_' {
+                let mut ident = String::new();
+                while let Some(c) = self
+                    .peek()
+                    .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+                {
+                    ident.push(c);
+                    self.next_char();
+                }
+                TokenKind::Ident(ident)
+            } else if c == '-' && {
+                let mut ahead = self.chars.clone();
+                ahead.next();
+                ahead.peek() == Some(&'>')
+            } {
+                self.next_char();
+                self.next_char();
+                TokenKind::Arrow
+            } else if c.is_ascii_digit() || c == '-' {
+                TokenKind::Number(self.lex_number())
+            } else if "{}(),;:=".contains(c) {
+                self.next_char();
+                TokenKind::Punct(c)
+            } else {
+                return Err(self.error(format!("Unexpected character '{c}'")));
+            };
+            tokens.push(Token { kind, line, column });
+        }
+    }
+
+    fn lex_number(&mut self) -> String {
+        let mut num = String::new();
+        if self.peek() == Some('-') {
+            num.push('-');
+            self.next_char();
+            // Negative infinity (`-inf`)
+            if self.peek().is_some_and(|c| c.is_alphabetic()) {
+                while let Some(c) = self.peek().filter(|c| c.is_alphanumeric()) {
+                    num.push(c);
+                    self.next_char();
+                }
+                return num;
+            }
+        }
+        while let Some(c) = self.peek() {
+            let after_exponent = num.ends_with(['e', 'E']);
+            if c.is_ascii_digit()
+                || c == '.'
+                || c == 'e'
+                || c == 'E'
+                || (after_exponent && (c == '-' || c == '+'))
+            {
+                num.push(c);
+                self.next_char();
+            } else {
+                break;
+            }
+        }
+        num
+    }
+
+    fn lex_string(&mut self, line: usize, column: usize) -> Result<String, BoxTreeParseError> {
+        let mut s = String::new();
+        loop {
+            match self.next_char() {
+                None => return Err(unterminated(line, column)),
+                Some('"') => return Ok(s),
+                Some('\\') => match self.next_char() {
+                    Some('"') => s.push('"'),
+                    Some('\\') => s.push('\\'),
+                    Some('n') => s.push('\n'),
+                    Some('r') => s.push('\r'),
+                    Some('t') => s.push('\t'),
+                    Some('u') => {
+                        if self.next_char() != Some('{') {
+                            return Err(self.error("Expected '{' in unicode escape"));
+                        }
+                        let mut hex = String::new();
+                        while let Some(c) = self.next_char() {
+                            if c == '}' {
+                                break;
+                            }
+                            hex.push(c);
+                        }
+                        match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                            Some(c) => s.push(c),
+                            None => {
+                                return Err(self.error(format!("Invalid unicode escape '{hex}'")))
+                            }
+                        }
+                    }
+                    Some(c) => return Err(self.error(format!("Unknown escape sequence '\\{c}'"))),
+                    None => return Err(unterminated(line, column)),
+                },
+                Some(c) => s.push(c),
+            }
+        }
+    }
+
+    fn lex_raw_string(&mut self, line: usize, column: usize) -> Result<String, BoxTreeParseError> {
+        let mut hashes = 0;
+        while self.peek() == Some('#') {
+            hashes += 1;
+            self.next_char();
+        }
+        if self.next_char() != Some('"') {
+            return Err(self.error("Expected '\"' to start raw string"));
+        }
+        let terminator = format!("\"{}", "#".repeat(hashes));
+        let mut s = String::new();
+        loop {
+            match self.next_char() {
+                None => return Err(unterminated(line, column)),
+                Some(c) => {
+                    s.push(c);
+                    if s.ends_with(&terminator) {
+                        s.truncate(s.len() - terminator.len());
+                        return Ok(s);
+                    }
+                }
+            }
+        }
+    }
+}
+
+///
+/// Parse the textual representation of a [`BindingBoxTree`] (see the module documentation)
+///
+pub fn box_tree_from_text(text: &str) -> Result<BindingBoxTree, BoxTreeParseError> {
+    let tokens = Lexer::new(text).tokenize()?;
+    Parser {
+        tokens,
+        pos: 0,
+        edge_names: HashMap::new(),
+        child_refs: Vec::new(),
+    }
+    .tree()
+}
+
+/// Inverse of [`ev_var_to_name`] and [`ob_var_to_name`]
+fn parse_var_name(name: &str) -> Option<Variable> {
+    if name.len() < 2 || !name.is_ascii() {
+        return None;
+    }
+    let (prefix, num) = name.split_at(1);
+    if !num.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let index = num.parse::<usize>().ok()?.checked_sub(1)?;
+    match prefix {
+        "e" => Some(Variable::Event(EventVariable(index))),
+        "o" => Some(Variable::Object(ObjectVariable(index))),
+        _ => None,
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    edge_names: HashMap<(usize, usize), String>,
+    /// Referenced nodes (checked to exist after all nodes are parsed)
+    child_refs: Vec<(usize, Token)>,
+}
+
+type ParseResult<T> = Result<T, BoxTreeParseError>;
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn next(&mut self) -> Token {
+        let t = self.tokens[self.pos].clone();
+        if t.kind != TokenKind::Eof {
+            self.pos += 1;
+        }
+        t
+    }
+
+    fn error_at<T>(token: &Token, message: impl Into<String>) -> ParseResult<T> {
+        Err(BoxTreeParseError {
+            line: token.line,
+            column: token.column,
+            message: message.into(),
+        })
+    }
+
+    fn unexpected<T>(token: &Token, expected: &str) -> ParseResult<T> {
+        Self::error_at(token, format!("Expected {expected}, found {}", token.kind))
+    }
+
+    fn is_punct(&self, c: char) -> bool {
+        self.peek().kind == TokenKind::Punct(c)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(&self.peek().kind, TokenKind::Ident(s) if s == keyword)
+    }
+
+    fn expect_punct(&mut self, c: char) -> ParseResult<()> {
+        let t = self.next();
+        if t.kind == TokenKind::Punct(c) {
+            Ok(())
+        } else {
+            Self::unexpected(&t, &format!("'{c}'"))
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> ParseResult<()> {
+        let t = self.next();
+        match &t.kind {
+            TokenKind::Ident(s) if s == keyword => Ok(()),
+            _ => Self::unexpected(&t, &format!("'{keyword}'")),
+        }
+    }
+
+    fn ident(&mut self) -> ParseResult<(String, Token)> {
+        let t = self.next();
+        match &t.kind {
+            TokenKind::Ident(s) => Ok((s.clone(), t)),
+            _ => Self::unexpected(&t, "identifier"),
+        }
+    }
+
+    fn string(&mut self) -> ParseResult<String> {
+        let t = self.next();
+        match t.kind {
+            TokenKind::Str(s) => Ok(s),
+            _ => Self::unexpected(&t, "string"),
+        }
+    }
+
+    fn comma(&mut self) -> ParseResult<()> {
+        self.expect_punct(',')
+    }
+
+    /// `_` (i.e., no value) or a value parsed by `f`
+    fn opt<T>(&mut self, f: impl FnOnce(&mut Self) -> ParseResult<T>) -> ParseResult<Option<T>> {
+        if self.is_keyword("_") {
+            self.next();
+            Ok(None)
+        } else {
+            f(self).map(Some)
+        }
+    }
+
+    fn number<T: std::str::FromStr>(&mut self, expected: &str) -> ParseResult<T> {
+        let t = self.next();
+        let text = match &t.kind {
+            TokenKind::Number(n) => n.as_str(),
+            // Non-finite floats
+            TokenKind::Ident(s) if s == "inf" || s == "NaN" => s.as_str(),
+            _ => return Self::unexpected(&t, expected),
+        };
+        match text.parse() {
+            Ok(n) => Ok(n),
+            Err(_) => Self::error_at(&t, format!("Invalid {expected} '{text}'")),
+        }
+    }
+
+    fn usize(&mut self) -> ParseResult<usize> {
+        self.number("non-negative integer")
+    }
+
+    fn f64(&mut self) -> ParseResult<f64> {
+        self.number("number")
+    }
+
+    fn time(&mut self) -> ParseResult<DateTime<Utc>> {
+        let t = self.peek().clone();
+        let s = self.string()?;
+        match DateTime::parse_from_rfc3339(&s) {
+            Ok(dt) => Ok(dt.with_timezone(&Utc)),
+            Err(e) => Self::error_at(&t, format!("Invalid RFC 3339 timestamp '{s}': {e}")),
+        }
+    }
+
+    /// Index of a referenced node
+    fn node_ref(&mut self) -> ParseResult<usize> {
+        let t = self.peek().clone();
+        let index = self.usize()?;
+        self.child_refs.push((index, t));
+        Ok(index)
+    }
+
+    /// Comma-separated list in parentheses
+    fn list<T>(&mut self, mut f: impl FnMut(&mut Self) -> ParseResult<T>) -> ParseResult<Vec<T>> {
+        self.expect_punct('(')?;
+        let mut ret = Vec::new();
+        if self.is_punct(')') {
+            self.next();
+            return Ok(ret);
+        }
+        loop {
+            ret.push(f(self)?);
+            if self.is_punct(',') {
+                self.next();
+            } else {
+                self.expect_punct(')')?;
+                return Ok(ret);
+            }
+        }
+    }
+
+    /// Fixed arguments in parentheses (`f` parses the arguments and separating commas)
+    fn args<T>(&mut self, f: impl FnOnce(&mut Self) -> ParseResult<T>) -> ParseResult<T> {
+        self.expect_punct('(')?;
+        let ret = f(self)?;
+        self.expect_punct(')')?;
+        Ok(ret)
+    }
+
+    fn var(&mut self) -> ParseResult<Variable> {
+        let (name, t) = self.ident()?;
+        match parse_var_name(&name) {
+            Some(var) => Ok(var),
+            None => Self::unexpected(&t, "variable (e.g., e1 or o1)"),
+        }
+    }
+
+    fn ev_var(&mut self) -> ParseResult<EventVariable> {
+        let t = self.peek().clone();
+        match self.var()? {
+            Variable::Event(ev_var) => Ok(ev_var),
+            Variable::Object(_) => Self::unexpected(&t, "event variable (e.g., e1)"),
+        }
+    }
+
+    fn ob_var(&mut self) -> ParseResult<ObjectVariable> {
+        let t = self.peek().clone();
+        match self.var()? {
+            Variable::Object(ob_var) => Ok(ob_var),
+            Variable::Event(_) => Self::unexpected(&t, "object variable (e.g., o1)"),
+        }
+    }
+
+    fn insert_edge_name(
+        &mut self,
+        edge: (usize, usize),
+        name: String,
+        t: &Token,
+    ) -> ParseResult<()> {
+        match self.edge_names.get(&edge) {
+            Some(existing) if existing != &name => Self::error_at(
+                t,
+                format!(
+                    "Conflicting names {} and {} for edge {} -> {}",
+                    quote(existing),
+                    quote(&name),
+                    edge.0,
+                    edge.1
+                ),
+            ),
+            _ => {
+                self.edge_names.insert(edge, name);
+                Ok(())
+            }
+        }
+    }
+
+    fn tree(mut self) -> ParseResult<BindingBoxTree> {
+        let mut nodes = Vec::new();
+        loop {
+            let t = self.next();
+            match &t.kind {
+                TokenKind::Eof => break,
+                TokenKind::Ident(s) if s == "node" => {
+                    let index_token = self.peek().clone();
+                    let index = self.usize()?;
+                    if index != nodes.len() {
+                        return Self::error_at(
+                            &index_token,
+                            format!(
+                                "Expected node {} (nodes are numbered consecutively starting at 0)",
+                                nodes.len()
+                            ),
+                        );
+                    }
+                    nodes.push(self.node(index)?);
+                }
+                TokenKind::Ident(s) if s == "edge" => {
+                    let from = self.node_ref()?;
+                    let arrow = self.next();
+                    if arrow.kind != TokenKind::Arrow {
+                        return Self::unexpected(&arrow, "'->'");
+                    }
+                    let to = self.node_ref()?;
+                    self.expect_keyword("as")?;
+                    let name_token = self.peek().clone();
+                    let name = self.string()?;
+                    self.expect_punct(';')?;
+                    self.insert_edge_name((from, to), name, &name_token)?;
+                }
+                _ => return Self::unexpected(&t, "'node' or 'edge'"),
+            }
+        }
+        if let Some((index, t)) = self
+            .child_refs
+            .iter()
+            .find(|(index, _)| *index >= nodes.len())
+        {
+            return Self::error_at(t, format!("Node {index} does not exist"));
+        }
+        Ok(BindingBoxTree {
+            nodes,
+            edge_names: self.edge_names,
+        })
+    }
+
+    fn node(&mut self, index: usize) -> ParseResult<BindingBoxTreeNode> {
+        if self.is_punct('=') {
+            self.next();
+            let (op, op_token) = self.ident()?;
+            let node = match op.as_str() {
+                "OR" => self.args(|p| {
+                    let c1 = p.node_ref()?;
+                    p.comma()?;
+                    Ok(BindingBoxTreeNode::OR(c1, p.node_ref()?))
+                })?,
+                "AND" => self.args(|p| {
+                    let c1 = p.node_ref()?;
+                    p.comma()?;
+                    Ok(BindingBoxTreeNode::AND(c1, p.node_ref()?))
+                })?,
+                "NOT" => BindingBoxTreeNode::NOT(self.args(|p| p.node_ref())?),
+                _ => return Self::unexpected(&op_token, "'OR', 'AND' or 'NOT'"),
+            };
+            self.expect_punct(';')?;
+            return Ok(node);
+        }
+        self.expect_punct('{')?;
+        let mut bbox = BindingBox {
+            new_event_vars: HashMap::new(),
+            new_object_vars: HashMap::new(),
+            filters: Vec::new(),
+            size_filters: Vec::new(),
+            constraints: Vec::new(),
+        };
+        let mut children = Vec::new();
+        loop {
+            let (keyword, t) = match &self.peek().kind {
+                TokenKind::Punct('}') => {
+                    self.next();
+                    return Ok(BindingBoxTreeNode::Box(bbox, children));
+                }
+                _ => self.ident()?,
+            };
+            match keyword.as_str() {
+                "filter" => bbox.filters.push(self.filter()?),
+                "size" => bbox.size_filters.push(self.size_filter()?),
+                "constraint" => bbox.constraints.push(self.constraint()?),
+                "children" => loop {
+                    let c = self.node_ref()?;
+                    if self.is_keyword("as") {
+                        self.next();
+                        let name_token = self.peek().clone();
+                        let name = self.string()?;
+                        self.insert_edge_name((index, c), name, &name_token)?;
+                    }
+                    children.push(c);
+                    if self.is_punct(',') {
+                        self.next();
+                    } else {
+                        break;
+                    }
+                },
+                name => {
+                    let var = match parse_var_name(name) {
+                        Some(var) => var,
+                        None => {
+                            return Self::unexpected(
+                                &t,
+                                "variable declaration, 'filter', 'size', 'constraint', 'children' or '}'",
+                            )
+                        }
+                    };
+                    self.expect_punct(':')?;
+                    self.expect_punct('{')?;
+                    let mut types = HashSet::new();
+                    while !self.is_punct('}') {
+                        types.insert(self.string()?);
+                        if !self.is_punct('}') {
+                            self.comma()?;
+                        }
+                    }
+                    self.next();
+                    let already_declared = match var {
+                        Variable::Event(ev_var) => {
+                            bbox.new_event_vars.insert(ev_var, types).is_some()
+                        }
+                        Variable::Object(ob_var) => {
+                            bbox.new_object_vars.insert(ob_var, types).is_some()
+                        }
+                    };
+                    if already_declared {
+                        return Self::error_at(&t, format!("Variable {name} is declared twice"));
+                    }
+                }
+            }
+            self.expect_punct(';')?;
+        }
+    }
+
+    fn filter(&mut self) -> ParseResult<Filter> {
+        let (name, t) = self.ident()?;
+        match name.as_str() {
+            "O2E" => self.args(|p| {
+                let object = p.ob_var()?;
+                p.comma()?;
+                let event = p.ev_var()?;
+                p.comma()?;
+                let qualifier = p.opt(Self::string)?;
+                Ok(Filter::O2E {
+                    object,
+                    event,
+                    qualifier,
+                })
+            }),
+            "O2O" => self.args(|p| {
+                let object = p.ob_var()?;
+                p.comma()?;
+                let other_object = p.ob_var()?;
+                p.comma()?;
+                let qualifier = p.opt(Self::string)?;
+                Ok(Filter::O2O {
+                    object,
+                    other_object,
+                    qualifier,
+                })
+            }),
+            "TimeBetweenEvents" => self.args(|p| {
+                let from_event = p.ev_var()?;
+                p.comma()?;
+                let to_event = p.ev_var()?;
+                p.comma()?;
+                let min_seconds = p.opt(Self::f64)?;
+                p.comma()?;
+                let max_seconds = p.opt(Self::f64)?;
+                Ok(Filter::TimeBetweenEvents {
+                    from_event,
+                    to_event,
+                    min_seconds,
+                    max_seconds,
+                })
+            }),
+            "NotEqual" => self.args(|p| {
+                let var_1 = p.var()?;
+                p.comma()?;
+                let var_2 = p.var()?;
+                Ok(Filter::NotEqual { var_1, var_2 })
+            }),
+            "EventAttributeValueFilter" => self.args(|p| {
+                let event = p.ev_var()?;
+                p.comma()?;
+                let attribute_name = p.string()?;
+                p.comma()?;
+                let value_filter = p.value_filter()?;
+                Ok(Filter::EventAttributeValueFilter {
+                    event,
+                    attribute_name,
+                    value_filter,
+                })
+            }),
+            "ObjectAttributeValueFilter" => self.args(|p| {
+                let object = p.ob_var()?;
+                p.comma()?;
+                let attribute_name = p.string()?;
+                p.comma()?;
+                let at_time = p.timepoint()?;
+                p.comma()?;
+                let value_filter = p.value_filter()?;
+                Ok(Filter::ObjectAttributeValueFilter {
+                    object,
+                    attribute_name,
+                    at_time,
+                    value_filter,
+                })
+            }),
+            "BasicFilterCEL" => self.args(|p| Ok(Filter::BasicFilterCEL { cel: p.string()? })),
+            _ => Self::unexpected(&t, "filter (e.g., O2E, O2O, TimeBetweenEvents)"),
+        }
+    }
+
+    fn timepoint(&mut self) -> ParseResult<ObjectValueFilterTimepoint> {
+        let (name, t) = self.ident()?;
+        match name.as_str() {
+            "Always" => Ok(ObjectValueFilterTimepoint::Always),
+            "Sometime" => Ok(ObjectValueFilterTimepoint::Sometime),
+            "AtEvent" => {
+                self.args(|p| Ok(ObjectValueFilterTimepoint::AtEvent { event: p.ev_var()? }))
+            }
+            _ => Self::unexpected(&t, "'Always', 'Sometime' or 'AtEvent'"),
+        }
+    }
+
+    fn value_filter(&mut self) -> ParseResult<ValueFilter> {
+        let (name, t) = self.ident()?;
+        match name.as_str() {
+            "Float" => self.args(|p| {
+                let min = p.opt(Self::f64)?;
+                p.comma()?;
+                let max = p.opt(Self::f64)?;
+                Ok(ValueFilter::Float { min, max })
+            }),
+            "Integer" => self.args(|p| {
+                let min = p.opt(|p| p.number("integer"))?;
+                p.comma()?;
+                let max = p.opt(|p| p.number("integer"))?;
+                Ok(ValueFilter::Integer { min, max })
+            }),
+            "Boolean" => self.args(|p| {
+                let (val, val_token) = p.ident()?;
+                match val.as_str() {
+                    "true" => Ok(ValueFilter::Boolean { is_true: true }),
+                    "false" => Ok(ValueFilter::Boolean { is_true: false }),
+                    _ => Self::unexpected(&val_token, "'true' or 'false'"),
+                }
+            }),
+            "String" => Ok(ValueFilter::String {
+                is_in: self.list(Self::string)?,
+            }),
+            "Time" => self.args(|p| {
+                let from = p.opt(Self::time)?;
+                p.comma()?;
+                let to = p.opt(Self::time)?;
+                Ok(ValueFilter::Time { from, to })
+            }),
+            _ => Self::unexpected(&t, "'Float', 'Integer', 'Boolean', 'String' or 'Time'"),
+        }
+    }
+
+    fn size_filter(&mut self) -> ParseResult<SizeFilter> {
+        let (name, t) = self.ident()?;
+        match name.as_str() {
+            "NumChilds" => self.args(|p| {
+                let child_name = p.string()?;
+                p.comma()?;
+                let min = p.opt(Self::usize)?;
+                p.comma()?;
+                let max = p.opt(Self::usize)?;
+                Ok(SizeFilter::NumChilds {
+                    child_name,
+                    min,
+                    max,
+                })
+            }),
+            "BindingSetEqual" => Ok(SizeFilter::BindingSetEqual {
+                child_names: self.list(Self::string)?,
+            }),
+            "BindingSetProjectionEqual" => Ok(SizeFilter::BindingSetProjectionEqual {
+                child_name_with_var_name: self.list(|p| {
+                    p.args(|p| {
+                        let child_name = p.string()?;
+                        p.comma()?;
+                        Ok((child_name, p.var()?))
+                    })
+                })?,
+            }),
+            "NumChildsProj" => self.args(|p| {
+                let child_name = p.string()?;
+                p.comma()?;
+                let var_name = p.var()?;
+                p.comma()?;
+                let min = p.opt(Self::usize)?;
+                p.comma()?;
+                let max = p.opt(Self::usize)?;
+                Ok(SizeFilter::NumChildsProj {
+                    child_name,
+                    var_name,
+                    min,
+                    max,
+                })
+            }),
+            "AdvancedCEL" => self.args(|p| Ok(SizeFilter::AdvancedCEL { cel: p.string()? })),
+            _ => Self::unexpected(&t, "size filter (e.g., NumChilds, BindingSetEqual)"),
+        }
+    }
+
+    fn constraint(&mut self) -> ParseResult<Constraint> {
+        let (name, t) = self.ident()?;
+        Ok(match name.as_str() {
+            "filter" => Constraint::Filter {
+                filter: self.filter()?,
+            },
+            "size" => Constraint::SizeFilter {
+                filter: self.size_filter()?,
+            },
+            "SAT" => Constraint::SAT {
+                child_names: self.list(Self::string)?,
+            },
+            "ANY" => Constraint::ANY {
+                child_names: self.list(Self::string)?,
+            },
+            "NOT" => Constraint::NOT {
+                child_names: self.list(Self::string)?,
+            },
+            "OR" => Constraint::OR {
+                child_names: self.list(Self::string)?,
+            },
+            "AND" => Constraint::AND {
+                child_names: self.list(Self::string)?,
+            },
+            _ => {
+                return Self::unexpected(&t, "'filter', 'size', 'SAT', 'ANY', 'NOT', 'OR' or 'AND'")
+            }
+        })
+    }
+}
@@ -1,3 +1,4 @@
+use itertools::Itertools;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 use crate::preprocessing::linked_ocel::{EventOrObjectIndex, IndexLinkedOCEL};
@@ -148,9 +149,10 @@ impl BindingBox {
                         .into_par_iter()
                         .flat_map_iter(|b| {
                             let ob_index = b.get_ob_index(from_ob_var_name).unwrap();
+                            // Objects without any relationships have no entry
                             ocel.get_symmetric_rels_ob(ob_index)
-                                .unwrap()
-                                .iter()
+                                .into_iter()
+                                .flatten()
                                 .filter_map(move |(to_index, rev, qual)| {
                                     if let EventOrObjectIndex::Object(to_ob_index) = to_index {
                                         if rev == reversed
@@ -188,9 +190,10 @@ impl BindingBox {
                             let ob_index = b.get_ob_index(from_ob_var_name).unwrap();
                             // let ob = ocel.ob_by_index(ob_index).unwrap();
                             let ev_types = self.new_event_vars.get(ev_var_name).unwrap();
+                            // Objects without any relationships have no entry
                             ocel.get_symmetric_rels_ob(ob_index)
-                                .unwrap()
-                                .iter()
+                                .into_iter()
+                                .flatten()
                                 .filter_map(move |(rel_to, _reversed, q)| {
                                     if qualifier.is_none()
                                         || qualifier.as_ref().unwrap().contains(q)
@@ -218,6 +221,54 @@ impl BindingBox {
                         })
                         .collect();
                 }
+                BindingStep::BindEvFromAttributeIndex(ev_var, attr_name, value_filter) => {
+                    let ev_types = self.new_event_vars.get(ev_var).unwrap();
+                    let candidates = ev_types
+                        .iter()
+                        .flat_map(|ev_type| {
+                            ocel.event_attribute_index(ev_type, attr_name)
+                                .unwrap()
+                                .lookup(value_filter)
+                        })
+                        .collect_vec();
+                    ret = ret
+                        .into_par_iter()
+                        .flat_map_iter(|b| {
+                            candidates.iter().filter_map(move |e_index| {
+                                check_next_filters(
+                                    b.clone().expand_with_ev(*ev_var, *e_index),
+                                    step_index + 1,
+                                    steps,
+                                    ocel,
+                                )
+                            })
+                        })
+                        .collect();
+                }
+                BindingStep::BindObFromAttributeIndex(ob_var, attr_name, value_filter) => {
+                    let ob_types = self.new_object_vars.get(ob_var).unwrap();
+                    let candidates = ob_types
+                        .iter()
+                        .flat_map(|ob_type| {
+                            ocel.object_attribute_index(ob_type, attr_name)
+                                .unwrap()
+                                .lookup(value_filter)
+                        })
+                        .collect_vec();
+                    ret = ret
+                        .into_par_iter()
+                        .flat_map_iter(|b| {
+                            candidates.iter().filter_map(move |o_index| {
+                                check_next_filters(
+                                    b.clone().expand_with_ob(*ob_var, *o_index),
+                                    step_index + 1,
+                                    steps,
+                                    ocel,
+                                )
+                            })
+                        })
+                        .collect();
+                }
                 // _ => {}
                 BindingStep::Filter(f) => {
                     ret = ret
@@ -4,6 +4,14 @@ pub mod step_order;
 
 pub mod expand_step;
 
+pub mod sqlite;
+
+pub mod sampling;
+
+pub mod trends;
+
+pub mod dsl;
+
 #[cfg(test)]
 pub mod test;
 
@@ -17,6 +25,8 @@ use ts_rs::TS;
 
 use crate::preprocessing::linked_ocel::IndexLinkedOCEL;
 
+use self::sampling::{evaluate_sampled, EvaluationSamplingOptions, SampledEvaluation};
+
 #[derive(TS)]
 #[ts(export, export_to = "../../../frontend/src/types/generated/")]
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
@@ -25,6 +35,9 @@ pub struct EvaluateBoxTreeResult {
     pub evaluation_results: Vec<EvaluationResultWithCount>,
     pub object_ids: Vec<String>,
     pub event_ids: Vec<String>,
+    /// Only set for approximate evaluations (see [`sampling::evaluate_sampled`])
+    /// In that case, `evaluation_results` only contains the sampled situations
+    pub sampled_evaluation: Option<SampledEvaluation>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -33,6 +46,8 @@ pub struct EvaluateBoxTreeResult {
 pub struct CheckWithBoxTreeRequest {
     pub tree: BindingBoxTree,
     pub measure_performance: Option<bool>,
+    /// If set, only a sample of the root bindings is evaluated
+    pub sampling: Option<EvaluationSamplingOptions>,
 }
 
 #[derive(TS)]
@@ -49,6 +64,7 @@ pub fn evaluate_box_tree(
     tree: BindingBoxTree,
     ocel: &IndexLinkedOCEL,
     measure_performance: bool,
+    sampling: Option<EvaluationSamplingOptions>,
 ) -> EvaluateBoxTreeResult {
     if measure_performance {
         let n = 10;
@@ -72,7 +88,13 @@ pub fn evaluate_box_tree(
         println!("Evaluation time: {eval_times:?}");
     }
     let now = Instant::now();
-    let evaluation_results_flat = tree.evaluate(ocel);
+    let (evaluation_results_flat, sampled_evaluation) = match sampling {
+        Some(options) => {
+            let (res, sampled) = evaluate_sampled(&tree, ocel, &options);
+            (res, Some(sampled))
+        }
+        None => (tree.evaluate(ocel), None),
+    };
     println!("Tree Evaluated in {:?}", now.elapsed());
     let mut evaluation_results = tree
         .nodes
@@ -101,5 +123,6 @@ pub fn evaluate_box_tree(
         evaluation_results,
         object_ids: ocel.ocel.objects.iter().map(|o| o.id.clone()).collect(),
         event_ids: ocel.ocel.events.iter().map(|o| o.id.clone()).collect(),
+        sampled_evaluation,
     }
 }
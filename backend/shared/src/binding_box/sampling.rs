@@ -0,0 +1,203 @@
+use rand::{rngs::StdRng, seq::IteratorRandom, SeedableRng};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{discovery::RNG_SEED, preprocessing::linked_ocel::IndexLinkedOCEL};
+
+use super::structs::{BindingBoxTree, EvaluationResults};
+
+/// z-value for 95% confidence intervals
+const CONFIDENCE_Z: f64 = 1.96;
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluationSamplingOptions {
+    /// Fraction of root node bindings to evaluate (between 0 and 1)
+    pub fraction: f32,
+    /// Seed for selecting the sample (defaults to [`RNG_SEED`])
+    #[ts(as = "Option<i32>")]
+    pub seed: Option<u64>,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CountEstimate {
+    pub estimate: f64,
+    /// Lower bound of the 95% confidence interval
+    pub lower: f64,
+    /// Upper bound of the 95% confidence interval
+    pub upper: f64,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeEvaluationEstimate {
+    pub situation_count: CountEstimate,
+    pub situation_violated_count: CountEstimate,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SampledEvaluation {
+    pub num_root_bindings: usize,
+    pub num_sampled_root_bindings: usize,
+    /// Estimated counts for all nodes of the tree (by node index)
+    pub node_estimates: Vec<NodeEvaluationEstimate>,
+}
+
+///
+/// Evaluate the tree only for a seeded random sample of the bindings of the root node
+///
+/// Returns the evaluation results of the sampled root bindings (and their children),
+/// together with estimated counts for the full evaluation.
+///
+/// Counts are estimated as the number of root bindings times the mean count per sampled root binding.
+/// For the root node, the confidence interval of the violated count is the Wilson score interval,
+/// otherwise the normal approximation is used.
+/// Both include the finite population correction, so that evaluating all root bindings yields exact counts.
+///
+pub fn evaluate_sampled(
+    tree: &BindingBoxTree,
+    ocel: &IndexLinkedOCEL,
+    options: &EvaluationSamplingOptions,
+) -> (EvaluationResults, SampledEvaluation) {
+    let root = match tree.nodes.first() {
+        Some(root) => root,
+        None => return (vec![], SampledEvaluation::default()),
+    };
+    let root_bindings = root.expand(Default::default(), ocel);
+    let num_root_bindings = root_bindings.len();
+    let sample_count = ((num_root_bindings as f32 * options.fraction.clamp(0.0, 1.0)).ceil()
+        as usize)
+        .clamp(num_root_bindings.min(1), num_root_bindings);
+    let mut rng = StdRng::seed_from_u64(options.seed.unwrap_or(RNG_SEED));
+    let sample = root_bindings
+        .into_iter()
+        .choose_multiple(&mut rng, sample_count);
+
+    // Evaluate each sampled root binding separately, to get the counts per root binding
+    let per_root_results: Vec<EvaluationResults> = sample
+        .into_par_iter()
+        .map(|b| root.evaluate_expanded(0, vec![b], tree, ocel).0)
+        .collect();
+    let num_nodes = tree.nodes.len();
+    let counts_per_root: Vec<Vec<(usize, usize)>> = per_root_results
+        .iter()
+        .map(|res| {
+            let mut counts = vec![(0, 0); num_nodes];
+            for (index, _binding, viol) in res {
+                counts[*index].0 += 1;
+                if viol.is_some() {
+                    counts[*index].1 += 1;
+                }
+            }
+            counts
+        })
+        .collect();
+    let node_estimates = (0..num_nodes)
+        .map(|node_index| {
+            let situations = counts_per_root
+                .iter()
+                .map(|c| c[node_index].0)
+                .collect::<Vec<_>>();
+            let violations = counts_per_root
+                .iter()
+                .map(|c| c[node_index].1)
+                .collect::<Vec<_>>();
+            NodeEvaluationEstimate {
+                situation_count: estimate_total(&situations, num_root_bindings),
+                situation_violated_count: if node_index == 0 {
+                    estimate_proportion_total(&violations, num_root_bindings)
+                } else {
+                    estimate_total(&violations, num_root_bindings)
+                },
+            }
+        })
+        .collect();
+    (
+        per_root_results.into_iter().flatten().collect(),
+        SampledEvaluation {
+            num_root_bindings,
+            num_sampled_root_bindings: sample_count,
+            node_estimates,
+        },
+    )
+}
+
+/// Finite population correction factor for sampling `n` out of `population` without replacement
+fn finite_population_correction(n: usize, population: usize) -> f64 {
+    if population <= 1 {
+        0.0
+    } else {
+        ((population - n) as f64 / (population - 1) as f64).sqrt()
+    }
+}
+
+/// Estimate the population total from the counts of the sampled units (normal approximation)
+fn estimate_total(sample: &[usize], population: usize) -> CountEstimate {
+    let n = sample.len();
+    if n == 0 {
+        return CountEstimate::default();
+    }
+    let observed: usize = sample.iter().sum();
+    let mean = observed as f64 / n as f64;
+    let variance = if n > 1 {
+        sample
+            .iter()
+            .map(|x| (*x as f64 - mean).powi(2))
+            .sum::<f64>()
+            / (n - 1) as f64
+    } else {
+        0.0
+    };
+    let half_width = CONFIDENCE_Z
+        * (variance / n as f64).sqrt()
+        * finite_population_correction(n, population)
+        * population as f64;
+    let estimate = mean * population as f64;
+    CountEstimate {
+        estimate,
+        lower: (estimate - half_width).max(observed as f64),
+        upper: estimate + half_width,
+    }
+}
+
+/// Estimate the population total of 0/1 counts of the sampled units (Wilson score interval)
+fn estimate_proportion_total(sample: &[usize], population: usize) -> CountEstimate {
+    let n = sample.len();
+    if n == 0 {
+        return CountEstimate::default();
+    }
+    let observed = sample.iter().filter(|x| **x > 0).count();
+    let p = observed as f64 / n as f64;
+    let fpc = finite_population_correction(n, population);
+    let (lower, upper) = if fpc == 0.0 {
+        (p, p)
+    } else {
+        // Effective sample size, incorporating the finite population correction
+        wilson_score_interval(p, n as f64 / (fpc * fpc))
+    };
+    CountEstimate {
+        estimate: p * population as f64,
+        lower: (lower * population as f64).max(observed as f64),
+        upper: upper * population as f64,
+    }
+}
+
+/// 95% Wilson score interval of the proportion `p` observed in a sample of size `n`
+pub(crate) fn wilson_score_interval(p: f64, n: f64) -> (f64, f64) {
+    let z2 = CONFIDENCE_Z * CONFIDENCE_Z;
+    let denominator = 1.0 + z2 / n;
+    let center = (p + z2 / (2.0 * n)) / denominator;
+    let half_width = CONFIDENCE_Z * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / denominator;
+    (center - half_width, center + half_width)
+}
@@ -0,0 +1,1069 @@
+//! Evaluation of binding boxes directly against an OCEL 2.0 SQLite database
+//!
+//! Instead of importing the whole OCEL into memory, the binding steps of each binding box
+//! (see [`BindingStep::get_binding_order`]) are compiled into one SQL query over the standard OCEL 2.0 SQLite schema.
+//! Bindings are streamed back row by row, and refer to events/objects by their IDs (see [`IdBinding`]).
+//!
+//! CEL filters and CEL size filters are not supported.
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Display,
+    path::Path,
+};
+
+use itertools::Itertools;
+use rusqlite::{types::Value, Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::preprocessing::linked_ocel::IndexLinkedOCEL;
+
+use super::structs::{
+    Binding, BindingBox, BindingBoxTree, BindingBoxTreeNode, BindingStep, Constraint,
+    EventVariable, Filter, ObjectValueFilterTimepoint, ObjectVariable, SizeFilter, ValueFilter,
+    Variable, ViolationReason,
+};
+
+const UNNAMED: &str = "UNNAMED - ";
+
+#[derive(Debug)]
+pub enum SqliteEvaluationError {
+    Sqlite(rusqlite::Error),
+    Unsupported(String),
+}
+
+impl Display for SqliteEvaluationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SqliteEvaluationError::Sqlite(e) => write!(f, "SQLite error: {e}"),
+            SqliteEvaluationError::Unsupported(s) => write!(f, "Unsupported: {s}"),
+        }
+    }
+}
+
+impl std::error::Error for SqliteEvaluationError {}
+
+impl From<rusqlite::Error> for SqliteEvaluationError {
+    fn from(value: rusqlite::Error) -> Self {
+        SqliteEvaluationError::Sqlite(value)
+    }
+}
+
+///
+/// Binding of variables to event/object IDs
+///
+/// Counterpart of [`Binding`] for evaluations which do not have an [`IndexLinkedOCEL`]
+///
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdBinding {
+    pub event_map: BTreeMap<EventVariable, String>,
+    pub object_map: BTreeMap<ObjectVariable, String>,
+}
+
+impl IdBinding {
+    pub fn get_any_id(&self, var: &Variable) -> Option<&String> {
+        match var {
+            Variable::Event(ev_var) => self.event_map.get(ev_var),
+            Variable::Object(ob_var) => self.object_map.get(ob_var),
+        }
+    }
+
+    /// Convert to an index-based [`Binding`] for the given (in-memory) OCEL, e.g., to cross-validate results
+    ///
+    /// Returns `None` if any of the bound IDs does not exist in `ocel`
+    pub fn to_index_binding(&self, ocel: &IndexLinkedOCEL) -> Option<Binding> {
+        let mut binding = Binding::default();
+        for (ev_var, ev_id) in &self.event_map {
+            binding = binding.expand_with_ev(*ev_var, *ocel.index_of_ev(ev_id)?);
+        }
+        for (ob_var, ob_id) in &self.object_map {
+            binding = binding.expand_with_ob(*ob_var, *ocel.index_of_ob(ob_id)?);
+        }
+        Some(binding)
+    }
+}
+
+impl Binding {
+    /// Convert to an ID-based [`IdBinding`]
+    pub fn to_id_binding(&self, ocel: &IndexLinkedOCEL) -> IdBinding {
+        IdBinding {
+            event_map: self
+                .event_map
+                .iter()
+                .map(|(ev_var, ev_index)| (*ev_var, ocel.ev_by_index(ev_index).unwrap().id.clone()))
+                .collect(),
+            object_map: self
+                .object_map
+                .iter()
+                .map(|(ob_var, ob_index)| (*ob_var, ocel.ob_by_index(ob_index).unwrap().id.clone()))
+                .collect(),
+        }
+    }
+}
+
+pub type IdEvaluationResult = (usize, IdBinding, Option<ViolationReason>);
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SqliteEvaluateBoxTreeResult {
+    pub evaluation_results: Vec<SqliteEvaluationResultWithCount>,
+}
+
+/// Only violated situations are kept, as the number of all situations can be very large
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SqliteEvaluationResultWithCount {
+    pub violated_situations: Vec<(IdBinding, ViolationReason)>,
+    pub situation_count: usize,
+    pub situation_violated_count: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckSqliteWithBoxTreeRequest {
+    pub ocel_path: String,
+    pub tree: BindingBoxTree,
+}
+
+/// Evaluate a [`BindingBoxTree`] on the OCEL 2.0 SQLite file at `path`, without importing it into memory
+pub fn evaluate_box_tree_sqlite<P: AsRef<Path>>(
+    tree: &BindingBoxTree,
+    path: P,
+) -> Result<SqliteEvaluateBoxTreeResult, SqliteEvaluationError> {
+    let ocel = SqliteOCEL::open(path)?;
+    let mut evaluation_results: Vec<SqliteEvaluationResultWithCount> = tree
+        .nodes
+        .iter()
+        .map(|_| SqliteEvaluationResultWithCount::default())
+        .collect();
+    ocel.evaluate_box_tree(tree, |node_index, binding, violation| {
+        let res = &mut evaluation_results[node_index];
+        res.situation_count += 1;
+        if let Some(violation) = violation {
+            res.situation_violated_count += 1;
+            res.violated_situations.push((binding, violation));
+        }
+    })?;
+    Ok(SqliteEvaluateBoxTreeResult { evaluation_results })
+}
+
+///
+/// OCEL 2.0 SQLite database
+///
+/// Only the type and column metadata is read upfront
+///
+pub struct SqliteOCEL {
+    con: Connection,
+    /// Maps event types to the name of their event attribute table
+    event_type_tables: HashMap<String, String>,
+    /// Maps object types to the name of their object attribute table
+    object_type_tables: HashMap<String, String>,
+    /// Maps table names to their columns (and declared column types)
+    table_columns: HashMap<String, HashMap<String, String>>,
+}
+
+impl SqliteOCEL {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, SqliteEvaluationError> {
+        let con = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        Self::from_connection(con)
+    }
+
+    pub fn from_connection(con: Connection) -> Result<Self, SqliteEvaluationError> {
+        let event_type_tables = get_type_tables(&con, "event")?;
+        let object_type_tables = get_type_tables(&con, "object")?;
+        let mut table_columns = HashMap::new();
+        for table in event_type_tables
+            .values()
+            .chain(object_type_tables.values())
+        {
+            let mut s = con.prepare(&format!("PRAGMA table_info({})", quote_ident(table)))?;
+            let columns = s
+                .query_map([], |r| {
+                    Ok((
+                        r.get::<_, String>("name")?,
+                        r.get::<_, String>("type")?.to_uppercase(),
+                    ))
+                })?
+                .collect::<Result<HashMap<_, _>, _>>()?;
+            table_columns.insert(table.clone(), columns);
+        }
+        Ok(Self {
+            con,
+            event_type_tables,
+            object_type_tables,
+            table_columns,
+        })
+    }
+
+    ///
+    /// Stream all bindings of a binding box (without parent binding), calling `f` for each of them
+    ///
+    /// Returns the number of bindings
+    ///
+    pub fn for_each_binding<F: FnMut(IdBinding)>(
+        &self,
+        bbox: &BindingBox,
+        mut f: F,
+    ) -> Result<usize, SqliteEvaluationError> {
+        let var_types = get_var_types(bbox, &HashMap::new());
+        let compiled = CompiledBox::compile(self, bbox, &var_types)?;
+        let mut count = 0;
+        self.query_box(&compiled, &IdBinding::default(), |binding, _| {
+            count += 1;
+            f(binding);
+            Ok(())
+        })?;
+        Ok(count)
+    }
+
+    ///
+    /// Evaluate a [`BindingBoxTree`], calling `f` with each evaluation result (node index, binding and violation)
+    ///
+    /// Results are streamed per binding of the root node, mirroring [`BindingBoxTree::evaluate`].
+    ///
+    pub fn evaluate_box_tree<F: FnMut(usize, IdBinding, Option<ViolationReason>)>(
+        &self,
+        tree: &BindingBoxTree,
+        mut f: F,
+    ) -> Result<(), SqliteEvaluationError> {
+        if tree.nodes.is_empty() {
+            return Ok(());
+        }
+        let mut nodes: Vec<Option<CompiledNode>> = tree.nodes.iter().map(|_| None).collect();
+        self.compile_node(tree, 0, &HashMap::new(), &mut nodes)?;
+        self.evaluate_node(tree, &nodes, 0, &IdBinding::default(), &mut |_, _, res| {
+            for (node_index, binding, violation) in res {
+                f(node_index, binding, violation);
+            }
+            Ok(())
+        })
+    }
+
+    fn compile_node(
+        &self,
+        tree: &BindingBoxTree,
+        index: usize,
+        parent_var_types: &HashMap<Variable, HashSet<String>>,
+        nodes: &mut Vec<Option<CompiledNode>>,
+    ) -> Result<(), SqliteEvaluationError> {
+        let (bbox, children) = match tree.nodes[index].clone() {
+            BindingBoxTreeNode::Box(b, cs) => (b, cs),
+            x => x.to_box(),
+        };
+        let var_types = get_var_types(&bbox, parent_var_types);
+        let compiled = CompiledBox::compile(self, &bbox, &var_types)?;
+        for c in &children {
+            self.compile_node(tree, *c, &var_types, nodes)?;
+        }
+        nodes[index] = Some(CompiledNode {
+            bbox,
+            children,
+            compiled,
+        });
+        Ok(())
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn evaluate_node(
+        &self,
+        tree: &BindingBoxTree,
+        nodes: &[Option<CompiledNode>],
+        index: usize,
+        parent_binding: &IdBinding,
+        f: &mut dyn FnMut(
+            IdBinding,
+            Option<ViolationReason>,
+            Vec<IdEvaluationResult>,
+        ) -> Result<(), SqliteEvaluationError>,
+    ) -> Result<(), SqliteEvaluationError> {
+        let node = nodes[index].as_ref().unwrap();
+        self.query_box(&node.compiled, parent_binding, |b, constraint_filters| {
+            let mut all_res: Vec<IdEvaluationResult> = Vec::new();
+            let mut child_res: HashMap<String, Vec<(IdBinding, Option<ViolationReason>)>> =
+                HashMap::new();
+            for c in &node.children {
+                let c_name = tree
+                    .edge_names
+                    .get(&(index, *c))
+                    .cloned()
+                    .unwrap_or(format!("{UNNAMED}{c}"));
+                let mut c_bindings = Vec::new();
+                self.evaluate_node(tree, nodes, *c, &b, &mut |c_b, c_viol, c_res| {
+                    all_res.extend(c_res);
+                    c_bindings.push((c_b, c_viol));
+                    Ok(())
+                })?;
+                child_res.insert(c_name, c_bindings);
+            }
+            for sf in &node.bbox.size_filters {
+                if !check_size_filter(sf, &child_res) {
+                    return Ok(());
+                }
+            }
+            let viol = get_violation(&node.bbox, &constraint_filters, &child_res);
+            all_res.push((index, b.clone(), viol));
+            f(b, viol, all_res)
+        })
+    }
+
+    /// Run the compiled query of a binding box for the given parent binding
+    ///
+    /// `f` is called with each binding and the values of the [`Constraint::Filter`]s of the box (by constraint index)
+    fn query_box<F: FnMut(IdBinding, HashMap<usize, bool>) -> Result<(), SqliteEvaluationError>>(
+        &self,
+        compiled: &CompiledBox,
+        parent_binding: &IdBinding,
+        mut f: F,
+    ) -> Result<(), SqliteEvaluationError> {
+        let mut stmt = self.con.prepare_cached(&compiled.sql)?;
+        let parent_params = compiled
+            .parent_vars
+            .iter()
+            .map(|var| {
+                parent_binding
+                    .get_any_id(var)
+                    .map(|id| Value::Text(id.clone()))
+                    .ok_or_else(|| {
+                        SqliteEvaluationError::Unsupported(format!("Unbound variable {var:?}"))
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let param_count = stmt.parameter_count();
+        for (i, param) in parent_params
+            .into_iter()
+            .chain(compiled.params.iter().cloned())
+            .enumerate()
+            .take(param_count)
+        {
+            stmt.raw_bind_parameter(i + 1, param)?;
+        }
+        let mut rows = stmt.raw_query();
+        while let Some(row) = rows.next()? {
+            let mut b = parent_binding.clone();
+            let mut col = 0;
+            for ev_var in &compiled.new_event_vars {
+                b.event_map.insert(*ev_var, row.get(col)?);
+                col += 1;
+            }
+            for ob_var in &compiled.new_object_vars {
+                b.object_map.insert(*ob_var, row.get(col)?);
+                col += 1;
+            }
+            let mut constraint_filters = HashMap::new();
+            for constr_index in &compiled.constraint_filters {
+                constraint_filters.insert(*constr_index, row.get::<_, bool>(col)?);
+                col += 1;
+            }
+            f(b, constraint_filters)?;
+        }
+        Ok(())
+    }
+}
+
+struct CompiledNode {
+    bbox: BindingBox,
+    children: Vec<usize>,
+    compiled: CompiledBox,
+}
+
+///
+/// SQL query binding the new variables of a binding box
+///
+/// Parameters `?1`...`?n` are the IDs of the (used) variables bound by the parent binding,
+/// followed by the static parameters of the query.
+/// The selected columns are the IDs of the new event variables, then the IDs of the new object variables,
+/// and finally the results of the [`Constraint::Filter`]s of the box.
+///
+#[derive(Debug)]
+struct CompiledBox {
+    sql: String,
+    parent_vars: Vec<Variable>,
+    params: Vec<Value>,
+    new_event_vars: Vec<EventVariable>,
+    new_object_vars: Vec<ObjectVariable>,
+    constraint_filters: Vec<usize>,
+}
+
+impl CompiledBox {
+    fn compile(
+        ocel: &SqliteOCEL,
+        bbox: &BindingBox,
+        var_types: &HashMap<Variable, HashSet<String>>,
+    ) -> Result<Self, SqliteEvaluationError> {
+        check_supported(bbox)?;
+        let new_event_vars = bbox.new_event_vars.keys().copied().sorted().collect_vec();
+        let new_object_vars = bbox.new_object_vars.keys().copied().sorted().collect_vec();
+        let new_vars: HashSet<Variable> = new_event_vars
+            .iter()
+            .map(|v| Variable::Event(*v))
+            .chain(new_object_vars.iter().map(|v| Variable::Object(*v)))
+            .collect();
+        let parent_vars = var_types
+            .keys()
+            .filter(|v| !new_vars.contains(v))
+            .cloned()
+            .sorted_by_key(|v| match v {
+                Variable::Event(ev_var) => (0, ev_var.0),
+                Variable::Object(ob_var) => (1, ob_var.0),
+            })
+            .collect_vec();
+        let mut compiler = BoxCompiler {
+            ocel,
+            var_types,
+            parent_vars: &parent_vars,
+            params: Vec::new(),
+        };
+
+        // Planning only depends on which variables are bound, not on the bound values
+        let steps = BindingStep::get_binding_order(bbox, None, None);
+        let mut from = vec!["(SELECT 1) AS root".to_string()];
+        let mut conditions = Vec::new();
+        for (step_index, step) in steps.iter().enumerate() {
+            match step {
+                BindingStep::BindEv(ev_var, time_constr) => {
+                    from.push(format!("event AS {}", alias(&Variable::Event(*ev_var))));
+                    conditions.push(compiler.type_condition(&Variable::Event(*ev_var)));
+                    for (ref_ev_var, (min_sec, max_sec)) in time_constr.iter().flatten() {
+                        conditions.push(
+                            compiler.time_between_condition(ref_ev_var, ev_var, min_sec, max_sec),
+                        );
+                    }
+                }
+                BindingStep::BindOb(ob_var) => {
+                    from.push(format!("object AS {}", alias(&Variable::Object(*ob_var))));
+                    conditions.push(compiler.type_condition(&Variable::Object(*ob_var)));
+                }
+                BindingStep::BindObFromEv(ob_var, from_ev_var, qualifier) => {
+                    let rel = format!("r{step_index}");
+                    let ob_alias = alias(&Variable::Object(*ob_var));
+                    from.push(format!("event_object AS {rel}"));
+                    from.push(format!("object AS {ob_alias}"));
+                    conditions.push(format!(
+                        "{rel}.ocel_event_id = {}",
+                        compiler.id(&Variable::Event(*from_ev_var))
+                    ));
+                    conditions.push(compiler.qualifier_condition(&rel, qualifier));
+                    conditions.push(format!("{ob_alias}.ocel_id = {rel}.ocel_object_id"));
+                    conditions.push(compiler.type_condition(&Variable::Object(*ob_var)));
+                }
+                BindingStep::BindObFromOb(ob_var, from_ob_var, qualifier, reversed) => {
+                    let rel = format!("r{step_index}");
+                    let ob_alias = alias(&Variable::Object(*ob_var));
+                    let (from_col, to_col) = if *reversed {
+                        ("ocel_target_id", "ocel_source_id")
+                    } else {
+                        ("ocel_source_id", "ocel_target_id")
+                    };
+                    from.push(format!("object_object AS {rel}"));
+                    from.push(format!("object AS {ob_alias}"));
+                    conditions.push(format!(
+                        "{rel}.{from_col} = {}",
+                        compiler.id(&Variable::Object(*from_ob_var))
+                    ));
+                    conditions.push(compiler.qualifier_condition(&rel, qualifier));
+                    conditions.push(format!("{ob_alias}.ocel_id = {rel}.{to_col}"));
+                    conditions.push(compiler.type_condition(&Variable::Object(*ob_var)));
+                }
+                BindingStep::BindEvFromOb(ev_var, from_ob_var, qualifier) => {
+                    let rel = format!("r{step_index}");
+                    let ev_alias = alias(&Variable::Event(*ev_var));
+                    from.push(format!("event_object AS {rel}"));
+                    from.push(format!("event AS {ev_alias}"));
+                    conditions.push(format!(
+                        "{rel}.ocel_object_id = {}",
+                        compiler.id(&Variable::Object(*from_ob_var))
+                    ));
+                    conditions.push(compiler.qualifier_condition(&rel, qualifier));
+                    conditions.push(format!("{ev_alias}.ocel_id = {rel}.ocel_event_id"));
+                    conditions.push(compiler.type_condition(&Variable::Event(*ev_var)));
+                }
+                BindingStep::BindEvFromAttributeIndex(ev_var, attribute_name, value_filter) => {
+                    from.push(format!("event AS {}", alias(&Variable::Event(*ev_var))));
+                    conditions.push(compiler.type_condition(&Variable::Event(*ev_var)));
+                    conditions.push(compiler.filter_condition(
+                        &Filter::EventAttributeValueFilter {
+                            event: *ev_var,
+                            attribute_name: attribute_name.clone(),
+                            value_filter: value_filter.clone(),
+                        },
+                    ));
+                }
+                BindingStep::BindObFromAttributeIndex(ob_var, attribute_name, value_filter) => {
+                    from.push(format!("object AS {}", alias(&Variable::Object(*ob_var))));
+                    conditions.push(compiler.type_condition(&Variable::Object(*ob_var)));
+                    conditions.push(compiler.filter_condition(
+                        &Filter::ObjectAttributeValueFilter {
+                            object: *ob_var,
+                            attribute_name: attribute_name.clone(),
+                            at_time: ObjectValueFilterTimepoint::Sometime,
+                            value_filter: value_filter.clone(),
+                        },
+                    ));
+                }
+                BindingStep::Filter(f) => conditions.push(compiler.filter_condition(f)),
+            }
+        }
+
+        let mut columns = new_event_vars
+            .iter()
+            .map(|v| format!("{}.ocel_id", alias(&Variable::Event(*v))))
+            .chain(
+                new_object_vars
+                    .iter()
+                    .map(|v| format!("{}.ocel_id", alias(&Variable::Object(*v)))),
+            )
+            .collect_vec();
+        let mut constraint_filters = Vec::new();
+        for (constr_index, constr) in bbox.constraints.iter().enumerate() {
+            if let Constraint::Filter { filter } = constr {
+                columns.push(format!("({})", compiler.filter_condition(filter)));
+                constraint_filters.push(constr_index);
+            }
+        }
+        if columns.is_empty() {
+            columns.push("1".to_string());
+        }
+        let mut sql = format!(
+            "SELECT {} FROM {}",
+            columns.join(", "),
+            from.join(" CROSS JOIN ")
+        );
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.iter().map(|c| format!("({c})")).join(" AND "));
+        }
+        let params = compiler.params;
+        Ok(Self {
+            sql,
+            parent_vars,
+            params,
+            new_event_vars,
+            new_object_vars,
+            constraint_filters,
+        })
+    }
+}
+
+struct BoxCompiler<'a> {
+    ocel: &'a SqliteOCEL,
+    var_types: &'a HashMap<Variable, HashSet<String>>,
+    parent_vars: &'a [Variable],
+    params: Vec<Value>,
+}
+
+impl BoxCompiler<'_> {
+    /// Add a static parameter, returning its placeholder
+    fn param(&mut self, value: Value) -> String {
+        self.params.push(value);
+        format!("?{}", self.parent_vars.len() + self.params.len())
+    }
+
+    /// SQL expression for the ID of the event/object bound to `var`
+    fn id(&self, var: &Variable) -> String {
+        match self.parent_vars.iter().position(|v| v == var) {
+            Some(i) => format!("?{}", i + 1),
+            None => format!("{}.ocel_id", alias(var)),
+        }
+    }
+
+    fn types(&self, var: &Variable) -> Vec<&String> {
+        self.var_types
+            .get(var)
+            .map(|types| types.iter().sorted().collect())
+            .unwrap_or_default()
+    }
+
+    /// Attribute tables of the types of `var`, which have a column for `attribute_name`
+    ///
+    /// Returns the table names with the declared type of the column
+    fn attribute_tables(&self, var: &Variable, attribute_name: &str) -> Vec<(String, String)> {
+        let type_tables = match var {
+            Variable::Event(_) => &self.ocel.event_type_tables,
+            Variable::Object(_) => &self.ocel.object_type_tables,
+        };
+        let column = clean_sql_name(attribute_name);
+        self.types(var)
+            .into_iter()
+            .filter_map(|t| type_tables.get(t))
+            .filter_map(|table| {
+                let declared_type = self.ocel.table_columns.get(table)?.get(&column)?;
+                Some((table.clone(), declared_type.clone()))
+            })
+            .collect()
+    }
+
+    fn type_condition(&mut self, var: &Variable) -> String {
+        let types = self.types(var).into_iter().cloned().collect_vec();
+        if types.is_empty() {
+            return "0".to_string();
+        }
+        let placeholders = types
+            .into_iter()
+            .map(|t| self.param(Value::Text(t)))
+            .join(", ");
+        format!("{}.ocel_type IN ({placeholders})", alias(var))
+    }
+
+    fn qualifier_condition(&mut self, rel: &str, qualifier: &Option<String>) -> String {
+        match qualifier {
+            Some(q) => format!(
+                "{rel}.ocel_qualifier = {}",
+                self.param(Value::Text(q.clone()))
+            ),
+            None => "1".to_string(),
+        }
+    }
+
+    /// SQL expression for the timestamp of the event bound to `ev_var`
+    fn event_time(&self, ev_var: &EventVariable) -> String {
+        let var = Variable::Event(*ev_var);
+        let id = self.id(&var);
+        let tables = self
+            .types(&var)
+            .into_iter()
+            .filter_map(|t| self.ocel.event_type_tables.get(t))
+            .map(|table| {
+                format!(
+                    "(SELECT ocel_time FROM {} WHERE ocel_id = {id})",
+                    quote_ident(table)
+                )
+            })
+            .collect_vec();
+        match tables.len() {
+            0 => "NULL".to_string(),
+            1 => tables[0].clone(),
+            _ => format!("COALESCE({})", tables.join(", ")),
+        }
+    }
+
+    fn time_between_condition(
+        &mut self,
+        from_event: &EventVariable,
+        to_event: &EventVariable,
+        min_seconds: &Option<f64>,
+        max_seconds: &Option<f64>,
+    ) -> String {
+        // Compare on millisecond precision, as the in-memory evaluation does
+        let duration_ms = format!(
+            "ROUND((julianday({}) - julianday({})) * 86400000.0)",
+            self.event_time(to_event),
+            self.event_time(from_event)
+        );
+        let mut conds = vec![format!("{duration_ms} IS NOT NULL")];
+        if let Some(min_sec) = min_seconds {
+            let p = self.param(Value::Real(min_sec * 1000.0));
+            conds.push(format!("{duration_ms} >= {p}"));
+        }
+        if let Some(max_sec) = max_seconds {
+            let p = self.param(Value::Real(max_sec * 1000.0));
+            conds.push(format!("{duration_ms} <= {p}"));
+        }
+        conds.join(" AND ")
+    }
+
+    fn range_conditions(
+        &mut self,
+        expr: &str,
+        min: Option<Value>,
+        max: Option<Value>,
+    ) -> Vec<String> {
+        let mut conds = Vec::new();
+        if let Some(min) = min {
+            let p = self.param(min);
+            conds.push(format!("{expr} >= {p}"));
+        }
+        if let Some(max) = max {
+            let p = self.param(max);
+            conds.push(format!("{expr} <= {p}"));
+        }
+        conds
+    }
+
+    ///
+    /// SQL condition checking a value filter for the column `col` (with the given declared type)
+    ///
+    /// Mirrors [`ValueFilter::check_value`], where the type of a value is determined by the declared column type
+    /// (as in the SQLite OCEL import)
+    ///
+    fn value_condition(
+        &mut self,
+        col: &str,
+        declared_type: &str,
+        value_filter: &ValueFilter,
+    ) -> String {
+        let conds = match (value_filter, declared_type) {
+            (ValueFilter::Float { min, max }, "REAL" | "INTEGER") => {
+                let mut conds = vec![format!("typeof({col}) IN ('integer', 'real')")];
+                conds.extend(self.range_conditions(
+                    col,
+                    min.map(Value::Real),
+                    max.map(Value::Real),
+                ));
+                conds
+            }
+            (ValueFilter::Integer { min, max }, "REAL" | "INTEGER") => {
+                let mut conds = vec![format!("typeof({col}) IN ('integer', 'real')")];
+                conds.extend(self.range_conditions(
+                    col,
+                    min.map(Value::Integer),
+                    max.map(Value::Integer),
+                ));
+                conds
+            }
+            (ValueFilter::Boolean { is_true }, "BOOLEAN") => vec![
+                format!("typeof({col}) = 'integer'"),
+                format!("({col} <> 0) = {}", if *is_true { 1 } else { 0 }),
+            ],
+            (ValueFilter::String { is_in }, t)
+                if !matches!(t, "REAL" | "INTEGER" | "BOOLEAN" | "TIMESTAMP") =>
+            {
+                if is_in.is_empty() {
+                    vec!["0".to_string()]
+                } else {
+                    let placeholders = is_in
+                        .iter()
+                        .map(|s| self.param(Value::Text(s.clone())))
+                        .join(", ");
+                    vec![
+                        format!("typeof({col}) = 'text'"),
+                        format!("{col} IN ({placeholders})"),
+                    ]
+                }
+            }
+            (ValueFilter::Time { from, to }, "TIMESTAMP") => {
+                let mut conds = vec![format!("julianday({col}) IS NOT NULL")];
+                // Both sides are compared as julian day numbers (i.e., independent of the timestamp format)
+                if let Some(from) = from {
+                    let p = self.param(Value::Text(from.to_rfc3339()));
+                    conds.push(format!("julianday({col}) >= julianday({p})"));
+                }
+                if let Some(to) = to {
+                    let p = self.param(Value::Text(to.to_rfc3339()));
+                    conds.push(format!("julianday({col}) <= julianday({p})"));
+                }
+                conds
+            }
+            _ => vec!["0".to_string()],
+        };
+        conds.join(" AND ")
+    }
+
+    fn filter_condition(&mut self, filter: &Filter) -> String {
+        match filter {
+            Filter::O2E {
+                object,
+                event,
+                qualifier,
+            } => {
+                let qualifier_cond = self.qualifier_condition("r", qualifier);
+                format!(
+                    "EXISTS (SELECT 1 FROM event_object AS r WHERE r.ocel_event_id = {} AND r.ocel_object_id = {} AND {qualifier_cond})",
+                    self.id(&Variable::Event(*event)),
+                    self.id(&Variable::Object(*object))
+                )
+            }
+            Filter::O2O {
+                object,
+                other_object,
+                qualifier,
+            } => {
+                let qualifier_cond = self.qualifier_condition("r", qualifier);
+                format!(
+                    "EXISTS (SELECT 1 FROM object_object AS r WHERE r.ocel_source_id = {} AND r.ocel_target_id = {} AND {qualifier_cond})",
+                    self.id(&Variable::Object(*object)),
+                    self.id(&Variable::Object(*other_object))
+                )
+            }
+            Filter::TimeBetweenEvents {
+                from_event,
+                to_event,
+                min_seconds,
+                max_seconds,
+            } => self.time_between_condition(from_event, to_event, min_seconds, max_seconds),
+            Filter::NotEqual { var_1, var_2 } => match (var_1, var_2) {
+                (Variable::Event(_), Variable::Event(_))
+                | (Variable::Object(_), Variable::Object(_)) => {
+                    format!("{} <> {}", self.id(var_1), self.id(var_2))
+                }
+                _ => "1".to_string(),
+            },
+            Filter::EventAttributeValueFilter {
+                event,
+                attribute_name,
+                value_filter,
+            } => {
+                let var = Variable::Event(*event);
+                let id = self.id(&var);
+                let col = format!("a.{}", quote_ident(&clean_sql_name(attribute_name)));
+                let tables = self
+                    .attribute_tables(&var, attribute_name)
+                    .into_iter()
+                    .map(|(table, declared_type)| {
+                        format!(
+                            "(SELECT {} FROM {} AS a WHERE a.ocel_id = {id})",
+                            self.value_condition(&col, &declared_type, value_filter),
+                            quote_ident(&table)
+                        )
+                    })
+                    .collect_vec();
+                if tables.is_empty() {
+                    "0".to_string()
+                } else {
+                    format!("COALESCE({}, 0)", tables.join(", "))
+                }
+            }
+            Filter::ObjectAttributeValueFilter {
+                object,
+                attribute_name,
+                at_time,
+                value_filter,
+            } => {
+                let var = Variable::Object(*object);
+                let id = self.id(&var);
+                let column = clean_sql_name(attribute_name);
+                let col = format!("a.{}", quote_ident(&column));
+                let column_param = self.param(Value::Text(column.clone()));
+                // Rows holding a value for the attribute: Either initial values or changes of that attribute
+                let row_cond = format!("a.ocel_id = {id} AND {col} IS NOT NULL AND (a.ocel_changed_field IS NULL OR a.ocel_changed_field = {column_param})");
+                let ev_time = match at_time {
+                    ObjectValueFilterTimepoint::AtEvent { event } => Some(self.event_time(event)),
+                    _ => None,
+                };
+                let tables = self.attribute_tables(&var, attribute_name);
+                let per_table = tables
+                    .into_iter()
+                    .map(|(table, declared_type)| {
+                        let value_cond = self.value_condition(&col, &declared_type, value_filter);
+                        let table = quote_ident(&table);
+                        match at_time {
+                            ObjectValueFilterTimepoint::Always => format!(
+                                "NOT EXISTS (SELECT 1 FROM {table} AS a WHERE {row_cond} AND NOT ({value_cond}))"
+                            ),
+                            ObjectValueFilterTimepoint::Sometime => format!(
+                                "EXISTS (SELECT 1 FROM {table} AS a WHERE {row_cond} AND {value_cond})"
+                            ),
+                            // Initial values are always before the event (their time is treated as UNIX epoch on import)
+                            ObjectValueFilterTimepoint::AtEvent { .. } => format!(
+                                "(SELECT {value_cond} FROM {table} AS a WHERE {row_cond} AND (a.ocel_changed_field IS NULL OR julianday(a.ocel_time) <= julianday({})) ORDER BY a.ocel_changed_field IS NOT NULL DESC, julianday(a.ocel_time) DESC LIMIT 1)",
+                                ev_time.as_ref().unwrap()
+                            ),
+                        }
+                    })
+                    .collect_vec();
+                match at_time {
+                    ObjectValueFilterTimepoint::Always if per_table.is_empty() => "1".to_string(),
+                    ObjectValueFilterTimepoint::Always => per_table.join(" AND "),
+                    _ if per_table.is_empty() => "0".to_string(),
+                    ObjectValueFilterTimepoint::Sometime => per_table.join(" OR "),
+                    ObjectValueFilterTimepoint::AtEvent { .. } => {
+                        format!("COALESCE({}, 0)", per_table.join(", "))
+                    }
+                }
+            }
+            // Rejected in `check_supported`
+            Filter::BasicFilterCEL { .. } => "0".to_string(),
+        }
+    }
+}
+
+fn check_supported(bbox: &BindingBox) -> Result<(), SqliteEvaluationError> {
+    let filters = bbox
+        .filters
+        .iter()
+        .chain(bbox.constraints.iter().filter_map(|c| match c {
+            Constraint::Filter { filter } => Some(filter),
+            _ => None,
+        }));
+    let size_filters = bbox
+        .size_filters
+        .iter()
+        .chain(bbox.constraints.iter().filter_map(|c| match c {
+            Constraint::SizeFilter { filter } => Some(filter),
+            _ => None,
+        }));
+    if filters
+        .into_iter()
+        .any(|f| matches!(f, Filter::BasicFilterCEL { .. }))
+        || size_filters
+            .into_iter()
+            .any(|sf| matches!(sf, SizeFilter::AdvancedCEL { .. }))
+    {
+        return Err(SqliteEvaluationError::Unsupported(
+            "CEL filters can not be evaluated on SQLite".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Types of all variables available in a binding box (bound by the parent binding or the box itself)
+fn get_var_types(
+    bbox: &BindingBox,
+    parent_var_types: &HashMap<Variable, HashSet<String>>,
+) -> HashMap<Variable, HashSet<String>> {
+    let mut var_types = parent_var_types.clone();
+    for (ev_var, types) in &bbox.new_event_vars {
+        var_types.insert(Variable::Event(*ev_var), types.clone());
+    }
+    for (ob_var, types) in &bbox.new_object_vars {
+        var_types.insert(Variable::Object(*ob_var), types.clone());
+    }
+    var_types
+}
+
+fn get_type_tables(
+    con: &Connection,
+    kind: &str,
+) -> Result<HashMap<String, String>, rusqlite::Error> {
+    let mut s = con.prepare(&format!(
+        "SELECT ocel_type, ocel_type_map FROM {kind}_map_type"
+    ))?;
+    let type_tables = s
+        .query_map([], |r| {
+            Ok((
+                r.get::<_, String>(0)?,
+                format!("{kind}_{}", r.get::<_, String>(1)?),
+            ))
+        })?
+        .collect();
+    type_tables
+}
+
+fn alias(var: &Variable) -> String {
+    match var {
+        Variable::Event(ev_var) => format!("e{}", ev_var.0),
+        Variable::Object(ob_var) => format!("o{}", ob_var.0),
+    }
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Attribute columns are named as in the SQLite OCEL export
+fn clean_sql_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c != '\'' && c != '\\' { c } else { '-' })
+        .collect()
+}
+
+/// Counterpart of [`SizeFilter::check`] for [`IdBinding`]s (CEL is not supported)
+fn check_size_filter(
+    size_filter: &SizeFilter,
+    child_res: &HashMap<String, Vec<(IdBinding, Option<ViolationReason>)>>,
+) -> bool {
+    let projection = |child_name: &String, var: &Variable| {
+        child_res.get(child_name).map(|c_res| {
+            c_res
+                .iter()
+                .map(|(b, _)| b.get_any_id(var))
+                .collect::<HashSet<_>>()
+        })
+    };
+    match size_filter {
+        SizeFilter::NumChilds {
+            child_name,
+            min,
+            max,
+        } => match child_res.get(child_name) {
+            Some(c_res) => {
+                !min.is_some_and(|min| c_res.len() < min)
+                    && !max.is_some_and(|max| c_res.len() > max)
+            }
+            None => false,
+        },
+        SizeFilter::NumChildsProj {
+            child_name,
+            var_name,
+            min,
+            max,
+        } => match projection(child_name, var_name) {
+            Some(set) => {
+                let len = set.into_iter().flatten().count();
+                !min.is_some_and(|min| len < min) && !max.is_some_and(|max| len > max)
+            }
+            None => false,
+        },
+        SizeFilter::BindingSetEqual { child_names } => {
+            let mut sets = child_names.iter().map(|c| {
+                child_res
+                    .get(c)
+                    .map(|c_res| c_res.iter().map(|(b, _)| b).collect::<HashSet<_>>())
+            });
+            match sets.next() {
+                None => true,
+                Some(None) => false,
+                Some(Some(set)) => sets.all(|set2| set2.is_some_and(|set2| set == set2)),
+            }
+        }
+        SizeFilter::BindingSetProjectionEqual {
+            child_name_with_var_name,
+        } => {
+            let mut sets = child_name_with_var_name
+                .iter()
+                .map(|(c, var)| projection(c, var));
+            match sets.next() {
+                None => true,
+                Some(None) => false,
+                Some(Some(set)) => sets.all(|set2| set2.is_some_and(|set2| set == set2)),
+            }
+        }
+        // Rejected in `check_supported`
+        SizeFilter::AdvancedCEL { .. } => false,
+    }
+}
+
+/// Check the constraints of a binding box, mirroring the in-memory evaluation
+fn get_violation(
+    bbox: &BindingBox,
+    constraint_filters: &HashMap<usize, bool>,
+    child_res: &HashMap<String, Vec<(IdBinding, Option<ViolationReason>)>>,
+) -> Option<ViolationReason> {
+    let all_sat = |child_name: &String| {
+        child_res
+            .get(child_name)
+            .map(|c_res| c_res.iter().all(|(_b, v)| v.is_none()))
+    };
+    let any_sat = |child_name: &String| {
+        child_res
+            .get(child_name)
+            .map(|c_res| c_res.iter().any(|(_b, v)| v.is_none()))
+    };
+    for (constr_index, constr) in bbox.constraints.iter().enumerate() {
+        let violated = match constr {
+            Constraint::Filter { .. } => !constraint_filters
+                .get(&constr_index)
+                .copied()
+                .unwrap_or_default(),
+            Constraint::SizeFilter { filter } => !check_size_filter(filter, child_res),
+            Constraint::SAT { child_names } => {
+                child_names.iter().any(|c| !all_sat(c).unwrap_or(false))
+            }
+            Constraint::ANY { child_names } => {
+                child_names.iter().any(|c| !any_sat(c).unwrap_or(false))
+            }
+            Constraint::NOT { child_names } => {
+                child_names.iter().all(|c| any_sat(c).unwrap_or(true))
+            }
+            Constraint::OR { child_names } => {
+                !child_names.iter().any(|c| all_sat(c).unwrap_or(true))
+            }
+            Constraint::AND { child_names } => {
+                !child_names.iter().all(|c| all_sat(c).unwrap_or(true))
+            }
+        };
+        if violated {
+            return Some(ViolationReason::ConstraintNotSatisfied(constr_index));
+        }
+    }
+    None
+}
@@ -5,7 +5,7 @@ use itertools::Itertools;
 use crate::{discovery::advanced::EventOrObjectType, preprocessing::linked_ocel::IndexLinkedOCEL};
 
 use super::{
-    structs::{BindingBox, BindingStep, Filter, Qualifier, Variable},
+    structs::{BindingBox, BindingStep, Filter, ObjectValueFilterTimepoint, Qualifier, Variable},
     Binding,
 };
 
@@ -15,10 +15,8 @@ pub fn get_expected_relation_count(
     parent_binding_opt: Option<&Binding>,
     ocel: Option<&IndexLinkedOCEL>,
 ) -> Option<f32> {
-    if ocel.is_none() {
-        eprintln!("NO OCEL?! for step order");
-        return None;
-    }
+    // Without an OCEL (e.g., when compiling to SQL) there are no statistics to estimate from
+    ocel?;
     let mut bound_by_types = Vec::new();
     // First check if bound_by is already bound by parent
     if let Some(bound_by_index) = parent_binding_opt.and_then(|b| b.get_any_index(bound_by)) {
@@ -58,6 +56,99 @@ pub fn get_expected_relation_count(
     // println!("{res} for {var:?} {bound_by:?}");
     Some(res)
 }
+/// Access path for binding a variable directly from an attribute value index
+pub struct AttributeIndexAccess {
+    /// Index of the value filter (in [`BindingBox::filters`]) used for the lookup
+    pub filter_index: usize,
+    /// Binding step using the attribute value index
+    pub step: BindingStep,
+    /// Upper bound on the number of candidates the index lookup yields
+    pub estimated_count: usize,
+    /// If the lookup exactly captures the filter (i.e., the filter does not need to be checked again)
+    pub exact: bool,
+}
+
+/// Get the most selective attribute value index access path for binding `var`, if any
+///
+/// Only value filters for which attribute value indices exist for _all_ types of the variable are considered
+pub fn get_attribute_index_access(
+    var: &Variable,
+    bbox: &BindingBox,
+    ocel: &IndexLinkedOCEL,
+) -> Option<AttributeIndexAccess> {
+    bbox.filters
+        .iter()
+        .enumerate()
+        .filter_map(|(filter_index, f)| match (f, var) {
+            (
+                Filter::EventAttributeValueFilter {
+                    event,
+                    attribute_name,
+                    value_filter,
+                },
+                Variable::Event(var_ev),
+            ) if event == var_ev => {
+                let estimated_count = bbox
+                    .new_event_vars
+                    .get(var_ev)?
+                    .iter()
+                    .map(|ev_type| {
+                        ocel.event_attribute_index(ev_type, attribute_name)
+                            .map(|index| index.estimate(value_filter))
+                    })
+                    .sum::<Option<usize>>()?;
+                Some(AttributeIndexAccess {
+                    filter_index,
+                    step: BindingStep::BindEvFromAttributeIndex(
+                        *var_ev,
+                        attribute_name.clone(),
+                        value_filter.clone(),
+                    ),
+                    estimated_count,
+                    exact: true,
+                })
+            }
+            (
+                Filter::ObjectAttributeValueFilter {
+                    object,
+                    attribute_name,
+                    at_time,
+                    value_filter,
+                },
+                Variable::Object(var_ob),
+            ) if object == var_ob => {
+                // Objects without any value for the attribute satisfy `Always`,
+                // so the index can only be used for `Sometime` (exact) and `AtEvent` (superset)
+                let exact = match at_time {
+                    ObjectValueFilterTimepoint::Sometime => true,
+                    ObjectValueFilterTimepoint::AtEvent { event: _ } => false,
+                    ObjectValueFilterTimepoint::Always => return None,
+                };
+                let estimated_count = bbox
+                    .new_object_vars
+                    .get(var_ob)?
+                    .iter()
+                    .map(|ob_type| {
+                        ocel.object_attribute_index(ob_type, attribute_name)
+                            .map(|index| index.estimate(value_filter))
+                    })
+                    .sum::<Option<usize>>()?;
+                Some(AttributeIndexAccess {
+                    filter_index,
+                    step: BindingStep::BindObFromAttributeIndex(
+                        *var_ob,
+                        attribute_name.clone(),
+                        value_filter.clone(),
+                    ),
+                    estimated_count,
+                    exact,
+                })
+            }
+            _ => None,
+        })
+        .min_by_key(|access| access.estimated_count)
+}
+
 impl BindingStep {
     /// Get a binding order from a binding box
     ///
@@ -232,7 +323,7 @@ impl BindingStep {
                 if bound_vars.contains(&var) {
                     continue;
                 }
-                if let Some((v, (_var, qualifier, filter_index, reversed))) = bound_vars
+                let relation_access = bound_vars
                     .iter()
                     .flat_map(|v| {
                         var_can_bind_with_qualifier
@@ -242,12 +333,38 @@ impl BindingStep {
                             .find(|(x, _q, _filter_index, _reversed)| x == &var)
                             .map(|t| (v, t))
                     })
-                    .sorted_by_cached_key(|(bound_by_var, (_v, _q, _filter_index, _reversed))| {
-                        get_expected_relation_count(bound_by_var, bbox, parent_binding_opt, ocel)
-                            .unwrap_or(10.0)
-                            .round() as usize
+                    .map(|(bound_by_var, t)| {
+                        let expected_count = get_expected_relation_count(
+                            bound_by_var,
+                            bbox,
+                            parent_binding_opt,
+                            ocel,
+                        )
+                        .unwrap_or(10.0);
+                        (bound_by_var, t, expected_count)
+                    })
+                    .sorted_by_key(|(_bound_by_var, _t, expected_count)| {
+                        expected_count.round() as usize
                     })
-                    .next()
+                    .next();
+                // Bind from an attribute value index if it is the most selective access path
+                // (the index is only consulted here for planning: its estimated candidate count is compared to the
+                // expected number of related candidates per partial binding)
+                let index_access = ocel
+                    .and_then(|ocel| get_attribute_index_access(&var, bbox, ocel))
+                    .filter(|access| match &relation_access {
+                        Some((_v, _t, expected_count)) => {
+                            (access.estimated_count as f32) < *expected_count
+                        }
+                        None => true,
+                    });
+                if let Some(access) = index_access {
+                    if access.exact {
+                        filter_indices_incoporated.insert(access.filter_index);
+                    }
+                    ret.push(access.step);
+                } else if let Some((v, (_var, qualifier, filter_index, reversed), _)) =
+                    relation_access
                 {
                     // `var` can be bound based on `v`!
                     filter_indices_incoporated.insert(*filter_index);
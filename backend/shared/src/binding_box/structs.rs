@@ -115,7 +115,7 @@ pub type NewEventVariables = HashMap<EventVariable, HashSet<String>>;
 
 #[derive(TS)]
 #[ts(export, export_to = "../../../frontend/src/types/generated/")]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BindingBox {
     pub new_event_vars: NewEventVariables,
@@ -128,7 +128,7 @@ pub struct BindingBox {
 #[derive(TS)]
 #[ts(export, export_to = "../../../frontend/src/types/generated/")]
 #[serde_as]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BindingBoxTree {
     pub nodes: Vec<BindingBoxTreeNode>,
@@ -176,7 +176,7 @@ impl BindingBoxTree {
 }
 #[derive(TS)]
 #[ts(export, export_to = "../../../frontend/src/types/generated/")]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum BindingBoxTreeNode {
     Box(BindingBox, Vec<usize>),
     OR(usize, usize),
@@ -262,6 +262,26 @@ impl BindingBoxTreeNode {
         parent_binding: Binding,
         tree: &BindingBoxTree,
         ocel: &IndexLinkedOCEL,
+    ) -> (EvaluationResults, Vec<(Binding, Option<ViolationReason>)>) {
+        let expanded: Vec<Binding> = self.expand(parent_binding, ocel);
+        self.evaluate_expanded(own_index, expanded, tree, ocel)
+    }
+
+    /// Get all bindings of this node for the given parent binding
+    pub fn expand(&self, parent_binding: Binding, ocel: &IndexLinkedOCEL) -> Vec<Binding> {
+        match self {
+            BindingBoxTreeNode::Box(bbox, _) => bbox.expand(parent_binding, ocel),
+            x => x.clone().to_box().0.expand(parent_binding, ocel),
+        }
+    }
+
+    /// Evaluate this node for the given (already expanded) bindings of it
+    pub fn evaluate_expanded(
+        &self,
+        own_index: usize,
+        expanded: Vec<Binding>,
+        tree: &BindingBoxTree,
+        ocel: &IndexLinkedOCEL,
     ) -> (EvaluationResults, Vec<(Binding, Option<ViolationReason>)>) {
         let (bbox, children) = match self.clone() {
             BindingBoxTreeNode::Box(b, cs) => (b, cs),
@@ -269,7 +289,6 @@ impl BindingBoxTreeNode {
         };
         // match self {
         //     BindingBoxTreeNode::Box(bbox, children) => {
-        let expanded: Vec<Binding> = bbox.expand(parent_binding.clone(), ocel);
         enum BindingResult {
             FilteredOutBySizeFilter(Binding, EvaluationResults),
             Sat(Binding, EvaluationResults),
@@ -565,7 +584,7 @@ impl BindingBoxTreeNode {
 
 #[derive(TS)]
 #[ts(export, export_to = "../../../frontend/src/types/generated/")]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum Filter {
     /// Object is associated with event (optionally through a qualifier)
@@ -728,7 +747,7 @@ impl Filter {
     }
 }
 
-#[derive(TS, Debug, Clone, Serialize, Deserialize)]
+#[derive(TS, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[ts(export, export_to = "../../../frontend/src/types/generated/")]
 #[serde(tag = "type")]
 
@@ -798,7 +817,7 @@ impl ValueFilter {
     }
 }
 
-#[derive(TS, Debug, Clone, Serialize, Deserialize)]
+#[derive(TS, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[ts(export, export_to = "../../../frontend/src/types/generated/")]
 #[serde(tag = "type")]
 pub enum ObjectValueFilterTimepoint {
@@ -809,7 +828,7 @@ pub enum ObjectValueFilterTimepoint {
 
 #[derive(TS)]
 #[ts(export, export_to = "../../../frontend/src/types/generated/")]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum SizeFilter {
     // The nth child should be between (min,max) interval, where None represent no bound
@@ -947,7 +966,7 @@ type NodeEdgeName = String;
 
 #[derive(TS)]
 #[ts(export, export_to = "../../../frontend/src/types/generated/")]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum Constraint {
     Filter { filter: Filter },
@@ -1026,6 +1045,10 @@ pub enum BindingStep {
     // bool: reversed?
     BindObFromOb(ObjectVariable, ObjectVariable, Qualifier, bool),
     BindEvFromOb(EventVariable, ObjectVariable, Qualifier),
+    /// Bind ev from the attribute value index (attribute name, value filter)
+    BindEvFromAttributeIndex(EventVariable, String, ValueFilter),
+    /// Bind ob from the attribute value index (attribute name, value filter)
+    BindObFromAttributeIndex(ObjectVariable, String, ValueFilter),
     Filter(Filter),
 }
 
@@ -315,3 +315,733 @@
 //         )
 //     }
 // }
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Duration, FixedOffset};
+use itertools::Itertools;
+use process_mining::{
+    ocel::ocel_struct::{
+        OCELAttributeValue, OCELEvent, OCELEventAttribute, OCELObject, OCELObjectAttribute,
+        OCELRelationship, OCELType, OCELTypeAttribute,
+    },
+    OCEL,
+};
+
+use crate::preprocessing::linked_ocel::{link_ocel_info, IndexLinkedOCEL};
+
+use super::{
+    structs::{Filter, ObjectValueFilterTimepoint, ValueFilter},
+    Binding, BindingBox, BindingStep,
+};
+
+pub(crate) fn ocel_type(name: &str, attributes: &[(&str, &str)]) -> OCELType {
+    OCELType {
+        name: name.to_string(),
+        attributes: attributes
+            .iter()
+            .map(|(name, value_type)| OCELTypeAttribute {
+                name: name.to_string(),
+                value_type: value_type.to_string(),
+            })
+            .collect(),
+    }
+}
+
+pub(crate) fn relationship(object_id: &str, qualifier: &str) -> OCELRelationship {
+    OCELRelationship {
+        object_id: object_id.to_string(),
+        qualifier: qualifier.to_string(),
+    }
+}
+
+pub(crate) fn example_start_time() -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339("2024-01-01T08:00:00+00:00").unwrap()
+}
+
+/// Small order management OCEL with integer, float, string and time attributes
+///
+/// * 8 orders (attributes `price`, `priority` and `label`), each with 1-3 items
+/// * One `place order`, `pick item` per item and (for even orders) `pay order` event per order
+/// * `place order` events have the attributes `amount`, `channel` and `due`
+pub(crate) fn example_ocel() -> OCEL {
+    let start = example_start_time();
+    let mut events = Vec::new();
+    let mut objects = Vec::new();
+    for i in 0..8_i64 {
+        let order = format!("o{i}");
+        let items = (0..(i % 3 + 1)).map(|j| format!("i{i}-{j}")).collect_vec();
+        let price = match i {
+            3 => f64::NAN,
+            _ => 10.0 * i as f64 + 0.5,
+        };
+        objects.push(OCELObject {
+            id: order.clone(),
+            object_type: "orders".to_string(),
+            attributes: vec![
+                OCELObjectAttribute {
+                    name: "price".to_string(),
+                    value: OCELAttributeValue::Float(price),
+                    time: start,
+                },
+                OCELObjectAttribute {
+                    name: "priority".to_string(),
+                    // Values beyond the exact integer range of f64
+                    value: OCELAttributeValue::Integer((1 << 53) + i),
+                    time: start,
+                },
+                OCELObjectAttribute {
+                    name: "label".to_string(),
+                    value: OCELAttributeValue::String(
+                        ["standard", "express", "bulk"][i as usize % 3].to_string(),
+                    ),
+                    time: start,
+                },
+            ],
+            relationships: items
+                .iter()
+                .map(|item| relationship(item, "contains"))
+                .collect(),
+        });
+        for item in &items {
+            objects.push(OCELObject {
+                id: item.clone(),
+                object_type: "items".to_string(),
+                attributes: Vec::new(),
+                relationships: Vec::new(),
+            });
+        }
+        let placed = start + Duration::hours(i);
+        events.push(OCELEvent {
+            id: format!("place-{i}"),
+            event_type: "place order".to_string(),
+            time: placed,
+            attributes: vec![
+                OCELEventAttribute {
+                    name: "amount".to_string(),
+                    value: OCELAttributeValue::Integer(items.len() as i64 * 5 - i),
+                },
+                OCELEventAttribute {
+                    name: "channel".to_string(),
+                    value: OCELAttributeValue::String(["web", "phone"][i as usize % 2].to_string()),
+                },
+                OCELEventAttribute {
+                    name: "due".to_string(),
+                    value: OCELAttributeValue::Time(placed + Duration::days(i % 4)),
+                },
+            ],
+            relationships: std::iter::once(relationship(&order, "order"))
+                .chain(items.iter().map(|item| relationship(item, "item")))
+                .collect(),
+        });
+        for (j, item) in items.iter().enumerate() {
+            events.push(OCELEvent {
+                id: format!("pick-{i}-{j}"),
+                event_type: "pick item".to_string(),
+                time: placed + Duration::minutes(10 * (j as i64 + 1)),
+                attributes: Vec::new(),
+                relationships: vec![relationship(item, "item"), relationship(&order, "order")],
+            });
+        }
+        if i % 2 == 0 {
+            events.push(OCELEvent {
+                id: format!("pay-{i}"),
+                event_type: "pay order".to_string(),
+                time: placed + Duration::hours(2),
+                attributes: Vec::new(),
+                relationships: vec![relationship(&order, "order")],
+            });
+        }
+    }
+    OCEL {
+        event_types: vec![
+            ocel_type(
+                "place order",
+                &[
+                    ("amount", "integer"),
+                    ("channel", "string"),
+                    ("due", "time"),
+                ],
+            ),
+            ocel_type("pick item", &[]),
+            ocel_type("pay order", &[]),
+        ],
+        object_types: vec![
+            ocel_type(
+                "orders",
+                &[
+                    ("price", "float"),
+                    ("priority", "integer"),
+                    ("label", "string"),
+                ],
+            ),
+            ocel_type("items", &[]),
+        ],
+        events,
+        objects,
+    }
+}
+
+pub(crate) fn example_linked_ocel() -> IndexLinkedOCEL {
+    link_ocel_info(example_ocel())
+}
+
+fn numeric_and_time_filters() -> Vec<ValueFilter> {
+    let start = example_start_time();
+    vec![
+        ValueFilter::Float {
+            min: Some(20.0),
+            max: Some(50.5),
+        },
+        ValueFilter::Float {
+            min: None,
+            max: Some(30.0),
+        },
+        ValueFilter::Float {
+            min: Some(f64::NAN),
+            max: Some(40.0),
+        },
+        ValueFilter::Float {
+            min: Some(100.0),
+            max: Some(0.0),
+        },
+        ValueFilter::Integer {
+            min: Some((1 << 53) + 3),
+            max: Some((1 << 53) + 4),
+        },
+        ValueFilter::Integer {
+            min: Some(1),
+            max: Some(40),
+        },
+        ValueFilter::Integer {
+            min: None,
+            max: Some(5),
+        },
+        ValueFilter::Time {
+            from: Some((start + Duration::days(1)).to_utc()),
+            to: Some((start + Duration::days(2) + Duration::hours(6)).to_utc()),
+        },
+        ValueFilter::Time {
+            from: None,
+            to: Some((start + Duration::hours(3)).to_utc()),
+        },
+        ValueFilter::String {
+            is_in: vec!["express".to_string(), "phone".to_string()],
+        },
+        ValueFilter::Boolean { is_true: true },
+    ]
+}
+
+#[test]
+fn attribute_index_lookup_matches_value_filter() {
+    let ocel = example_linked_ocel();
+    for value_filter in numeric_and_time_filters() {
+        for attr in ["price", "priority", "label"] {
+            let index = ocel.object_attribute_index("orders", attr).unwrap();
+            let expected: HashSet<_> = ocel.objects_of_type["orders"]
+                .iter()
+                .filter(|o| {
+                    ocel.ocel.objects[o.0]
+                        .attributes
+                        .iter()
+                        .any(|a| a.name == attr && value_filter.check_value(&a.value))
+                })
+                .collect();
+            let found = index.lookup(&value_filter);
+            assert_eq!(
+                found.iter().collect::<HashSet<_>>(),
+                expected,
+                "{attr} {value_filter:?}"
+            );
+            assert!(index.estimate(&value_filter) >= found.len());
+        }
+        for attr in ["amount", "channel", "due"] {
+            let index = ocel.event_attribute_index("place order", attr).unwrap();
+            let expected: HashSet<_> = ocel.events_of_type["place order"]
+                .iter()
+                .filter(|e| {
+                    ocel.ocel.events[e.0]
+                        .attributes
+                        .iter()
+                        .find(|a| a.name == attr)
+                        .is_some_and(|a| value_filter.check_value(&a.value))
+                })
+                .collect();
+            let found = index.lookup(&value_filter);
+            assert_eq!(
+                found.iter().collect::<HashSet<_>>(),
+                expected,
+                "{attr} {value_filter:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn attribute_indices_are_built_lazily() {
+    let ocel = example_linked_ocel();
+    assert!(ocel
+        .object_attribute_indices
+        .values()
+        .all(|cell| cell.get().is_none()));
+    assert!(ocel.object_attribute_index("orders", "price").is_some());
+    assert!(ocel.object_attribute_index("orders", "unknown").is_none());
+    assert_eq!(
+        ocel.object_attribute_indices
+            .values()
+            .filter(|cell| cell.get().is_some())
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn planner_binds_from_attribute_index() {
+    let ocel = example_linked_ocel();
+    let filters = [
+        Filter::ObjectAttributeValueFilter {
+            object: 0.into(),
+            attribute_name: "priority".to_string(),
+            at_time: ObjectValueFilterTimepoint::Sometime,
+            value_filter: ValueFilter::Integer {
+                min: Some((1 << 53) + 2),
+                max: Some((1 << 53) + 2),
+            },
+        },
+        Filter::ObjectAttributeValueFilter {
+            object: 0.into(),
+            attribute_name: "price".to_string(),
+            at_time: ObjectValueFilterTimepoint::Sometime,
+            value_filter: ValueFilter::Float {
+                min: Some(0.0),
+                max: Some(15.0),
+            },
+        },
+        Filter::EventAttributeValueFilter {
+            event: 0.into(),
+            attribute_name: "channel".to_string(),
+            value_filter: ValueFilter::String {
+                is_in: vec!["phone".to_string()],
+            },
+        },
+    ];
+    let sorted = |bindings: Vec<Binding>| {
+        bindings
+            .into_iter()
+            .map(|b| format!("{b:?}"))
+            .sorted()
+            .collect_vec()
+    };
+    for filter in filters {
+        let single_var_box = BindingBox {
+            new_event_vars: match filter {
+                Filter::EventAttributeValueFilter { .. } => {
+                    vec![(0.into(), HashSet::from(["place order".to_string()]))]
+                }
+                _ => vec![],
+            }
+            .into_iter()
+            .collect(),
+            new_object_vars: match filter {
+                Filter::ObjectAttributeValueFilter { .. } => {
+                    vec![(0.into(), HashSet::from(["orders".to_string()]))]
+                }
+                _ => vec![],
+            }
+            .into_iter()
+            .collect(),
+            filters: vec![filter.clone()],
+            size_filters: Vec::new(),
+            constraints: Vec::new(),
+        };
+        let related_box = BindingBox {
+            new_event_vars: vec![(0.into(), HashSet::from(["place order".to_string()]))]
+                .into_iter()
+                .collect(),
+            new_object_vars: vec![(0.into(), HashSet::from(["orders".to_string()]))]
+                .into_iter()
+                .collect(),
+            filters: vec![
+                filter,
+                Filter::O2E {
+                    object: 0.into(),
+                    event: 0.into(),
+                    qualifier: None,
+                },
+            ],
+            size_filters: Vec::new(),
+            constraints: Vec::new(),
+        };
+        let steps = BindingStep::get_binding_order(&single_var_box, None, Some(&ocel));
+        assert!(matches!(
+            steps.first(),
+            Some(
+                BindingStep::BindEvFromAttributeIndex(..)
+                    | BindingStep::BindObFromAttributeIndex(..)
+            )
+        ));
+        // The index lookup already checks the filter
+        assert_eq!(steps.len(), 1);
+        for bbox in [single_var_box, related_box] {
+            let without_index = BindingStep::get_binding_order(&bbox, None, None);
+            let expected =
+                sorted(bbox.expand_with_steps(Binding::default(), &ocel, &without_index));
+            assert!(!expected.is_empty());
+            assert_eq!(sorted(bbox.expand(Binding::default(), &ocel)), expected);
+        }
+    }
+}
+
+#[test]
+fn sqlite_evaluation_matches_in_memory_evaluation() {
+    use super::{
+        sqlite::SqliteOCEL,
+        structs::{BindingBoxTree, BindingBoxTreeNode, Constraint, SizeFilter},
+    };
+
+    let start = example_start_time();
+    let ocel = example_linked_ocel();
+    let path = std::env::temp_dir().join(format!(
+        "ocedeclare-sqlite-test-{}.sqlite",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    process_mining::export_ocel_sqlite_to_path(&path, &ocel.ocel).unwrap();
+
+    let tree = BindingBoxTree {
+        nodes: vec![
+            BindingBoxTreeNode::Box(
+                BindingBox {
+                    new_event_vars: Default::default(),
+                    new_object_vars: vec![(0.into(), HashSet::from(["orders".to_string()]))]
+                        .into_iter()
+                        .collect(),
+                    filters: vec![
+                        Filter::ObjectAttributeValueFilter {
+                            object: 0.into(),
+                            attribute_name: "priority".to_string(),
+                            at_time: ObjectValueFilterTimepoint::Sometime,
+                            value_filter: ValueFilter::Integer {
+                                min: Some((1 << 53) + 1),
+                                max: Some((1 << 53) + 6),
+                            },
+                        },
+                        Filter::ObjectAttributeValueFilter {
+                            object: 0.into(),
+                            attribute_name: "label".to_string(),
+                            at_time: ObjectValueFilterTimepoint::Always,
+                            value_filter: ValueFilter::String {
+                                is_in: vec!["standard".to_string(), "express".to_string()],
+                            },
+                        },
+                    ],
+                    size_filters: Vec::new(),
+                    constraints: vec![
+                        Constraint::SizeFilter {
+                            filter: SizeFilter::NumChilds {
+                                child_name: "A".to_string(),
+                                min: Some(1),
+                                max: None,
+                            },
+                        },
+                        Constraint::SizeFilter {
+                            filter: SizeFilter::NumChilds {
+                                child_name: "B".to_string(),
+                                min: None,
+                                max: Some(1),
+                            },
+                        },
+                    ],
+                },
+                vec![1, 2],
+            ),
+            BindingBoxTreeNode::Box(
+                BindingBox {
+                    new_event_vars: vec![(0.into(), HashSet::from(["place order".to_string()]))]
+                        .into_iter()
+                        .collect(),
+                    new_object_vars: Default::default(),
+                    filters: vec![
+                        Filter::O2E {
+                            object: 0.into(),
+                            event: 0.into(),
+                            qualifier: None,
+                        },
+                        Filter::EventAttributeValueFilter {
+                            event: 0.into(),
+                            attribute_name: "due".to_string(),
+                            value_filter: ValueFilter::Time {
+                                from: Some((start + Duration::days(1)).to_utc()),
+                                to: Some((start + Duration::days(4)).to_utc()),
+                            },
+                        },
+                        Filter::EventAttributeValueFilter {
+                            event: 0.into(),
+                            attribute_name: "amount".to_string(),
+                            value_filter: ValueFilter::Integer {
+                                min: Some(0),
+                                max: Some(10),
+                            },
+                        },
+                    ],
+                    size_filters: Vec::new(),
+                    constraints: Vec::new(),
+                },
+                vec![],
+            ),
+            BindingBoxTreeNode::Box(
+                BindingBox {
+                    new_event_vars: vec![(1.into(), HashSet::from(["pick item".to_string()]))]
+                        .into_iter()
+                        .collect(),
+                    new_object_vars: Default::default(),
+                    filters: vec![
+                        Filter::O2E {
+                            object: 0.into(),
+                            event: 1.into(),
+                            qualifier: Some("order".to_string()),
+                        },
+                        Filter::EventAttributeValueFilter {
+                            event: 1.into(),
+                            attribute_name: "channel".to_string(),
+                            value_filter: ValueFilter::String { is_in: Vec::new() },
+                        },
+                    ],
+                    size_filters: Vec::new(),
+                    constraints: Vec::new(),
+                },
+                vec![],
+            ),
+        ],
+        edge_names: vec![((0, 1), "A".to_string()), ((0, 2), "B".to_string())]
+            .into_iter()
+            .collect(),
+    };
+
+    let sorted_results =
+        |results: Vec<(usize, Binding, Option<super::structs::ViolationReason>)>| {
+            results
+                .into_iter()
+                .map(|(node, binding, violation)| format!("{node} {binding:?} {violation:?}"))
+                .sorted()
+                .collect_vec()
+        };
+    let expected = sorted_results(tree.evaluate(&ocel));
+    let mut sqlite_results = Vec::new();
+    SqliteOCEL::open(&path)
+        .unwrap()
+        .evaluate_box_tree(&tree, |node, id_binding, violation| {
+            sqlite_results.push((node, id_binding.to_index_binding(&ocel).unwrap(), violation))
+        })
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    // Some situations of each node, of which some (but not all) are violated
+    for node in 0..2 {
+        let prefix = format!("{node} ");
+        let situations = expected
+            .iter()
+            .filter(|r| r.starts_with(&prefix))
+            .collect_vec();
+        assert!(!situations.is_empty());
+    }
+    assert!(expected.iter().any(|r| r.ends_with(" None")));
+    assert!(expected.iter().any(|r| !r.ends_with(" None")));
+    assert_eq!(sorted_results(sqlite_results), expected);
+}
+
+#[test]
+fn box_tree_text_round_trip() {
+    use super::{
+        dsl::{box_tree_from_text, box_tree_to_text},
+        structs::{BindingBoxTree, BindingBoxTreeNode, Constraint, SizeFilter, Variable},
+    };
+
+    let start = example_start_time().to_utc();
+    let weird_name = "quote \" backslash \\ newline \n tab \t bell \u{7} unicode ü ✓".to_string();
+    let float_bounds = [
+        (Some(f64::NAN), Some(f64::INFINITY)),
+        (Some(f64::NEG_INFINITY), Some(-0.0)),
+        (Some(0.0), None),
+        (None, Some(-1.5e-300)),
+    ];
+    let tree = BindingBoxTree {
+        nodes: vec![
+            BindingBoxTreeNode::Box(
+                BindingBox {
+                    new_event_vars: vec![
+                        (0.into(), HashSet::from(["place order".to_string()])),
+                        (1.into(), HashSet::from([weird_name.clone(), String::new()])),
+                    ]
+                    .into_iter()
+                    .collect(),
+                    new_object_vars: vec![(10.into(), HashSet::from(["orders".to_string()]))]
+                        .into_iter()
+                        .collect(),
+                    filters: float_bounds
+                        .iter()
+                        .map(|(min, max)| Filter::TimeBetweenEvents {
+                            from_event: 0.into(),
+                            to_event: 1.into(),
+                            min_seconds: *min,
+                            max_seconds: *max,
+                        })
+                        .chain(float_bounds.iter().map(|(min, max)| {
+                            Filter::ObjectAttributeValueFilter {
+                                object: 10.into(),
+                                attribute_name: weird_name.clone(),
+                                at_time: ObjectValueFilterTimepoint::AtEvent { event: 1.into() },
+                                value_filter: ValueFilter::Float {
+                                    min: *min,
+                                    max: *max,
+                                },
+                            }
+                        }))
+                        .chain([
+                            Filter::O2E {
+                                object: 10.into(),
+                                event: 0.into(),
+                                qualifier: Some(weird_name.clone()),
+                            },
+                            Filter::NotEqual {
+                                var_1: Variable::Event(0.into()),
+                                var_2: Variable::Event(1.into()),
+                            },
+                            Filter::EventAttributeValueFilter {
+                                event: 0.into(),
+                                attribute_name: "amount".to_string(),
+                                value_filter: ValueFilter::Integer {
+                                    min: Some(i64::MIN),
+                                    max: Some(i64::MAX),
+                                },
+                            },
+                            Filter::EventAttributeValueFilter {
+                                event: 0.into(),
+                                attribute_name: "due".to_string(),
+                                value_filter: ValueFilter::Time {
+                                    from: Some(start),
+                                    to: None,
+                                },
+                            },
+                            Filter::BasicFilterCEL {
+                                cel: "attr(e1, \"a\") == \"#\\\"\"#".to_string(),
+                            },
+                        ])
+                        .collect(),
+                    size_filters: vec![SizeFilter::AdvancedCEL {
+                        cel: "size(A) > 1 && \"\\n\" != \"\"".to_string(),
+                    }],
+                    constraints: vec![
+                        Constraint::SizeFilter {
+                            filter: SizeFilter::NumChilds {
+                                child_name: "as".to_string(),
+                                min: Some(0),
+                                max: None,
+                            },
+                        },
+                        Constraint::SAT {
+                            child_names: vec![weird_name.clone(), String::new()],
+                        },
+                    ],
+                },
+                vec![1, 2],
+            ),
+            BindingBoxTreeNode::NOT(3),
+            BindingBoxTreeNode::OR(1, 3),
+            BindingBoxTreeNode::Box(
+                BindingBox {
+                    new_event_vars: Default::default(),
+                    new_object_vars: Default::default(),
+                    filters: Vec::new(),
+                    size_filters: Vec::new(),
+                    constraints: Vec::new(),
+                },
+                Vec::new(),
+            ),
+        ],
+        edge_names: vec![
+            ((0, 1), "as".to_string()),
+            ((0, 2), weird_name.clone()),
+            ((1, 3), String::new()),
+            ((2, 3), "edge -> name;".to_string()),
+        ]
+        .into_iter()
+        .collect(),
+    };
+
+    let text = box_tree_to_text(&tree);
+    let parsed = box_tree_from_text(&text).unwrap_or_else(|e| panic!("{e}\n{text}"));
+    assert_eq!(box_tree_to_text(&parsed), text);
+    assert_eq!(parsed.nodes.len(), tree.nodes.len());
+    assert_eq!(parsed.edge_names, tree.edge_names);
+    let BindingBoxTreeNode::Box(parsed_box, _) = &parsed.nodes[0] else {
+        panic!("Expected a box node")
+    };
+    let BindingBoxTreeNode::Box(bbox, _) = &tree.nodes[0] else {
+        unreachable!()
+    };
+    assert_eq!(parsed_box.new_event_vars, bbox.new_event_vars);
+    assert_eq!(parsed_box.constraints, bbox.constraints);
+    assert_eq!(parsed_box.size_filters, bbox.size_filters);
+    for (parsed_filter, filter) in parsed_box.filters.iter().zip(&bbox.filters) {
+        match (parsed_filter, filter) {
+            (
+                Filter::TimeBetweenEvents {
+                    min_seconds: parsed_min,
+                    max_seconds: parsed_max,
+                    ..
+                },
+                Filter::TimeBetweenEvents {
+                    min_seconds: min,
+                    max_seconds: max,
+                    ..
+                },
+            )
+            | (
+                Filter::ObjectAttributeValueFilter {
+                    value_filter:
+                        ValueFilter::Float {
+                            min: parsed_min,
+                            max: parsed_max,
+                        },
+                    ..
+                },
+                Filter::ObjectAttributeValueFilter {
+                    value_filter: ValueFilter::Float { min, max },
+                    ..
+                },
+            ) => {
+                // Compare bit patterns, so that NaN and the sign of zero are checked as well
+                assert_eq!(parsed_min.map(f64::to_bits), min.map(f64::to_bits));
+                assert_eq!(parsed_max.map(f64::to_bits), max.map(f64::to_bits));
+            }
+            _ => assert_eq!(parsed_filter, filter),
+        }
+    }
+    assert_eq!(parsed_box.filters.len(), bbox.filters.len());
+    assert_eq!(parsed.nodes[1..], tree.nodes[1..]);
+
+    // The example from the module documentation
+    let example = r#"
+        // Every order must be paid within 2 weeks
+        node 0 {
+            o1: {"orders"};
+            e1: {"place order"};
+            filter O2E(o1, e1, _);
+            children 1 as "A";
+            constraint size NumChilds("A", 1, _);
+        }
+        node 1 {
+            e2: {"pay order"};
+            filter O2E(o1, e2, _);
+            filter TimeBetweenEvents(e1, e2, 0.0, 1209600.0);
+        }
+        node 2 = NOT(1);
+        edge 2 -> 1 as "B";
+    "#;
+    let example_tree = box_tree_from_text(example).unwrap();
+    let reparsed = box_tree_from_text(&box_tree_to_text(&example_tree)).unwrap();
+    assert_eq!(reparsed.nodes, example_tree.nodes);
+    assert_eq!(reparsed.edge_names, example_tree.edge_names);
+    assert_eq!(example_tree.edge_names.len(), 2);
+}
@@ -0,0 +1,229 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, TimeZone, Utc};
+use plotly::{
+    common::{AxisSide, Mode, Title},
+    layout::Axis,
+    Bar, Layout, Plot, Scatter,
+};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::preprocessing::linked_ocel::IndexLinkedOCEL;
+
+use super::structs::{Binding, BindingBoxTree, EventVariable, ObjectVariable};
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum TrendTimeReference {
+    /// Timestamp of the event bound to the variable
+    Event { event: EventVariable },
+    /// Timestamp of the first event the object bound to the variable is involved in
+    ObjectFirstEvent { object: ObjectVariable },
+}
+
+impl TrendTimeReference {
+    pub fn get_time(&self, binding: &Binding, ocel: &IndexLinkedOCEL) -> Option<DateTime<Utc>> {
+        match self {
+            TrendTimeReference::Event { event } => binding
+                .get_ev(event, ocel)
+                .map(|ev| ev.time.with_timezone(&Utc)),
+            TrendTimeReference::ObjectFirstEvent { object } => binding
+                .get_ob_index(object)
+                .and_then(|ob_index| ocel.object_events_map.get(ob_index))
+                .and_then(|evs| {
+                    evs.iter()
+                        .filter_map(|ev_index| ocel.ev_by_index(ev_index))
+                        .map(|ev| ev.time.with_timezone(&Utc))
+                        .min()
+                }),
+        }
+    }
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum TrendBucketSize {
+    Day,
+    /// ISO weeks (starting on Monday)
+    Week,
+    Month,
+}
+
+impl TrendBucketSize {
+    /// Start of the (UTC) bucket containing the given time
+    pub fn bucket_start(&self, time: &DateTime<Utc>) -> NaiveDate {
+        let date = time.date_naive();
+        match self {
+            TrendBucketSize::Day => date,
+            TrendBucketSize::Week => {
+                date - Duration::days(date.weekday().num_days_from_monday() as i64)
+            }
+            TrendBucketSize::Month => date.with_day(1).unwrap(),
+        }
+    }
+
+    /// Start of the bucket following the bucket starting at `start`
+    pub fn next_bucket_start(&self, start: &NaiveDate) -> NaiveDate {
+        match self {
+            TrendBucketSize::Day => *start + Duration::days(1),
+            TrendBucketSize::Week => *start + Duration::weeks(1),
+            TrendBucketSize::Month => *start + Months::new(1),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrendAnalysisRequest {
+    pub tree: BindingBoxTree,
+    pub time_reference: TrendTimeReference,
+    pub bucket_size: TrendBucketSize,
+    /// If set, the result also includes an HTML plot of the trend
+    pub render_plot: Option<bool>,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrendBucket {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub situation_count: usize,
+    pub situation_violated_count: usize,
+    /// `None` if there are no situations in this bucket
+    pub violation_rate: Option<f64>,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrendAnalysisResult {
+    /// Consecutive buckets from the first to the last situation (including empty buckets)
+    pub buckets: Vec<TrendBucket>,
+    /// Number of root situations for which no time reference could be determined
+    /// (e.g., because the variable is not bound by the root node)
+    pub unassigned_situation_count: usize,
+    pub plot_html: Option<String>,
+}
+
+/// Evaluate the trend for a request, rendering the plot if requested
+pub fn analyze_trend(req: &TrendAnalysisRequest, ocel: &IndexLinkedOCEL) -> TrendAnalysisResult {
+    let mut result = evaluate_trend(&req.tree, ocel, &req.time_reference, req.bucket_size);
+    if req.render_plot.unwrap_or(false) {
+        result.plot_html = Some(render_trend_plot(&result));
+    }
+    result
+}
+
+///
+/// Evaluate the tree and bucket the situations of the root node by time
+///
+pub fn evaluate_trend(
+    tree: &BindingBoxTree,
+    ocel: &IndexLinkedOCEL,
+    time_reference: &TrendTimeReference,
+    bucket_size: TrendBucketSize,
+) -> TrendAnalysisResult {
+    let root_situations = match tree.nodes.first() {
+        Some(root) => root.evaluate(0, Binding::default(), tree, ocel).0,
+        None => Vec::new(),
+    };
+    let mut counts: BTreeMap<NaiveDate, (usize, usize)> = BTreeMap::new();
+    let mut unassigned_situation_count = 0;
+    for (_, binding, viol) in root_situations.iter().filter(|(index, _, _)| *index == 0) {
+        match time_reference.get_time(binding, ocel) {
+            Some(time) => {
+                let c = counts.entry(bucket_size.bucket_start(&time)).or_default();
+                c.0 += 1;
+                if viol.is_some() {
+                    c.1 += 1;
+                }
+            }
+            None => unassigned_situation_count += 1,
+        }
+    }
+    let mut buckets = Vec::new();
+    if let (Some(first), Some(last)) = (counts.keys().next(), counts.keys().next_back()) {
+        let mut start = *first;
+        while start <= *last {
+            let end = bucket_size.next_bucket_start(&start);
+            let (situation_count, situation_violated_count) =
+                counts.get(&start).copied().unwrap_or_default();
+            buckets.push(TrendBucket {
+                start: Utc.from_utc_datetime(&start.and_hms_opt(0, 0, 0).unwrap()),
+                end: Utc.from_utc_datetime(&end.and_hms_opt(0, 0, 0).unwrap()),
+                situation_count,
+                situation_violated_count,
+                violation_rate: if situation_count > 0 {
+                    Some(situation_violated_count as f64 / situation_count as f64)
+                } else {
+                    None
+                },
+            });
+            start = end;
+        }
+    }
+    TrendAnalysisResult {
+        buckets,
+        unassigned_situation_count,
+        plot_html: None,
+    }
+}
+
+///
+/// Render the trend as a standalone HTML page
+///
+/// Shows the violation rate (line) together with the number of situations per bucket (bars)
+///
+pub fn render_trend_plot(result: &TrendAnalysisResult) -> String {
+    let x: Vec<String> = result
+        .buckets
+        .iter()
+        .map(|b| b.start.format("%Y-%m-%d").to_string())
+        .collect();
+    let mut plot = Plot::new();
+    plot.add_trace(
+        Bar::new(
+            x.clone(),
+            result.buckets.iter().map(|b| b.situation_count).collect(),
+        )
+        .name("Situations")
+        .y_axis("y2")
+        .opacity(0.3),
+    );
+    plot.add_trace(
+        Scatter::new(
+            x,
+            result
+                .buckets
+                .iter()
+                .map(|b| b.violation_rate.map(|r| r * 100.0))
+                .collect(),
+        )
+        .name("Violation rate")
+        .mode(Mode::LinesMarkers),
+    );
+    plot.set_layout(
+        Layout::new()
+            .x_axis(Axis::new().title(Title::with_text("Time")))
+            .y_axis(
+                Axis::new()
+                    .title(Title::with_text("Violation rate (%)"))
+                    .range(vec![0.0, 100.0]),
+            )
+            .y_axis2(
+                Axis::new()
+                    .title(Title::with_text("Situations"))
+                    .overlaying("y")
+                    .side(AxisSide::Right)
+                    .show_grid(false),
+            ),
+    );
+    plot.to_html()
+}
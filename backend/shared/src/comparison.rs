@@ -0,0 +1,360 @@
+//! Constraint-based comparison of two OCELs (e.g., to detect concept drift between two extracts)
+//!
+//! The same [`BindingBoxTree`]s are evaluated on both OCELs, and the violation rates of all nodes are compared
+//! using a two-proportion z-test.
+//! Additionally, count and eventually-follows constraints are discovered on both OCELs,
+//! and constraints which hold in one OCEL but not in the other are reported.
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{
+    binding_box::BindingBoxTree,
+    discovery::{
+        advanced::EventOrObjectType,
+        graph_discovery::{discover_count_constraints, discover_ef_constraints},
+        CountConstraintOptions, DiscoverySamplingOptions, EventuallyFollowsConstraintOptions,
+    },
+    preprocessing::linked_ocel::IndexLinkedOCEL,
+};
+
+const DEFAULT_SIGNIFICANCE_LEVEL: f64 = 0.05;
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OCELComparisonOptions {
+    /// Named trees to evaluate on both OCELs
+    pub trees: Vec<(String, BindingBoxTree)>,
+    pub count_constraints: Option<CountConstraintOptions>,
+    pub eventually_follows_constraints: Option<EventuallyFollowsConstraintOptions>,
+    /// Significance level of the tests (defaults to 0.05)
+    pub significance_level: Option<f64>,
+    /// Sampling of events/objects used for discovery (defaults to [`DiscoverySamplingOptions::default`])
+    pub sampling: Option<DiscoverySamplingOptions>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CompareOCELsRequest {
+    /// The OCEL to compare the currently loaded OCEL to
+    pub other_ocel_path: String,
+    #[serde(flatten)]
+    pub options: OCELComparisonOptions,
+}
+
+/// One of the two compared OCELs
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ComparedOCEL {
+    A,
+    B,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeComparison {
+    pub situation_count_a: usize,
+    pub situation_count_b: usize,
+    pub situation_violated_count_a: usize,
+    pub situation_violated_count_b: usize,
+    /// `None` if there are no situations for this node in OCEL A
+    pub violation_rate_a: Option<f64>,
+    /// `None` if there are no situations for this node in OCEL B
+    pub violation_rate_b: Option<f64>,
+    /// z-score of the two-proportion z-test (positive if the violation rate is higher in OCEL B)
+    pub z_score: Option<f64>,
+    /// Two-sided p-value of the two-proportion z-test
+    pub p_value: Option<f64>,
+    /// Whether the p-value is below the significance level
+    ///
+    /// Only set for the root node: The situations of child nodes are nested in (and depend on) the root situations,
+    /// so the test assumptions do not hold for them and their z-score and p-value are only indicative
+    pub significant: bool,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TreeComparison {
+    pub name: String,
+    /// Comparison for all nodes of the tree (by node index)
+    pub nodes: Vec<NodeComparison>,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveredConstraintComparison {
+    pub name: String,
+    pub tree: BindingBoxTree,
+    pub discovered_in: ComparedOCEL,
+    /// Fraction of satisfied root situations in OCEL A (`None` if there are no root situations)
+    pub satisfied_fraction_a: Option<f64>,
+    /// Fraction of satisfied root situations in OCEL B (`None` if there are no root situations)
+    pub satisfied_fraction_b: Option<f64>,
+    pub holds_in_a: bool,
+    pub holds_in_b: bool,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OCELComparisonResult {
+    pub trees: Vec<TreeComparison>,
+    /// Discovered constraints which hold in exactly one of the two OCELs
+    pub changed_constraints: Vec<DiscoveredConstraintComparison>,
+}
+
+///
+/// Compare two OCELs based on the given trees and on discovered constraints
+///
+/// A discovered constraint holds in an OCEL if at least the requested cover fraction of its root situations is satisfied.
+///
+pub fn compare_ocels(
+    ocel_a: &IndexLinkedOCEL,
+    ocel_b: &IndexLinkedOCEL,
+    options: &OCELComparisonOptions,
+) -> OCELComparisonResult {
+    let significance_level = options
+        .significance_level
+        .unwrap_or(DEFAULT_SIGNIFICANCE_LEVEL);
+    let trees = options
+        .trees
+        .iter()
+        .map(|(name, tree)| {
+            let (counts_a, counts_b) =
+                rayon::join(|| node_counts(tree, ocel_a), || node_counts(tree, ocel_b));
+            TreeComparison {
+                name: name.clone(),
+                nodes: counts_a
+                    .into_iter()
+                    .zip(counts_b)
+                    .enumerate()
+                    .map(|(index, (a, b))| {
+                        compare_node_counts(a, b, significance_level, index == 0)
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+
+    let mut changed_constraints = Vec::new();
+    for (discovered_in, ocel) in [(ComparedOCEL::A, ocel_a), (ComparedOCEL::B, ocel_b)] {
+        for (name, tree, cover_fraction) in discover_constraints(ocel, options) {
+            let (satisfied_fraction_a, satisfied_fraction_b) = rayon::join(
+                || satisfied_fraction(&tree, ocel_a),
+                || satisfied_fraction(&tree, ocel_b),
+            );
+            let holds_in_a = satisfied_fraction_a.is_some_and(|f| f >= cover_fraction as f64);
+            let holds_in_b = satisfied_fraction_b.is_some_and(|f| f >= cover_fraction as f64);
+            if holds_in_a != holds_in_b {
+                changed_constraints.push(DiscoveredConstraintComparison {
+                    name,
+                    tree,
+                    discovered_in,
+                    satisfied_fraction_a,
+                    satisfied_fraction_b,
+                    holds_in_a,
+                    holds_in_b,
+                });
+            }
+        }
+    }
+    OCELComparisonResult {
+        trees,
+        changed_constraints,
+    }
+}
+
+/// Discover count and eventually-follows constraints (with their requested cover fraction)
+fn discover_constraints(
+    ocel: &IndexLinkedOCEL,
+    options: &OCELComparisonOptions,
+) -> Vec<(String, BindingBoxTree, f32)> {
+    let sampling = options.sampling.clone().unwrap_or_default();
+    let mut ret = Vec::new();
+    if let Some(count_opts) = &options.count_constraints {
+        let types = count_opts
+            .object_types
+            .iter()
+            .map(|ot| EventOrObjectType::Object(ot.clone()))
+            .chain(
+                count_opts
+                    .event_types
+                    .iter()
+                    .map(|et| EventOrObjectType::Event(et.clone())),
+            )
+            .collect_vec();
+        for t in types {
+            for cc in discover_count_constraints(ocel, count_opts.cover_fraction, t, &sampling) {
+                ret.push((
+                    cc.get_constraint_name(),
+                    cc.get_full_tree(),
+                    count_opts.cover_fraction,
+                ));
+            }
+        }
+    }
+    if let Some(ef_opts) = &options.eventually_follows_constraints {
+        for ot in &ef_opts.object_types {
+            for c in discover_ef_constraints(ocel, ef_opts.cover_fraction, ot, &sampling) {
+                ret.push((
+                    c.get_constraint_name(),
+                    c.get_full_tree(),
+                    ef_opts.cover_fraction,
+                ));
+            }
+        }
+    }
+    ret
+}
+
+/// Number of situations and violated situations for all nodes of the tree
+fn node_counts(tree: &BindingBoxTree, ocel: &IndexLinkedOCEL) -> Vec<(usize, usize)> {
+    let mut counts = vec![(0, 0); tree.nodes.len()];
+    for (index, _binding, viol) in tree.evaluate(ocel) {
+        counts[index].0 += 1;
+        if viol.is_some() {
+            counts[index].1 += 1;
+        }
+    }
+    counts
+}
+
+fn satisfied_fraction(tree: &BindingBoxTree, ocel: &IndexLinkedOCEL) -> Option<f64> {
+    let (situation_count, violated_count) = node_counts(tree, ocel).first().copied()?;
+    if situation_count == 0 {
+        None
+    } else {
+        Some(1.0 - violated_count as f64 / situation_count as f64)
+    }
+}
+
+fn compare_node_counts(
+    (situation_count_a, situation_violated_count_a): (usize, usize),
+    (situation_count_b, situation_violated_count_b): (usize, usize),
+    significance_level: f64,
+    is_root: bool,
+) -> NodeComparison {
+    let rate = |violated: usize, count: usize| {
+        if count > 0 {
+            Some(violated as f64 / count as f64)
+        } else {
+            None
+        }
+    };
+    let test = two_proportion_z_test(
+        situation_violated_count_a,
+        situation_count_a,
+        situation_violated_count_b,
+        situation_count_b,
+    );
+    NodeComparison {
+        situation_count_a,
+        situation_count_b,
+        situation_violated_count_a,
+        situation_violated_count_b,
+        violation_rate_a: rate(situation_violated_count_a, situation_count_a),
+        violation_rate_b: rate(situation_violated_count_b, situation_count_b),
+        z_score: test.map(|(z, _)| z),
+        p_value: test.map(|(_, p)| p),
+        significant: is_root && test.is_some_and(|(_, p)| p < significance_level),
+    }
+}
+
+///
+/// Two-sided two-proportion z-test (with pooled variance)
+///
+/// Returns the z-score and p-value, or `None` if one of the samples is empty
+///
+pub fn two_proportion_z_test(x_a: usize, n_a: usize, x_b: usize, n_b: usize) -> Option<(f64, f64)> {
+    if n_a == 0 || n_b == 0 {
+        return None;
+    }
+    let (n_a, n_b) = (n_a as f64, n_b as f64);
+    let p_a = x_a as f64 / n_a;
+    let p_b = x_b as f64 / n_b;
+    let p_pooled = (x_a + x_b) as f64 / (n_a + n_b);
+    let std_error = (p_pooled * (1.0 - p_pooled) * (1.0 / n_a + 1.0 / n_b)).sqrt();
+    if std_error == 0.0 {
+        // Both proportions are 0 or both are 1
+        return Some((0.0, 1.0));
+    }
+    let z = (p_b - p_a) / std_error;
+    Some((z, erfc(z.abs() / std::f64::consts::SQRT_2)))
+}
+
+/// Complementary error function (Chebyshev approximation, fractional error below 1.2e-7)
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let r = t
+        * (-z * z - 1.26551223
+            + t * (1.00002368
+                + t * (0.37409196
+                    + t * (0.09678418
+                        + t * (-0.18628806
+                            + t * (0.27886807
+                                + t * (-1.13520398
+                                    + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277)))))))))
+            .exp();
+    if x >= 0.0 {
+        r
+    } else {
+        2.0 - r
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn erfc_values() {
+        assert!((erfc(0.0) - 1.0).abs() < 1e-6);
+        assert!((erfc(1.0) - 0.157299).abs() < 1e-5);
+        assert!((erfc(-1.0) - 1.842701).abs() < 1e-5);
+    }
+
+    #[test]
+    fn z_test_with_empty_sample() {
+        assert_eq!(two_proportion_z_test(0, 0, 3, 10), None);
+        assert_eq!(two_proportion_z_test(3, 10, 0, 0), None);
+    }
+
+    #[test]
+    fn z_test_with_equal_proportions() {
+        let (z, p) = two_proportion_z_test(2, 10, 4, 20).unwrap();
+        assert!(z.abs() < 1e-9);
+        assert!((p - 1.0).abs() < 1e-6);
+        assert_eq!(two_proportion_z_test(0, 10, 0, 20), Some((0.0, 1.0)));
+        assert_eq!(two_proportion_z_test(10, 10, 20, 20), Some((0.0, 1.0)));
+    }
+
+    #[test]
+    fn z_test_with_different_proportions() {
+        // p_a = 0.1, p_b = 0.3 and pooled proportion 0.2
+        let (z, p) = two_proportion_z_test(10, 100, 30, 100).unwrap();
+        assert!((z - 0.2 / (0.2_f64 * 0.8 * 0.02).sqrt()).abs() < 1e-9);
+        assert!(z > 0.0);
+        assert!(p < 0.001);
+        let (z_rev, p_rev) = two_proportion_z_test(30, 100, 10, 100).unwrap();
+        assert!((z + z_rev).abs() < 1e-9);
+        assert!((p - p_rev).abs() < 1e-9);
+    }
+
+    #[test]
+    fn only_root_nodes_are_significant() {
+        let root = compare_node_counts((100, 10), (100, 30), DEFAULT_SIGNIFICANCE_LEVEL, true);
+        let child = compare_node_counts((100, 10), (100, 30), DEFAULT_SIGNIFICANCE_LEVEL, false);
+        assert!(root.significant);
+        assert!(!child.significant);
+        assert_eq!(root.p_value, child.p_value);
+    }
+}
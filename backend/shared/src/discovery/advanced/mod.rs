@@ -6,8 +6,8 @@ use std::{cmp::max, collections::HashMap};
 
 use itertools::Itertools;
 
-use rand::{rngs::StdRng, seq::IteratorRandom, SeedableRng};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     binding_box::{
@@ -18,8 +18,7 @@ use crate::{
 };
 
 use super::{
-    graph_discovery::discover_count_constraints_for_supporting_instances, RNG_SEED, SAMPLE_FRAC,
-    SAMPLE_MIN_NUM_INSTANCES,
+    graph_discovery::discover_count_constraints_for_supporting_instances, DiscoverySamplingOptions,
 };
 
 // 1st Step: Allow building of  (simple) sampled bindings based on object/event type
@@ -27,8 +26,9 @@ pub fn generate_sample_bindings(
     ocel: &IndexLinkedOCEL,
     ocel_types: &Vec<EventOrObjectType>,
     target_variable: Variable,
+    sampling: &DiscoverySamplingOptions,
 ) -> Vec<Binding> {
-    let mut rng = StdRng::seed_from_u64(RNG_SEED);
+    let mut rng = sampling.rng();
     match target_variable {
         Variable::Event(ev) => {
             let instances: Vec<_> = ocel_types
@@ -36,16 +36,10 @@ pub fn generate_sample_bindings(
                 .flat_map(|t| ocel.events_of_type.get(t.inner()))
                 .flatten()
                 .collect();
-            let sample_count = if instances.len() >= SAMPLE_MIN_NUM_INSTANCES {
-                (instances.len() as f32 * SAMPLE_FRAC).ceil() as usize
-            } else {
-                instances.len()
-            };
-            instances
-                .iter()
-                .choose_multiple(&mut rng, sample_count)
-                .iter()
-                .map(|i| Binding::default().expand_with_ev(ev, ***i))
+            sampling
+                .sample(instances, sampling.min_num_instances, &mut rng)
+                .into_iter()
+                .map(|i| Binding::default().expand_with_ev(ev, *i))
                 .collect()
         }
         Variable::Object(ov) => {
@@ -54,16 +48,10 @@ pub fn generate_sample_bindings(
                 .flat_map(|t| ocel.objects_of_type.get(t.inner()))
                 .flatten()
                 .collect();
-            let sample_count = if instances.len() >= SAMPLE_MIN_NUM_INSTANCES {
-                (instances.len() as f32 * SAMPLE_FRAC).ceil() as usize
-            } else {
-                instances.len()
-            };
-            instances
-                .iter()
-                .choose_multiple(&mut rng, sample_count)
-                .iter()
-                .map(|i| Binding::default().expand_with_ob(ov, ***i))
+            sampling
+                .sample(instances, sampling.min_num_instances, &mut rng)
+                .into_iter()
+                .map(|i| Binding::default().expand_with_ob(ov, *i))
                 .collect()
         }
     }
@@ -106,7 +94,12 @@ pub fn get_labeled_instances(
         EventOrObjectType::Event(_) => Variable::Event(EventVariable(0)),
         EventOrObjectType::Object(_) => Variable::Object(ObjectVariable(0)),
     };
-    let bindings = generate_sample_bindings(ocel, &vec![ocel_type.clone()], variable.clone());
+    let bindings = generate_sample_bindings(
+        ocel,
+        &vec![ocel_type.clone()],
+        variable.clone(),
+        &DiscoverySamplingOptions::default(),
+    );
 
     let violated_instances = bindings
         .iter()
@@ -168,79 +161,12 @@ pub fn test_tree_combinations(
             let good_or_frac = num_or_sat as f32 / max(num_tree_sat[i], num_tree_sat[j]) as f32;
             let good_sat_frac = num_or_sat as f32 / input_bindings.len() as f32;
             if (0.8..0.98).contains(&good_sat_frac) && good_or_frac >= 1.33 {
-                let tree1 = &subtrees[i];
-                let tree2 = &subtrees[j];
-                let name1 = "A".to_string();
-                let name2: String = "B".to_string();
-                let mut bbox = BindingBox {
-                    new_event_vars: HashMap::new(),
-                    new_object_vars: HashMap::new(),
-                    filters: Vec::default(),
-                    size_filters: Vec::default(),
-                    constraints: vec![Constraint::SAT {
-                        child_names: vec![name1.clone(), name2.clone()],
-                    }],
-                };
-                match ocel_type {
-                    EventOrObjectType::Event(et) => bbox.new_event_vars.insert(
-                        EventVariable(input_variable.to_inner()),
-                        vec![et.clone()].into_iter().collect(),
-                    ),
-                    EventOrObjectType::Object(ot) => bbox.new_object_vars.insert(
-                        ObjectVariable(input_variable.to_inner()),
-                        vec![ot.clone()].into_iter().collect(),
-                    ),
-                };
-                let or_box = BindingBoxTreeNode::Box(bbox, vec![1, 1 + tree1.nodes.len()]);
-                let mut or_tree = BindingBoxTree {
-                    nodes: vec![or_box],
-                    edge_names: HashMap::default(),
-                };
-                for tn in &tree1.nodes {
-                    match tn {
-                        BindingBoxTreeNode::Box(tn_box, tn_children) => {
-                            or_tree.nodes.push(BindingBoxTreeNode::Box(
-                                tn_box.clone(),
-                                tn_children.iter().map(|c| c + 1).collect(),
-                            ))
-                        }
-                        _ => {}
-                    }
-                }
-                for tn in &tree2.nodes {
-                    match tn {
-                        BindingBoxTreeNode::Box(tn_box, tn_children) => {
-                            or_tree.nodes.push(BindingBoxTreeNode::Box(
-                                tn_box.clone(),
-                                tn_children
-                                    .iter()
-                                    .map(|c| c + 1 + tree1.nodes.len())
-                                    .collect(),
-                            ))
-                        }
-                        _ => {}
-                    }
-                }
-                or_tree.edge_names.insert((0, 1), name1.clone());
-                or_tree
-                    .edge_names
-                    .insert((0, 1 + tree1.nodes.len()), name2.clone());
-                or_tree.edge_names.extend(
-                    tree1
-                        .edge_names
-                        .iter()
-                        .map(|((from, to), name)| ((*from + 1, to + 1), name.clone())),
-                );
-                or_tree
-                    .edge_names
-                    .extend(tree2.edge_names.iter().map(|((from, to), name)| {
-                        (
-                            (*from + 1 + tree1.nodes.len(), to + 1 + tree1.nodes.len()),
-                            name.clone(),
-                        )
-                    }));
-
-                ret.push(or_tree);
+                ret.push(combine_subtrees(
+                    &subtrees[i],
+                    &subtrees[j],
+                    &input_variable,
+                    ocel_type,
+                ));
                 println!("Good OR candidate with {good_or_frac}");
                 // println!("{:#?}\n{:#?}\n\n", tree1, tree1);
             }
@@ -249,6 +175,89 @@ pub fn test_tree_combinations(
     ret
 }
 
+///
+/// Combine the two subtrees (with the input variable not bound by their roots) using an OR (SAT) constraint
+///
+/// The nodes of `tree1` start at index 1, followed by the nodes of `tree2`.
+///
+pub fn combine_subtrees(
+    tree1: &BindingBoxTree,
+    tree2: &BindingBoxTree,
+    input_variable: &Variable,
+    ocel_type: &EventOrObjectType,
+) -> BindingBoxTree {
+    let name1 = "A".to_string();
+    let name2: String = "B".to_string();
+    let mut bbox = BindingBox {
+        new_event_vars: HashMap::new(),
+        new_object_vars: HashMap::new(),
+        filters: Vec::default(),
+        size_filters: Vec::default(),
+        constraints: vec![Constraint::SAT {
+            child_names: vec![name1.clone(), name2.clone()],
+        }],
+    };
+    match ocel_type {
+        EventOrObjectType::Event(et) => bbox.new_event_vars.insert(
+            EventVariable(input_variable.to_inner()),
+            vec![et.clone()].into_iter().collect(),
+        ),
+        EventOrObjectType::Object(ot) => bbox.new_object_vars.insert(
+            ObjectVariable(input_variable.to_inner()),
+            vec![ot.clone()].into_iter().collect(),
+        ),
+    };
+    let or_box = BindingBoxTreeNode::Box(bbox, vec![1, 1 + tree1.nodes.len()]);
+    let mut or_tree = BindingBoxTree {
+        nodes: vec![or_box],
+        edge_names: HashMap::default(),
+    };
+    for tn in &tree1.nodes {
+        match tn {
+            BindingBoxTreeNode::Box(tn_box, tn_children) => {
+                or_tree.nodes.push(BindingBoxTreeNode::Box(
+                    tn_box.clone(),
+                    tn_children.iter().map(|c| c + 1).collect(),
+                ))
+            }
+            _ => {}
+        }
+    }
+    for tn in &tree2.nodes {
+        match tn {
+            BindingBoxTreeNode::Box(tn_box, tn_children) => {
+                or_tree.nodes.push(BindingBoxTreeNode::Box(
+                    tn_box.clone(),
+                    tn_children
+                        .iter()
+                        .map(|c| c + 1 + tree1.nodes.len())
+                        .collect(),
+                ))
+            }
+            _ => {}
+        }
+    }
+    or_tree.edge_names.insert((0, 1), name1.clone());
+    or_tree
+        .edge_names
+        .insert((0, 1 + tree1.nodes.len()), name2.clone());
+    or_tree.edge_names.extend(
+        tree1
+            .edge_names
+            .iter()
+            .map(|((from, to), name)| ((*from + 1, to + 1), name.clone())),
+    );
+    or_tree
+        .edge_names
+        .extend(tree2.edge_names.iter().map(|((from, to), name)| {
+            (
+                (*from + 1 + tree1.nodes.len(), to + 1 + tree1.nodes.len()),
+                name.clone(),
+            )
+        }));
+    or_tree
+}
+
 /// Previous version of OR constraint discovery by combining previously discovered constraints
 /// For the new version see [`graph_discovery::discover_or_constraints_new`]
 ///
@@ -260,7 +269,12 @@ pub fn discover_or_constraints_old(
     input_variable: Variable,
     subtrees: Vec<BindingBoxTree>,
 ) -> Vec<BindingBoxTree> {
-    let bindings = generate_sample_bindings(ocel, &vec![ocel_type.clone()], input_variable.clone());
+    let bindings = generate_sample_bindings(
+        ocel,
+        &vec![ocel_type.clone()],
+        input_variable.clone(),
+        &DiscoverySamplingOptions::default(),
+    );
     let mut all_subtrees = subtrees.clone();
     for st in &subtrees {
         let violated_instances = bindings
@@ -284,7 +298,7 @@ pub fn discover_or_constraints_old(
     test_tree_combinations(ocel, all_subtrees, bindings, input_variable, ocel_type)
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum EventOrObjectType {
     Event(String),
     Object(String),
@@ -0,0 +1,390 @@
+//! Discovery of attribute value (data) constraints
+//!
+//! For each attribute of an event or object type, a [`ValueFilter`] is discovered that holds for at least the requested
+//! fraction of events/objects of that type:
+//! a numeric range (integer or float values), a set of allowed values (string values) or a fixed value (boolean values).
+//!
+//! Event attributes are checked on the value of the event;
+//! for object attributes, all values of the object over time have to satisfy the filter (see [`ObjectValueFilterTimepoint::Always`]).
+use std::collections::{HashMap, HashSet};
+
+use itertools::Itertools;
+use process_mining::ocel::ocel_struct::OCELAttributeValue;
+use rayon::prelude::*;
+
+use crate::{
+    binding_box::{
+        structs::{
+            BindingBoxTreeNode, Constraint, EventVariable, Filter, ObjectValueFilterTimepoint,
+            ObjectVariable, ValueFilter,
+        },
+        BindingBox, BindingBoxTree,
+    },
+    preprocessing::linked_ocel::{EventOrObjectIndex, IndexLinkedOCEL},
+};
+
+use super::{
+    advanced::EventOrObjectType, graph_discovery::get_instances, DiscoverySamplingOptions,
+};
+
+/// Maximal number of allowed values of a discovered string value constraint
+pub static MAX_STRING_VALUES: usize = 10;
+
+#[derive(Debug, Clone)]
+pub struct AttributeValueConstraint {
+    pub root_type: EventOrObjectType,
+    pub attribute_name: String,
+    pub value_filter: ValueFilter,
+}
+
+impl AttributeValueConstraint {
+    pub fn get_constraint_name(&self) -> String {
+        format!(
+            "{} '{}' per '{}'",
+            format_value_filter(&self.value_filter),
+            self.attribute_name,
+            self.root_type.inner()
+        )
+    }
+    pub fn get_full_tree(&self) -> BindingBoxTree {
+        let variable = 0;
+        let (new_event_vars, new_object_vars, filter) = match &self.root_type {
+            EventOrObjectType::Event(et) => (
+                vec![(
+                    EventVariable(variable),
+                    vec![et.clone()].into_iter().collect(),
+                )]
+                .into_iter()
+                .collect(),
+                HashMap::default(),
+                Filter::EventAttributeValueFilter {
+                    event: EventVariable(variable),
+                    attribute_name: self.attribute_name.clone(),
+                    value_filter: self.value_filter.clone(),
+                },
+            ),
+            EventOrObjectType::Object(ot) => (
+                HashMap::default(),
+                vec![(
+                    ObjectVariable(variable),
+                    vec![ot.clone()].into_iter().collect(),
+                )]
+                .into_iter()
+                .collect(),
+                Filter::ObjectAttributeValueFilter {
+                    object: ObjectVariable(variable),
+                    attribute_name: self.attribute_name.clone(),
+                    at_time: ObjectValueFilterTimepoint::Always,
+                    value_filter: self.value_filter.clone(),
+                },
+            ),
+        };
+        BindingBoxTree {
+            nodes: vec![BindingBoxTreeNode::Box(
+                BindingBox {
+                    new_event_vars,
+                    new_object_vars,
+                    filters: vec![],
+                    size_filters: vec![],
+                    constraints: vec![Constraint::Filter { filter }],
+                },
+                vec![],
+            )],
+            edge_names: HashMap::default(),
+        }
+    }
+}
+
+/// Short textual representation of the allowed values (e.g., `10-20` or `∈{a, b}`)
+pub(crate) fn format_value_filter(value_filter: &ValueFilter) -> String {
+    match value_filter {
+        ValueFilter::Float { min, max } => format_range(*min, *max),
+        ValueFilter::Integer { min, max } => format_range(*min, *max),
+        ValueFilter::Boolean { is_true } => format!("={is_true}"),
+        ValueFilter::String { is_in } => format!("∈{{{}}}", is_in.join(", ")),
+        ValueFilter::Time { from, to } => format_range(*from, *to),
+    }
+}
+
+fn format_range<T: PartialEq + std::fmt::Display>(min: Option<T>, max: Option<T>) -> String {
+    match (min, max) {
+        (None, None) => "any".to_string(),
+        (None, Some(max)) => format!("≤{max}"),
+        (Some(min), None) => format!("≥{min}"),
+        (Some(min), Some(max)) if min == max => format!("={min}"),
+        (Some(min), Some(max)) => format!("{min}-{max}"),
+    }
+}
+
+///
+/// Discover attribute value constraints for all attributes of the given event/object type
+///
+/// Every returned constraint is satisfied by at least `coverage` (fraction) of the (sampled) events/objects of the type
+///
+pub fn discover_attribute_constraints(
+    ocel: &IndexLinkedOCEL,
+    coverage: f32,
+    ocel_type: EventOrObjectType,
+    sampling: &DiscoverySamplingOptions,
+) -> Vec<AttributeValueConstraint> {
+    let attribute_names = match &ocel_type {
+        EventOrObjectType::Event(et) => ocel.ocel.event_types.iter().find(|t| &t.name == et),
+        EventOrObjectType::Object(ot) => ocel.ocel.object_types.iter().find(|t| &t.name == ot),
+    }
+    .map(|t| t.attributes.iter().map(|at| &at.name).collect_vec())
+    .unwrap_or_default();
+    let instances = get_instances(ocel, &ocel_type, sampling);
+    attribute_names
+        .into_par_iter()
+        .filter_map(|attribute_name| {
+            let values = instances
+                .iter()
+                .map(|index| attribute_values(ocel, index, attribute_name))
+                .collect_vec();
+            discover_value_filter(
+                &values,
+                coverage,
+                matches!(ocel_type, EventOrObjectType::Object(_)),
+            )
+            .map(|value_filter| AttributeValueConstraint {
+                root_type: ocel_type.clone(),
+                attribute_name: attribute_name.clone(),
+                value_filter,
+            })
+        })
+        .collect()
+}
+
+/// All values of the attribute for an event (at most one) or object (possibly multiple, over time)
+pub(crate) fn attribute_values<'a>(
+    ocel: &'a IndexLinkedOCEL,
+    index: &EventOrObjectIndex,
+    attribute_name: &String,
+) -> Vec<&'a OCELAttributeValue> {
+    match index {
+        EventOrObjectIndex::Event(e_index) => ocel.ev_by_index(e_index).map(|ev| {
+            ev.attributes
+                .iter()
+                .filter(|at| &at.name == attribute_name)
+                .map(|at| &at.value)
+                .collect()
+        }),
+        EventOrObjectIndex::Object(o_index) => ocel.ob_by_index(o_index).map(|ob| {
+            ob.attributes
+                .iter()
+                .filter(|at| &at.name == attribute_name)
+                .map(|at| &at.value)
+                .collect()
+        }),
+    }
+    .unwrap_or_default()
+}
+
+///
+/// Discover a [`ValueFilter`] satisfied by at least `coverage` of the instances
+///
+/// Each instance is given by its attribute values; it satisfies a filter if all its values satisfy it.
+/// Instances without any value never satisfy event filters (`all_values_semantics == false`),
+/// but always satisfy object filters (`all_values_semantics == true`).
+/// Instances with a null value never satisfy a filter.
+///
+fn discover_value_filter(
+    values: &[Vec<&OCELAttributeValue>],
+    coverage: f32,
+    all_values_semantics: bool,
+) -> Option<ValueFilter> {
+    let num_required = (values.len() as f32 * coverage).ceil() as usize;
+    let num_without_values = values.iter().filter(|vs| vs.is_empty()).count();
+    let num_free = if all_values_semantics {
+        num_without_values
+    } else {
+        0
+    };
+    // Instances with null values can not satisfy any filter
+    let with_values = values
+        .iter()
+        .filter(|vs| !vs.is_empty() && !vs.iter().any(|v| matches!(v, OCELAttributeValue::Null)))
+        .collect_vec();
+    if with_values.is_empty() || with_values.len() + num_free < num_required {
+        return None;
+    }
+    let all_values = || with_values.iter().flat_map(|vs| vs.iter());
+    // Number of instances with values that need to satisfy the filter
+    let num_required = num_required.saturating_sub(num_free);
+    if all_values().all(|v| {
+        matches!(
+            v,
+            OCELAttributeValue::Integer(_) | OCELAttributeValue::Float(_)
+        )
+    }) {
+        let ranges = with_values
+            .iter()
+            .map(|vs| numeric_range(vs))
+            .collect::<Option<Vec<_>>>()?;
+        let (min, max) = numeric_range_with_coverage(&ranges, num_required)?;
+        if all_values().all(|v| matches!(v, OCELAttributeValue::Integer(_))) {
+            // Bounds are integer values of the OCEL
+            Some(ValueFilter::Integer {
+                min: Some(min as i64),
+                max: Some(max as i64),
+            })
+        } else {
+            Some(ValueFilter::Float {
+                min: Some(min),
+                max: Some(max),
+            })
+        }
+    } else if all_values().all(|v| matches!(v, OCELAttributeValue::String(_))) {
+        let value_sets = with_values
+            .iter()
+            .map(|vs| {
+                vs.iter()
+                    .filter_map(|v| match v {
+                        OCELAttributeValue::String(s) => Some(s),
+                        _ => None,
+                    })
+                    .collect::<HashSet<_>>()
+            })
+            .collect_vec();
+        string_set_with_coverage(&value_sets, num_required)
+            .map(|is_in| ValueFilter::String { is_in })
+    } else if all_values().all(|v| matches!(v, OCELAttributeValue::Boolean(_))) {
+        [true, false].into_iter().find_map(|is_true| {
+            let num_satisfied = with_values
+                .iter()
+                .filter(|vs| {
+                    vs.iter()
+                        .all(|v| matches!(v, OCELAttributeValue::Boolean(b) if *b == is_true))
+                })
+                .count();
+            (num_satisfied >= num_required).then_some(ValueFilter::Boolean { is_true })
+        })
+    } else {
+        // Mixed or time values
+        None
+    }
+}
+
+/// (min, max) of integer/float values (as floats)
+fn numeric_range(values: &[&OCELAttributeValue]) -> Option<(f64, f64)> {
+    let values = values
+        .iter()
+        .map(|v| match v {
+            OCELAttributeValue::Integer(i) => Some(*i as f64),
+            OCELAttributeValue::Float(f) if !f.is_nan() => Some(*f),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let min = values.iter().copied().reduce(f64::min)?;
+    let max = values.iter().copied().reduce(f64::max)?;
+    Some((min, max))
+}
+
+///
+/// Narrowest range such that at least `num_required` instances have all their values
+/// (given by the (min, max) of their values) in the range
+///
+/// As many instances as possible are excluded, either at the lower or upper end of the value range.
+/// If no instance is required (e.g., because enough instances without values already satisfy the filter),
+/// the full range of all values is returned.
+///
+fn numeric_range_with_coverage(ranges: &[(f64, f64)], num_required: usize) -> Option<(f64, f64)> {
+    let n = ranges.len();
+    if n == 0 || num_required > n {
+        return None;
+    }
+    let mins = ranges
+        .iter()
+        .map(|(min, _)| *min)
+        .sorted_by(f64::total_cmp)
+        .collect_vec();
+    let maxs = ranges
+        .iter()
+        .map(|(_, max)| *max)
+        .sorted_by(f64::total_cmp)
+        .collect_vec();
+    if num_required == 0 {
+        return Some((mins[0], maxs[n - 1]));
+    }
+    let covers = |min: f64, max: f64| {
+        ranges
+            .iter()
+            .filter(|(lo, hi)| *lo >= min && *hi <= max)
+            .count()
+            >= num_required
+    };
+    (0..=n - num_required).rev().find_map(|excluded| {
+        (0..=excluded)
+            .map(|excluded_low| (mins[excluded_low], maxs[n - 1 - (excluded - excluded_low)]))
+            .filter(|(min, max)| min <= max)
+            .sorted_by(|(min1, max1), (min2, max2)| (max1 - min1).total_cmp(&(max2 - min2)))
+            .find(|(min, max)| covers(*min, *max))
+    })
+}
+
+///
+/// Smallest set of frequent values such that at least `num_required` instances only have values in the set
+///
+/// Returns `None` if more than [`MAX_STRING_VALUES`] values would be required
+///
+fn string_set_with_coverage(
+    value_sets: &[HashSet<&String>],
+    num_required: usize,
+) -> Option<Vec<String>> {
+    let value_frequencies = value_sets.iter().flatten().counts();
+    let mut allowed: HashSet<&String> = HashSet::new();
+    for (value, _count) in value_frequencies
+        .into_iter()
+        .sorted_by(|(v1, c1), (v2, c2)| c2.cmp(c1).then_with(|| v1.cmp(v2)))
+    {
+        if allowed.len() >= MAX_STRING_VALUES {
+            return None;
+        }
+        allowed.insert(value);
+        let num_covered = value_sets
+            .iter()
+            .filter(|vs| vs.iter().all(|v| allowed.contains(v)))
+            .count();
+        if num_covered >= num_required {
+            return Some(allowed.into_iter().sorted().cloned().collect());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numeric_range_with_coverage_excludes_outliers() {
+        let ranges = [(1.0, 1.0), (2.0, 3.0), (2.5, 2.5), (100.0, 100.0)];
+        assert_eq!(numeric_range_with_coverage(&ranges, 3), Some((1.0, 3.0)));
+        assert_eq!(numeric_range_with_coverage(&ranges, 4), Some((1.0, 100.0)));
+        assert_eq!(numeric_range_with_coverage(&ranges, 5), None);
+        assert_eq!(numeric_range_with_coverage(&[], 0), None);
+    }
+
+    #[test]
+    fn numeric_range_with_coverage_without_required_instances() {
+        let ranges = [(5.0, 7.0), (1.0, 2.0), (3.0, 10.0)];
+        assert_eq!(numeric_range_with_coverage(&ranges, 0), Some((1.0, 10.0)));
+    }
+
+    #[test]
+    fn value_filter_when_objects_without_values_suffice() {
+        let (a, b) = (
+            OCELAttributeValue::Integer(1),
+            OCELAttributeValue::Integer(5),
+        );
+        // Two of three objects have no value, which already satisfies a coverage of 0.5
+        let values = vec![vec![], vec![], vec![&a, &b]];
+        assert_eq!(
+            discover_value_filter(&values, 0.5, true),
+            Some(ValueFilter::Integer {
+                min: Some(1),
+                max: Some(5)
+            })
+        );
+    }
+}
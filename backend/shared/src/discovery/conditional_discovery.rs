@@ -0,0 +1,509 @@
+//! Discovery of conditional (data-aware) constraints
+//!
+//! Some constraints only hold for a subgroup of events/objects, characterized by their attribute values
+//! (e.g., "orders with a `price` of at least 500 have an `approve` event").
+//!
+//! Starting with all (sampled) events/objects of a type, the instances are split in a decision-tree style:
+//! For each attribute, candidate conditions (and their complements) are generated,
+//! i.e., numeric thresholds at quantiles of the values, single string values or boolean values.
+//! In each subgroup, count (and, for objects, eventually-follows) constraints are discovered.
+//! A constraint is reported for a subgroup if it holds for at least the requested fraction of the subgroup,
+//! but not for the enclosing group (i.e., without the last condition).
+//! The split yielding the highest gain in coverage is selected and the search continues in the resulting subgroups,
+//! up to [`MAX_CONDITION_DEPTH`] conditions.
+//!
+//! The conditions are added as attribute value filters to the root binding box of the constraint,
+//! so that only instances satisfying them are considered.
+//! Object attributes are checked for all values of the object over time (see [`ObjectValueFilterTimepoint::Always`]).
+use std::collections::HashSet;
+
+use itertools::Itertools;
+use process_mining::ocel::ocel_struct::OCELAttributeValue;
+use rayon::prelude::*;
+
+use crate::{
+    binding_box::{
+        structs::{
+            BindingBoxTreeNode, EventVariable, Filter, ObjectValueFilterTimepoint, ObjectVariable,
+            ValueFilter,
+        },
+        Binding, BindingBoxTree,
+    },
+    preprocessing::linked_ocel::{EventOrObjectIndex, IndexLinkedOCEL},
+};
+
+use super::{
+    advanced::{label_bindings, EventOrObjectType},
+    attribute_discovery::{attribute_values, format_value_filter, MAX_STRING_VALUES},
+    graph_discovery::{
+        discover_count_constraints_for_supporting_instances,
+        discover_ef_constraints_for_supporting_instances, get_instances, CountConstraint,
+        EFConstraint,
+    },
+    DiscoverySamplingOptions,
+};
+
+/// Maximal number of conditions of a discovered constraint (i.e., depth of the decision tree)
+pub static MAX_CONDITION_DEPTH: usize = 2;
+/// Maximal number of thresholds considered for splitting on a numeric attribute
+pub static NUM_NUMERIC_SPLIT_CANDIDATES: usize = 8;
+/// Subgroups need to contain at least this fraction of all (sampled) instances
+pub static MIN_SUBGROUP_FRACTION: f32 = 0.05;
+/// Subgroups need to contain at least this many instances
+pub static MIN_SUBGROUP_SIZE: usize = 10;
+
+/// Condition on an attribute of the root event/object
+#[derive(Debug, Clone)]
+pub struct AttributeCondition {
+    pub attribute_name: String,
+    pub value_filter: ValueFilter,
+}
+
+impl AttributeCondition {
+    pub fn get_condition_name(&self) -> String {
+        format!(
+            "{} '{}'",
+            format_value_filter(&self.value_filter),
+            self.attribute_name
+        )
+    }
+
+    /// Filter for the condition on the root event/object (bound to variable `0`)
+    pub fn to_filter(&self, root_type: &EventOrObjectType) -> Filter {
+        match root_type {
+            EventOrObjectType::Event(_) => Filter::EventAttributeValueFilter {
+                event: EventVariable(0),
+                attribute_name: self.attribute_name.clone(),
+                value_filter: self.value_filter.clone(),
+            },
+            EventOrObjectType::Object(_) => Filter::ObjectAttributeValueFilter {
+                object: ObjectVariable(0),
+                attribute_name: self.attribute_name.clone(),
+                at_time: ObjectValueFilterTimepoint::Always,
+                value_filter: self.value_filter.clone(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ConditionalConstraintKind {
+    Count(CountConstraint),
+    EventuallyFollows(EFConstraint),
+}
+
+impl ConditionalConstraintKind {
+    pub fn get_constraint_name(&self) -> String {
+        match self {
+            ConditionalConstraintKind::Count(c) => c.get_constraint_name(),
+            ConditionalConstraintKind::EventuallyFollows(c) => c.get_constraint_name(),
+        }
+    }
+    pub fn get_full_tree(&self) -> BindingBoxTree {
+        match self {
+            ConditionalConstraintKind::Count(c) => c.get_full_tree(),
+            ConditionalConstraintKind::EventuallyFollows(c) => c.get_full_tree(),
+        }
+    }
+    /// Tree without the root event/object (variable `0`) being bound, used for labeling instances
+    fn to_subtree(&self) -> BindingBoxTree {
+        match self {
+            ConditionalConstraintKind::Count(c) => c.to_subtree("A".to_string(), 0, 1),
+            ConditionalConstraintKind::EventuallyFollows(c) => {
+                c.to_subtree("A".to_string(), 0, 1, 2)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConditionalConstraint {
+    pub root_type: EventOrObjectType,
+    /// All conditions have to be satisfied by the root event/object
+    pub conditions: Vec<AttributeCondition>,
+    pub constraint: ConditionalConstraintKind,
+    /// Number of (sampled) instances satisfying the conditions
+    pub support: usize,
+    /// Fraction of the (sampled) instances satisfying the conditions, which also satisfy the constraint
+    pub coverage: f32,
+    /// Fraction of the instances satisfying all but the last condition, which satisfy the constraint
+    pub coverage_without_condition: f32,
+}
+
+impl ConditionalConstraint {
+    pub fn get_constraint_name(&self) -> String {
+        format!(
+            "{} if {}",
+            self.constraint.get_constraint_name(),
+            self.conditions
+                .iter()
+                .map(|c| c.get_condition_name())
+                .join(" and ")
+        )
+    }
+    pub fn get_full_tree(&self) -> BindingBoxTree {
+        let mut tree = self.constraint.get_full_tree();
+        // The root of count and eventually-follows constraint trees is always a box
+        if let BindingBoxTreeNode::Box(bbox, _) = &mut tree.nodes[0] {
+            bbox.filters
+                .extend(self.conditions.iter().map(|c| c.to_filter(&self.root_type)));
+        }
+        tree
+    }
+}
+
+/// A subgroup of instances satisfying a condition (in addition to the conditions of the enclosing group)
+struct Branch {
+    condition: AttributeCondition,
+    instances: Vec<EventOrObjectIndex>,
+    /// Count constraints holding in the subgroup (not necessarily conditional)
+    count_constraints: HashSet<CountConstraint>,
+    /// Constraints holding for the subgroup, but not for the enclosing group: (constraint, coverage, coverage in enclosing group)
+    conditional: Vec<(ConditionalConstraintKind, f32, f32)>,
+    gain: f32,
+}
+
+struct SearchContext<'a> {
+    ocel: &'a IndexLinkedOCEL,
+    coverage: f32,
+    root_type: EventOrObjectType,
+    attribute_names: Vec<&'a String>,
+    min_subgroup_size: usize,
+    sampling: &'a DiscoverySamplingOptions,
+}
+
+///
+/// Discover conditional constraints (see [module-level documentation](self)) for the given event/object type
+///
+pub fn discover_conditional_constraints(
+    ocel: &IndexLinkedOCEL,
+    coverage: f32,
+    ocel_type: EventOrObjectType,
+    sampling: &DiscoverySamplingOptions,
+) -> Vec<ConditionalConstraint> {
+    let attribute_names = match &ocel_type {
+        EventOrObjectType::Event(et) => ocel.ocel.event_types.iter().find(|t| &t.name == et),
+        EventOrObjectType::Object(ot) => ocel.ocel.object_types.iter().find(|t| &t.name == ot),
+    }
+    .map(|t| t.attributes.iter().map(|at| &at.name).collect_vec())
+    .unwrap_or_default();
+    let instances = get_instances(ocel, &ocel_type, sampling);
+    let count_constraints = discover_count_constraints_for_supporting_instances(
+        ocel,
+        coverage,
+        instances.iter(),
+        &ocel_type,
+    )
+    .into_iter()
+    .collect();
+    let ctx = SearchContext {
+        ocel,
+        coverage,
+        min_subgroup_size: MIN_SUBGROUP_SIZE
+            .max((instances.len() as f32 * MIN_SUBGROUP_FRACTION).ceil() as usize),
+        root_type: ocel_type,
+        attribute_names,
+        sampling,
+    };
+    let mut ret = Vec::new();
+    split_group(&ctx, &[], &instances, &count_constraints, &mut ret);
+    ret
+}
+
+/// Select the best split of the group and continue the search in the resulting subgroups
+fn split_group(
+    ctx: &SearchContext,
+    conditions: &[AttributeCondition],
+    instances: &[EventOrObjectIndex],
+    count_constraints: &HashSet<CountConstraint>,
+    ret: &mut Vec<ConditionalConstraint>,
+) {
+    if conditions.len() >= MAX_CONDITION_DEPTH {
+        return;
+    }
+    let bindings = instances.iter().map(instance_binding).collect_vec();
+    let best_split = ctx
+        .attribute_names
+        .iter()
+        .flat_map(|attribute_name| {
+            let values = instances
+                .iter()
+                .map(|index| attribute_values(ctx.ocel, index, attribute_name))
+                .collect_vec();
+            split_candidates(&values)
+                .into_iter()
+                .map(|(value_filter, complement)| {
+                    [Some(value_filter), complement]
+                        .into_iter()
+                        .flatten()
+                        .map(|value_filter| AttributeCondition {
+                            attribute_name: (*attribute_name).clone(),
+                            value_filter,
+                        })
+                        .collect_vec()
+                })
+                .collect_vec()
+        })
+        .collect_vec()
+        .into_par_iter()
+        .map(|split| {
+            split
+                .into_iter()
+                .filter_map(|condition| {
+                    evaluate_branch(ctx, condition, instances, &bindings, count_constraints)
+                })
+                .collect_vec()
+        })
+        .map(|branches| (branches.iter().map(|b| b.gain).sum::<f32>(), branches))
+        .filter(|(gain, _branches)| *gain > 0.0)
+        .max_by(|(gain1, _), (gain2, _)| gain1.total_cmp(gain2));
+
+    if let Some((_gain, branches)) = best_split {
+        for branch in branches {
+            let branch_conditions = conditions
+                .iter()
+                .cloned()
+                .chain(std::iter::once(branch.condition))
+                .collect_vec();
+            ret.extend(branch.conditional.into_iter().map(
+                |(constraint, coverage, coverage_without_condition)| ConditionalConstraint {
+                    root_type: ctx.root_type.clone(),
+                    conditions: branch_conditions.clone(),
+                    constraint,
+                    support: branch.instances.len(),
+                    coverage,
+                    coverage_without_condition,
+                },
+            ));
+            split_group(
+                ctx,
+                &branch_conditions,
+                &branch.instances,
+                &branch.count_constraints,
+                ret,
+            );
+        }
+    }
+}
+
+///
+/// Discover the constraints for the subgroup of instances satisfying the condition
+///
+/// Returns `None` if the subgroup is too small
+///
+fn evaluate_branch(
+    ctx: &SearchContext,
+    condition: AttributeCondition,
+    instances: &[EventOrObjectIndex],
+    bindings: &Vec<Binding>,
+    count_constraints: &HashSet<CountConstraint>,
+) -> Option<Branch> {
+    let filter = condition.to_filter(&ctx.root_type);
+    let in_subgroup = bindings
+        .iter()
+        .map(|b| filter.check_binding(b, ctx.ocel))
+        .collect_vec();
+    let subgroup = instances
+        .iter()
+        .zip(&in_subgroup)
+        .filter(|(_, in_subgroup)| **in_subgroup)
+        .map(|(index, _)| *index)
+        .collect_vec();
+    if subgroup.len() < ctx.min_subgroup_size || subgroup.len() == instances.len() {
+        return None;
+    }
+    let subgroup_count_constraints: HashSet<_> =
+        discover_count_constraints_for_supporting_instances(
+            ctx.ocel,
+            ctx.coverage,
+            subgroup.iter(),
+            &ctx.root_type,
+        )
+        .into_iter()
+        .collect();
+    // Count constraints also discovered for the enclosing group are not conditional
+    let mut candidates = subgroup_count_constraints
+        .iter()
+        .filter(|c| !count_constraints.contains(c))
+        .cloned()
+        .map(ConditionalConstraintKind::Count)
+        .collect_vec();
+    if let EventOrObjectType::Object(ot) = &ctx.root_type {
+        candidates.extend(
+            discover_ef_constraints_for_supporting_instances(
+                ctx.ocel,
+                ctx.coverage,
+                subgroup.iter().filter_map(|index| match index {
+                    EventOrObjectIndex::Object(oi) => Some(*oi),
+                    EventOrObjectIndex::Event(_) => None,
+                }),
+                ot,
+                ctx.sampling,
+            )
+            .into_iter()
+            .map(ConditionalConstraintKind::EventuallyFollows),
+        );
+    }
+    let subgroup_fraction = subgroup.len() as f32 / instances.len() as f32;
+    let conditional = candidates
+        .into_iter()
+        .filter_map(|constraint| {
+            let labels = label_bindings(ctx.ocel, bindings, &constraint.to_subtree());
+            let num_satisfied = labels.iter().filter(|sat| **sat).count();
+            let num_satisfied_in_subgroup = labels
+                .iter()
+                .zip(&in_subgroup)
+                .filter(|(sat, in_subgroup)| **sat && **in_subgroup)
+                .count();
+            let coverage = num_satisfied_in_subgroup as f32 / subgroup.len() as f32;
+            let coverage_without_condition = num_satisfied as f32 / instances.len() as f32;
+            (coverage >= ctx.coverage && coverage_without_condition < ctx.coverage).then_some((
+                constraint,
+                coverage,
+                coverage_without_condition,
+            ))
+        })
+        .collect_vec();
+    let gain = conditional
+        .iter()
+        .map(|(_, coverage, coverage_without_condition)| {
+            (coverage - coverage_without_condition) * subgroup_fraction
+        })
+        .sum();
+    Some(Branch {
+        condition,
+        instances: subgroup,
+        count_constraints: subgroup_count_constraints,
+        conditional,
+        gain,
+    })
+}
+
+/// Binding of the instance to variable `0`
+fn instance_binding(index: &EventOrObjectIndex) -> Binding {
+    match index {
+        EventOrObjectIndex::Event(ei) => Binding::default().expand_with_ev(EventVariable(0), *ei),
+        EventOrObjectIndex::Object(oi) => Binding::default().expand_with_ob(ObjectVariable(0), *oi),
+    }
+}
+
+///
+/// Candidate conditions for splitting instances (given by their attribute values), each with an optional complement condition
+///
+/// * Numeric values: Thresholds at quantiles of the values (`≤ threshold`, complement `≥` next larger value)
+/// * String values: The most frequent values (complement: all other values, if there are at most [`MAX_STRING_VALUES`])
+/// * Boolean values: `true` (complement: `false`)
+///
+fn split_candidates(
+    values: &[Vec<&OCELAttributeValue>],
+) -> Vec<(ValueFilter, Option<ValueFilter>)> {
+    let all_values = values
+        .iter()
+        .flatten()
+        .filter(|v| !matches!(v, OCELAttributeValue::Null))
+        .collect_vec();
+    if all_values.is_empty() {
+        return Vec::new();
+    }
+    if all_values.iter().all(|v| {
+        matches!(
+            v,
+            OCELAttributeValue::Integer(_) | OCELAttributeValue::Float(_)
+        )
+    }) {
+        let is_integer = all_values
+            .iter()
+            .all(|v| matches!(v, OCELAttributeValue::Integer(_)));
+        let sorted = all_values
+            .iter()
+            .filter_map(|v| match v {
+                OCELAttributeValue::Integer(i) => Some(*i as f64),
+                OCELAttributeValue::Float(f) if !f.is_nan() => Some(*f),
+                _ => None,
+            })
+            .sorted_by(f64::total_cmp)
+            .collect_vec();
+        let n = sorted.len();
+        if n == 0 {
+            return Vec::new();
+        }
+        (1..=NUM_NUMERIC_SPLIT_CANDIDATES)
+            .map(|k| sorted[k * (n - 1) / (NUM_NUMERIC_SPLIT_CANDIDATES + 1)])
+            .dedup()
+            .filter_map(|threshold| {
+                // Smallest value larger than the threshold
+                let next = sorted[sorted.partition_point(|v| *v <= threshold)..]
+                    .first()
+                    .copied()?;
+                Some(if is_integer {
+                    (
+                        ValueFilter::Integer {
+                            min: None,
+                            max: Some(threshold as i64),
+                        },
+                        Some(ValueFilter::Integer {
+                            min: Some(next as i64),
+                            max: None,
+                        }),
+                    )
+                } else {
+                    (
+                        ValueFilter::Float {
+                            min: None,
+                            max: Some(threshold),
+                        },
+                        Some(ValueFilter::Float {
+                            min: Some(next),
+                            max: None,
+                        }),
+                    )
+                })
+            })
+            .collect()
+    } else if all_values
+        .iter()
+        .all(|v| matches!(v, OCELAttributeValue::String(_)))
+    {
+        let value_frequencies = all_values
+            .iter()
+            .filter_map(|v| match v {
+                OCELAttributeValue::String(s) => Some(s),
+                _ => None,
+            })
+            .counts();
+        if value_frequencies.len() < 2 {
+            return Vec::new();
+        }
+        value_frequencies
+            .iter()
+            .sorted_by(|(v1, c1), (v2, c2)| c2.cmp(c1).then_with(|| v1.cmp(v2)))
+            .take(MAX_STRING_VALUES)
+            .map(|(value, _count)| {
+                let others = value_frequencies
+                    .keys()
+                    .filter(|other| *other != value)
+                    .sorted()
+                    .map(|other| (*other).clone())
+                    .collect_vec();
+                (
+                    ValueFilter::String {
+                        is_in: vec![(*value).clone()],
+                    },
+                    (others.len() <= MAX_STRING_VALUES)
+                        .then_some(ValueFilter::String { is_in: others }),
+                )
+            })
+            .collect()
+    } else if all_values
+        .iter()
+        .all(|v| matches!(v, OCELAttributeValue::Boolean(_)))
+    {
+        vec![(
+            ValueFilter::Boolean { is_true: true },
+            Some(ValueFilter::Boolean { is_true: false }),
+        )]
+    } else {
+        // Mixed or time values
+        Vec::new()
+    }
+}
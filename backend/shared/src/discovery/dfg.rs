@@ -0,0 +1,249 @@
+//! Discovery of object-centric directly-follows graphs (OC-DFGs)
+//!
+//! For each object type, the events of every object are sorted by time and consecutive events
+//! form a directly-follows arc between their event types.
+//! The per-type graphs share the event type nodes, resulting in a directly-follows multigraph.
+use std::{collections::HashMap, fmt::Write};
+
+use itertools::Itertools;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{
+    binding_box::BindingBoxTree, escape::dot_string, preprocessing::linked_ocel::IndexLinkedOCEL,
+};
+
+use super::graph_discovery::EFConstraint;
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoverOCDFGRequest {
+    /// Object types to include (all object types if not set)
+    pub object_types: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DFGArcConstraintRequest {
+    pub object_type: String,
+    pub arc: DFGArc,
+    /// If true, the observed min./max. duration of the arc are used as time bounds of the constraint
+    pub use_duration_bounds: bool,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OCDFG {
+    pub object_types: Vec<ObjectTypeDFG>,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectTypeDFG {
+    pub object_type: String,
+    pub num_objects: usize,
+    /// Number of events per event type (counted once per related object)
+    pub activities: Vec<ActivityCount>,
+    /// Number of objects starting with an event of the event type
+    pub start_activities: Vec<ActivityCount>,
+    /// Number of objects ending with an event of the event type
+    pub end_activities: Vec<ActivityCount>,
+    pub arcs: Vec<DFGArc>,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityCount {
+    pub activity: String,
+    pub count: usize,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DFGArc {
+    pub from: String,
+    pub to: String,
+    pub count: usize,
+    pub mean_duration_sec: f64,
+    pub median_duration_sec: f64,
+    pub min_duration_sec: f64,
+    pub max_duration_sec: f64,
+}
+
+impl DFGArc {
+    ///
+    /// Eventually-follows constraint for this arc (for all objects of the given type)
+    ///
+    /// If `use_duration_bounds` is set, the observed min./max. durations are used as time bounds
+    ///
+    pub fn to_ef_constraint(&self, object_type: &str, use_duration_bounds: bool) -> EFConstraint {
+        EFConstraint {
+            from_ev_type: self.from.clone(),
+            to_ev_type: self.to.clone(),
+            min_duration_sec: use_duration_bounds.then_some(self.min_duration_sec),
+            max_duration_sec: use_duration_bounds.then_some(self.max_duration_sec),
+            for_object_type: object_type.to_string(),
+        }
+    }
+}
+
+/// Named eventually-follows [`BindingBoxTree`] constraint for a DFG arc (see [`DFGArc::to_ef_constraint`])
+pub fn dfg_arc_to_constraint(req: &DFGArcConstraintRequest) -> (String, BindingBoxTree) {
+    let c = req
+        .arc
+        .to_ef_constraint(&req.object_type, req.use_duration_bounds);
+    (c.get_constraint_name(), c.get_full_tree())
+}
+
+///
+/// Discover the OC-DFG of the given object types (or all object types)
+///
+pub fn discover_ocdfg(ocel: &IndexLinkedOCEL, object_types: Option<&[String]>) -> OCDFG {
+    let object_types = ocel
+        .ocel
+        .object_types
+        .iter()
+        .map(|ot| &ot.name)
+        .filter(|ot| object_types.is_none_or(|types| types.contains(ot)))
+        .collect_vec();
+    OCDFG {
+        object_types: object_types
+            .into_par_iter()
+            .map(|ot| discover_dfg_for_type(ocel, ot))
+            .collect(),
+    }
+}
+
+fn discover_dfg_for_type(ocel: &IndexLinkedOCEL, object_type: &String) -> ObjectTypeDFG {
+    let mut activities: HashMap<&String, usize> = HashMap::new();
+    let mut start_activities: HashMap<&String, usize> = HashMap::new();
+    let mut end_activities: HashMap<&String, usize> = HashMap::new();
+    let mut arc_durations: HashMap<(&String, &String), Vec<f64>> = HashMap::new();
+    let objects = ocel
+        .objects_of_type
+        .get(object_type)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for ob_index in objects {
+        let events = ocel
+            .object_events_map
+            .get(ob_index)
+            .into_iter()
+            .flatten()
+            .map(|ev_index| &ocel.ocel.events[ev_index.0])
+            .sorted_by_key(|ev| ev.time)
+            .collect_vec();
+        for ev in &events {
+            *activities.entry(&ev.event_type).or_default() += 1;
+        }
+        if let (Some(first), Some(last)) = (events.first(), events.last()) {
+            *start_activities.entry(&first.event_type).or_default() += 1;
+            *end_activities.entry(&last.event_type).or_default() += 1;
+        }
+        for (a, b) in events.iter().tuple_windows() {
+            let duration = (b.time - a.time).num_milliseconds() as f64 / 1000.0;
+            arc_durations
+                .entry((&a.event_type, &b.event_type))
+                .or_default()
+                .push(duration);
+        }
+    }
+    let to_activity_counts = |counts: HashMap<&String, usize>| {
+        counts
+            .into_iter()
+            .map(|(activity, count)| ActivityCount {
+                activity: activity.clone(),
+                count,
+            })
+            .sorted_by(|a, b| {
+                b.count
+                    .cmp(&a.count)
+                    .then_with(|| a.activity.cmp(&b.activity))
+            })
+            .collect()
+    };
+    ObjectTypeDFG {
+        object_type: object_type.clone(),
+        num_objects: objects.len(),
+        activities: to_activity_counts(activities),
+        start_activities: to_activity_counts(start_activities),
+        end_activities: to_activity_counts(end_activities),
+        arcs: arc_durations
+            .into_iter()
+            .map(|((from, to), mut durations)| {
+                durations.sort_by(|a, b| a.total_cmp(b));
+                let n = durations.len();
+                let median = if n % 2 == 0 {
+                    (durations[n / 2 - 1] + durations[n / 2]) / 2.0
+                } else {
+                    durations[n / 2]
+                };
+                DFGArc {
+                    from: from.clone(),
+                    to: to.clone(),
+                    count: n,
+                    mean_duration_sec: durations.iter().sum::<f64>() / n as f64,
+                    median_duration_sec: median,
+                    min_duration_sec: durations[0],
+                    max_duration_sec: durations[n - 1],
+                }
+            })
+            .sorted_by(|a, b| (&a.from, &a.to).cmp(&(&b.from, &b.to)))
+            .collect(),
+    }
+}
+
+///
+/// Render the OC-DFG in the DOT format (Graphviz)
+///
+/// Event types are shared nodes, while every object type has its own start/end nodes and arcs
+///
+pub fn ocdfg_to_dot(dfg: &OCDFG) -> String {
+    let mut dot = String::from("digraph OCDFG {\n  rankdir=LR;\n  node [shape=box];\n");
+    let activities = dfg
+        .object_types
+        .iter()
+        .flat_map(|t| &t.activities)
+        .map(|a| &a.activity)
+        .unique()
+        .sorted()
+        .collect_vec();
+    for (i, activity) in activities.iter().enumerate() {
+        writeln!(dot, "  a{i} [label={}];", dot_string(activity)).unwrap();
+    }
+    let activity_node = |activity: &String| {
+        format!(
+            "a{}",
+            activities.iter().position(|a| *a == activity).unwrap_or(0)
+        )
+    };
+    for (t, type_dfg) in dfg.object_types.iter().enumerate() {
+        let ot = dot_string(&type_dfg.object_type);
+        writeln!(dot, "  start{t} [shape=circle, label={ot}];").unwrap();
+        writeln!(dot, "  end{t} [shape=doublecircle, label={ot}];").unwrap();
+        for a in &type_dfg.start_activities {
+            let to = activity_node(&a.activity);
+            writeln!(dot, "  start{t} -> {to} [label=\"{}\"];", a.count).unwrap();
+        }
+        for a in &type_dfg.end_activities {
+            let from = activity_node(&a.activity);
+            writeln!(dot, "  {from} -> end{t} [label=\"{}\"];", a.count).unwrap();
+        }
+        for arc in &type_dfg.arcs {
+            let label = dot_string(&format!("{} ({})", type_dfg.object_type, arc.count));
+            let (from, to) = (activity_node(&arc.from), activity_node(&arc.to));
+            writeln!(dot, "  {from} -> {to} [label={label}];").unwrap();
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
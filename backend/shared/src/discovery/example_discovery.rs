@@ -0,0 +1,418 @@
+//! Learning constraints from labeled examples
+//!
+//! Given events or objects labeled as good (i.e., conforming) or bad (i.e., problematic),
+//! candidate constraints are generated using the existing discovery techniques:
+//!
+//! * count (and, for objects, eventually-follows) constraints holding for the good examples or the (sampled) instances of their type,
+//! * OR constraints (see [`discover_or_constraints_new`]), and
+//! * combinations of the count/eventually-follows constraints (see [`test_tree_combinations`]).
+//!
+//! A constraint classifies an example as bad if the example violates it.
+//! Candidates are ranked by how well they separate the bad from the good examples (F1 score),
+//! and returned with their precision (fraction of flagged examples which are bad)
+//! and recall (fraction of bad examples which are flagged).
+use itertools::Itertools;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    binding_box::{
+        structs::{BindingBoxTreeNode, EventVariable, ObjectVariable, Variable},
+        Binding, BindingBoxTree,
+    },
+    preprocessing::linked_ocel::{EventOrObjectIndex, IndexLinkedOCEL},
+};
+
+use super::{
+    advanced::{label_bindings, test_tree_combinations, EventOrObjectType},
+    graph_discovery::{
+        discover_count_constraints_for_supporting_instances,
+        discover_ef_constraints_for_supporting_instances, discover_or_constraints_new,
+        get_instances,
+    },
+    redundancy::anchor_subtree,
+    DiscoverySamplingOptions,
+};
+
+/// Default number of returned constraints
+pub static DEFAULT_MAX_LEARNED_CONSTRAINTS: usize = 10;
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LearnFromExamplesRequest {
+    /// IDs of conforming events/objects
+    pub good_ids: Vec<String>,
+    /// IDs of problematic events/objects
+    pub bad_ids: Vec<String>,
+    /// If the IDs are object IDs (otherwise, event IDs)
+    pub is_object: bool,
+    /// Fraction of the good examples (or instances of their type) candidate constraints are discovered for
+    pub cover_fraction: f32,
+    /// Maximal number of returned constraints (defaults to [`DEFAULT_MAX_LEARNED_CONSTRAINTS`])
+    pub max_constraints: Option<usize>,
+    /// Sampling of events/objects used for discovery (defaults to [`DiscoverySamplingOptions::default`])
+    pub sampling: Option<DiscoverySamplingOptions>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LearnedConstraint {
+    pub name: String,
+    pub tree: BindingBoxTree,
+    /// Fraction of the examples violating the constraint which are labeled as bad
+    pub precision: f32,
+    /// Fraction of the bad examples violating the constraint
+    pub recall: f32,
+    /// Harmonic mean of precision and recall
+    pub f1_score: f32,
+    /// IDs of the good examples violating the constraint
+    pub violating_good_ids: Vec<String>,
+    /// IDs of the bad examples satisfying the constraint
+    pub satisfying_bad_ids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LearnFromExamplesResponse {
+    /// Type of the labeled examples
+    pub ocel_type: EventOrObjectType,
+    /// Best separating constraints (sorted by F1 score, descending)
+    pub constraints: Vec<LearnedConstraint>,
+}
+
+///
+/// Learn constraints separating the bad from the good examples (see [module-level documentation](self))
+///
+/// Returns `None` if an ID does not exist, if the examples have different types or if there are no bad examples
+///
+pub fn learn_constraints_from_examples(
+    ocel: &IndexLinkedOCEL,
+    req: &LearnFromExamplesRequest,
+) -> Option<LearnFromExamplesResponse> {
+    let sampling = req.sampling.clone().unwrap_or_default();
+    let get_index = |id: &String| match req.is_object {
+        true => ocel
+            .index_of_ob(id)
+            .map(|oi| EventOrObjectIndex::Object(*oi)),
+        false => ocel
+            .index_of_ev(id)
+            .map(|ei| EventOrObjectIndex::Event(*ei)),
+    };
+    let good = req
+        .good_ids
+        .iter()
+        .map(get_index)
+        .collect::<Option<Vec<_>>>()?;
+    let bad = req
+        .bad_ids
+        .iter()
+        .map(get_index)
+        .collect::<Option<Vec<_>>>()?;
+    if bad.is_empty() {
+        return None;
+    }
+    let ocel_type = good
+        .iter()
+        .chain(bad.iter())
+        .map(|index| match index {
+            EventOrObjectIndex::Event(ei) => {
+                EventOrObjectType::Event(ocel.ocel.events[ei.0].event_type.clone())
+            }
+            EventOrObjectIndex::Object(oi) => {
+                EventOrObjectType::Object(ocel.ocel.objects[oi.0].object_type.clone())
+            }
+        })
+        .all_equal_value()
+        .ok()?;
+
+    let candidates = candidate_trees(ocel, req.cover_fraction, &ocel_type, &good, &bad, &sampling);
+    let examples = good
+        .iter()
+        .zip(&req.good_ids)
+        .map(|(index, id)| (index, id, false))
+        .chain(
+            bad.iter()
+                .zip(&req.bad_ids)
+                .map(|(index, id)| (index, id, true)),
+        )
+        .collect_vec();
+    let constraints = candidates
+        .into_par_iter()
+        .filter_map(|(name, tree)| {
+            let (_key, variable, subtree) = anchor_subtree(&tree)?;
+            let bindings = examples
+                .iter()
+                .map(|(index, _, _)| instance_binding(index, &variable))
+                .collect::<Option<Vec<_>>>()?;
+            let labels = label_bindings(ocel, &bindings, &subtree);
+            let mut violating_good_ids = Vec::new();
+            let mut satisfying_bad_ids = Vec::new();
+            let mut true_positives = 0;
+            for ((_, id, is_bad), satisfied) in examples.iter().zip(labels) {
+                match (is_bad, satisfied) {
+                    (true, false) => true_positives += 1,
+                    (true, true) => satisfying_bad_ids.push((*id).clone()),
+                    (false, false) => violating_good_ids.push((*id).clone()),
+                    (false, true) => {}
+                }
+            }
+            if true_positives == 0 {
+                return None;
+            }
+            let precision =
+                true_positives as f32 / (true_positives + violating_good_ids.len()) as f32;
+            let recall = true_positives as f32 / bad.len() as f32;
+            Some(LearnedConstraint {
+                name,
+                tree,
+                precision,
+                recall,
+                f1_score: 2.0 * precision * recall / (precision + recall),
+                violating_good_ids,
+                satisfying_bad_ids,
+            })
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .sorted_by(|a, b| {
+            b.f1_score
+                .total_cmp(&a.f1_score)
+                .then_with(|| b.precision.total_cmp(&a.precision))
+                .then_with(|| a.tree.nodes.len().cmp(&b.tree.nodes.len()))
+                .then_with(|| a.name.cmp(&b.name))
+        })
+        .take(
+            req.max_constraints
+                .unwrap_or(DEFAULT_MAX_LEARNED_CONSTRAINTS),
+        )
+        .collect();
+    Some(LearnFromExamplesResponse {
+        ocel_type,
+        constraints,
+    })
+}
+
+/// Binding of the instance to the given variable (`None` if the variable does not fit the instance)
+fn instance_binding(index: &EventOrObjectIndex, variable: &Variable) -> Option<Binding> {
+    match (index, variable) {
+        (EventOrObjectIndex::Event(ei), Variable::Event(ev)) => {
+            Some(Binding::default().expand_with_ev(*ev, *ei))
+        }
+        (EventOrObjectIndex::Object(oi), Variable::Object(ov)) => {
+            Some(Binding::default().expand_with_ob(*ov, *oi))
+        }
+        _ => None,
+    }
+}
+
+/// Candidate constraints (name and full tree) for the type of the examples
+fn candidate_trees(
+    ocel: &IndexLinkedOCEL,
+    coverage: f32,
+    ocel_type: &EventOrObjectType,
+    good: &[EventOrObjectIndex],
+    bad: &[EventOrObjectIndex],
+    sampling: &DiscoverySamplingOptions,
+) -> Vec<(String, BindingBoxTree)> {
+    let population = get_instances(ocel, ocel_type, sampling);
+    let mut ret: Vec<(String, BindingBoxTree)> = Vec::new();
+    // Subtrees without the example being bound (variable 0), used for combinations
+    let mut subtrees: Vec<(String, BindingBoxTree)> = Vec::new();
+    for supporting_instances in [good, &population] {
+        for c in discover_count_constraints_for_supporting_instances(
+            ocel,
+            coverage,
+            supporting_instances.iter(),
+            ocel_type,
+        ) {
+            ret.push((c.get_constraint_name(), c.get_full_tree()));
+            subtrees.push((c.get_constraint_name(), c.to_subtree("X".to_string(), 0, 2)));
+        }
+        if let EventOrObjectType::Object(ot) = ocel_type {
+            for c in discover_ef_constraints_for_supporting_instances(
+                ocel,
+                coverage,
+                supporting_instances.iter().filter_map(|index| match index {
+                    EventOrObjectIndex::Object(oi) => Some(*oi),
+                    EventOrObjectIndex::Event(_) => None,
+                }),
+                ot,
+                sampling,
+            ) {
+                ret.push((c.get_constraint_name(), c.get_full_tree()));
+                subtrees.push((
+                    c.get_constraint_name(),
+                    c.to_subtree("X".to_string(), 0, 2, 3),
+                ));
+            }
+        }
+    }
+    ret.extend(discover_or_constraints_new(
+        ocel, ocel_type, coverage, sampling,
+    ));
+
+    let subtrees = dedup_trees(subtrees);
+    let variable = match ocel_type {
+        EventOrObjectType::Event(_) => Variable::Event(EventVariable(0)),
+        EventOrObjectType::Object(_) => Variable::Object(ObjectVariable(0)),
+    };
+    let bindings = good
+        .iter()
+        .chain(bad)
+        .filter_map(|index| instance_binding(index, &variable))
+        .collect_vec();
+    for tree in test_tree_combinations(
+        ocel,
+        subtrees.iter().map(|(_, t)| t.clone()).collect(),
+        bindings,
+        variable,
+        ocel_type,
+    ) {
+        ret.push((combination_name(&tree, &subtrees), tree));
+    }
+
+    dedup_trees(ret)
+}
+
+/// Remove duplicate trees (keeping the first occurrence)
+///
+/// Different trees can have the same name (e.g., eventually-follows constraints with different time windows),
+/// so trees are compared instead of names.
+fn dedup_trees(trees: Vec<(String, BindingBoxTree)>) -> Vec<(String, BindingBoxTree)> {
+    let mut ret: Vec<(String, BindingBoxTree)> = Vec::new();
+    for (name, tree) in trees {
+        if !ret.iter().any(|(_, t)| t == &tree) {
+            ret.push((name, tree));
+        }
+    }
+    ret
+}
+
+/// Name of a combination of two subtrees (see [`combine_subtrees`](super::advanced::combine_subtrees)), based on the names of the combined subtrees
+fn combination_name(tree: &BindingBoxTree, subtrees: &[(String, BindingBoxTree)]) -> String {
+    let second_start = match tree.nodes.first() {
+        Some(BindingBoxTreeNode::Box(_, children)) if children.len() == 2 && children[0] == 1 => {
+            children[1]
+        }
+        _ => return "Combination".to_string(),
+    };
+    // Combined subtrees are identified by all their nodes (e.g., eventually-follows subtrees only differ in their child)
+    let name_of = |nodes: &[BindingBoxTreeNode], offset: usize| {
+        subtrees
+            .iter()
+            .find(|(_, subtree)| is_shifted_copy(nodes, subtree, offset))
+            .map(|(name, _)| name)
+    };
+    match tree
+        .nodes
+        .get(1..second_start)
+        .zip(tree.nodes.get(second_start..))
+    {
+        Some((first, second)) => match (name_of(first, 1), name_of(second, second_start)) {
+            (Some(name1), Some(name2)) => format!("{name1} or {name2}"),
+            _ => "Combination".to_string(),
+        },
+        None => "Combination".to_string(),
+    }
+}
+
+/// Whether the nodes are the nodes of the subtree, with all child indices shifted by `offset`
+fn is_shifted_copy(nodes: &[BindingBoxTreeNode], subtree: &BindingBoxTree, offset: usize) -> bool {
+    nodes.len() == subtree.nodes.len()
+        && nodes
+            .iter()
+            .zip(&subtree.nodes)
+            .all(|(node, sub_node)| match (node, sub_node) {
+                (
+                    BindingBoxTreeNode::Box(bbox, children),
+                    BindingBoxTreeNode::Box(sub_bbox, sub_children),
+                ) => {
+                    bbox == sub_bbox
+                        && children.len() == sub_children.len()
+                        && children
+                            .iter()
+                            .zip(sub_children)
+                            .all(|(c, sub_c)| *c == sub_c + offset)
+                }
+                _ => node == sub_node,
+            })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{binding_box::test::example_linked_ocel, discovery::graph_discovery::EFConstraint};
+
+    use super::{super::advanced::combine_subtrees, *};
+
+    fn place_then_pick(max_duration_sec: f64) -> BindingBoxTree {
+        EFConstraint {
+            from_ev_type: "place order".to_string(),
+            to_ev_type: "pick item".to_string(),
+            min_duration_sec: Some(0.0),
+            max_duration_sec: Some(max_duration_sec),
+            for_object_type: "orders".to_string(),
+        }
+        .to_subtree("X".to_string(), 0, 2, 3)
+    }
+
+    #[test]
+    fn ef_candidates_with_different_windows_are_kept() {
+        let ocel = example_linked_ocel();
+        let orders = |ids: &[&str]| {
+            ids.iter()
+                .map(|id| EventOrObjectIndex::Object(*ocel.index_of_ob(&id.to_string()).unwrap()))
+                .collect_vec()
+        };
+        // Only even orders are paid: The delays from picking items to paying differ
+        // between the good examples and all orders
+        let candidates = candidate_trees(
+            &ocel,
+            0.4,
+            &EventOrObjectType::Object("orders".to_string()),
+            &orders(&["o0", "o2", "o4"]),
+            &orders(&["o1"]),
+            &DiscoverySamplingOptions {
+                full_data: true,
+                ..Default::default()
+            },
+        );
+        let pick_then_pay = candidates
+            .iter()
+            .filter(|(name, _)| name == "Quick 'pick item' -> 'pay order' for 'orders'")
+            .map(|(_, tree)| tree)
+            .collect_vec();
+        assert_eq!(pick_then_pay.len(), 2);
+        assert_ne!(pick_then_pay[0], pick_then_pay[1]);
+        for (i, (_, tree)) in candidates.iter().enumerate() {
+            assert!(!candidates[..i].iter().any(|(_, t)| t == tree));
+        }
+    }
+
+    #[test]
+    fn combinations_are_named_by_all_nodes() {
+        let subtrees = vec![
+            ("within 10 minutes".to_string(), place_then_pick(600.0)),
+            ("within an hour".to_string(), place_then_pick(3600.0)),
+        ];
+        let combine = |i: usize, j: usize| {
+            combine_subtrees(
+                &subtrees[i].1,
+                &subtrees[j].1,
+                &Variable::Object(ObjectVariable(0)),
+                &EventOrObjectType::Object("orders".to_string()),
+            )
+        };
+        assert_eq!(
+            combination_name(&combine(0, 1), &subtrees),
+            "within 10 minutes or within an hour"
+        );
+        assert_eq!(
+            combination_name(&combine(1, 0), &subtrees),
+            "within an hour or within 10 minutes"
+        );
+        assert_eq!(
+            combination_name(&combine(0, 1), &subtrees[..1]),
+            "Combination"
+        );
+    }
+}
@@ -14,12 +14,7 @@ use itertools::Itertools;
 //     layout::{Axis, ColorAxis},
 //     Layout, Plot, Scatter,
 // };
-use rand::{
-    // random,
-    rngs::StdRng,
-    seq::IteratorRandom,
-    SeedableRng,
-};
+// use rand::random;
 
 use crate::{
     binding_box::{
@@ -31,7 +26,7 @@ use crate::{
     },
     discovery::{
         advanced::{binding_to_instances, generate_sample_bindings, label_bindings},
-        RNG_SEED, SAMPLE_FRAC, SAMPLE_MIN_NUM_INSTANCES,
+        DiscoverySamplingOptions, SAMPLE_MIN_NUM_EVENTS_PER_OBJECT,
     },
     preprocessing::linked_ocel::{EventOrObjectIndex, IndexLinkedOCEL, OCELNodeRef, ObjectIndex},
 };
@@ -49,57 +44,37 @@ pub enum RefType {
 pub fn get_instances(
     ocel: &IndexLinkedOCEL,
     ocel_type: &EventOrObjectType,
+    sampling: &DiscoverySamplingOptions,
 ) -> Vec<EventOrObjectIndex> {
-    let mut rng = StdRng::seed_from_u64(RNG_SEED);
-    let instances: Option<Vec<_>> = match &ocel_type {
-        EventOrObjectType::Event(et) => {
-            if let Some(o_indices) = ocel.events_of_type.get(et) {
-                let instances = if o_indices.len() >= SAMPLE_MIN_NUM_INSTANCES {
-                    let sample_count = (o_indices.len() as f32 * SAMPLE_FRAC).ceil() as usize;
-                    o_indices.iter().choose_multiple(&mut rng, sample_count)
-                } else {
-                    o_indices.iter().collect()
-                };
-                Some(
-                    instances
-                        .into_iter()
-                        .map(|i| EventOrObjectIndex::Event(*i))
-                        .collect(),
-                )
-            } else {
-                None
-            }
-        }
-        EventOrObjectType::Object(ot) => {
-            if let Some(o_indices) = ocel.objects_of_type.get(ot) {
-                let instances = if o_indices.len() >= SAMPLE_MIN_NUM_INSTANCES {
-                    let sample_count = (o_indices.len() as f32 * SAMPLE_FRAC).ceil() as usize;
-                    o_indices.iter().choose_multiple(&mut rng, sample_count)
-                } else {
-                    o_indices.iter().collect()
-                };
-                Some(
-                    instances
-                        .into_iter()
-                        .map(|i| EventOrObjectIndex::Object(*i))
-                        .collect(),
-                )
-            } else {
-                None
-            }
-        }
+    let mut rng = sampling.rng();
+    let instances: Vec<_> = match &ocel_type {
+        EventOrObjectType::Event(et) => ocel
+            .events_of_type
+            .get(et)
+            .into_iter()
+            .flatten()
+            .map(|i| EventOrObjectIndex::Event(*i))
+            .collect(),
+        EventOrObjectType::Object(ot) => ocel
+            .objects_of_type
+            .get(ot)
+            .into_iter()
+            .flatten()
+            .map(|i| EventOrObjectIndex::Object(*i))
+            .collect(),
     };
-    instances.unwrap_or_default()
+    sampling.sample(instances, sampling.min_num_instances, &mut rng)
 }
 
 pub fn discover_count_constraints(
     ocel: &IndexLinkedOCEL,
     coverage: f32,
     ocel_type: EventOrObjectType,
+    sampling: &DiscoverySamplingOptions,
 ) -> Vec<CountConstraint> {
     let now = Instant::now();
     let mut ret = Vec::new();
-    let instances: Vec<_> = get_instances(ocel, &ocel_type);
+    let instances: Vec<_> = get_instances(ocel, &ocel_type, sampling);
     ret.extend(discover_count_constraints_for_supporting_instances(
         ocel,
         coverage,
@@ -191,9 +166,7 @@ pub fn discover_count_constraints_for_supporting_instances<
                     //     );
                     // }
                     // Otherwise, not interesting (no deviation between values)
-                    for (c_min, c_max) in
-                        get_range_with_coverage(counts, coverage, mean, std_deviation)
-                    {
+                    for (c_min, c_max) in get_range_with_coverage(counts, coverage) {
                         // println!("Counts {} Mean {} stdDev {} for min {} max {} FOR {ref_type:?} {ocel_type}",counts.len(),mean, std_deviation,min,max);
                         ret.push(CountConstraint {
                             min_count: if c_min > 0 && c_min < *min {
@@ -223,128 +196,92 @@ pub fn discover_count_constraints_for_supporting_instances<
     ret
 }
 
-fn get_range_with_coverage(
-    values: &[usize],
-    coverage: f32,
-    mean: f32,
-    std_deviation: f32,
-) -> HashSet<(usize, usize)> {
-    let mean_usize = mean.floor() as usize;
-    let step_size = (0.01 * std_deviation).round().max(1.0) as usize;
-    let mut ret: HashSet<_> = vec![
-        get_range_with_coverage_inner(
-            values,
-            coverage,
-            mean_usize,
-            step_size,
-            Direction::Symmetric,
-        ),
-        get_range_with_coverage_inner(values, coverage, 0, step_size, Direction::Increase),
-        get_range_with_coverage_inner(
-            values,
-            coverage,
-            10 + 10 * mean_usize,
-            step_size,
-            Direction::Decrease,
-        ),
-    ]
-    .into_iter()
-    .flatten()
-    .collect();
-    let mut remove_from_ret = HashSet::new();
-    for (min, max) in &ret {
-        if ret
-            .iter()
-            .any(|(min2, max2)| min2 >= min && max2 <= max && (min, max) != (min2, max2))
-        {
-            remove_from_ret.insert((*min, *max));
-        }
-    }
-    for k in remove_from_ret {
-        ret.remove(&k);
-    }
-    ret
-}
-
-#[derive(PartialEq)]
-enum Direction {
-    Decrease,
-    Increase,
-    Symmetric,
+///
+/// Tightest count ranges covering at least `coverage` of the values
+///
+/// See [`ranges_with_coverage`]: All Pareto-optimal ranges are returned (e.g., one range per mode of a multimodal distribution)
+///
+pub(crate) fn get_range_with_coverage(values: &[usize], coverage: f32) -> Vec<(usize, usize)> {
+    ranges_with_coverage(values.iter().copied(), values.len(), coverage, None)
 }
 
-fn get_range_with_coverage_inner(
-    values: &[usize],
+///
+/// Ranges `(min, max)` (with `min` and `max` being values) containing at least `coverage` of the `num_total` values
+///
+/// Based on the sorted values, each range of `k = ⌈coverage · num_total⌉` consecutive values is a candidate.
+/// Of those, all Pareto-optimal ranges are returned (in increasing order), i.e., the ranges not containing another candidate range.
+/// In particular, the ranges with the smallest width are included.
+///
+/// If `start` is set, only values not smaller than `start` are considered and the single range
+/// from `start` to the `k`-th value is returned.
+///
+/// Returns no range if not enough values are given.
+///
+fn ranges_with_coverage<T: PartialOrd + Copy>(
+    values: impl Iterator<Item = T>,
+    num_total: usize,
     coverage: f32,
-    start: usize,
-    step_size: usize,
-    direction: Direction,
-) -> Vec<(usize, usize)> {
-    let mut min = start;
-    let mut max = start;
-    let mut steps = 0;
-    let coverage_min_count = (values.len() as f32 * coverage).ceil() as usize;
-    while steps < 1000
-        && values.iter().filter(|v| **v >= min && **v <= max).count() < coverage_min_count
-    {
-        // Watch out for overflows!
-        if direction != Direction::Increase {
-            if min >= step_size {
-                min -= step_size;
-            } else {
-                min = 0;
-            }
-        }
-
-        if direction != Direction::Decrease {
-            max += step_size;
-        }
-        steps += 1;
+    start: Option<T>,
+) -> Vec<(T, T)> {
+    let num_required = ((num_total as f32 * coverage).ceil() as usize).max(1);
+    let sorted = values
+        .filter(|v| start.is_none_or(|start| *v >= start))
+        .sorted_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+        .collect_vec();
+    if sorted.len() < num_required {
+        return Vec::new();
     }
-    if steps >= 1000 {
-        println!("[Warning!] Could not find coverage range after 1000 steps.");
-        return Vec::default();
+    if let Some(start) = start {
+        return vec![(start, sorted[num_required - 1])];
     }
-    vec![(min, max)]
+    let windows = sorted
+        .windows(num_required)
+        .map(|w| (w[0], w[num_required - 1]))
+        .dedup()
+        .collect_vec();
+    // As both bounds are non-decreasing, a range can only contain its direct neighbors
+    windows
+        .iter()
+        .enumerate()
+        .filter(|(i, (min, max))| {
+            let contains_next = windows
+                .get(i + 1)
+                .is_some_and(|(_, next_max)| next_max == max);
+            let contains_prev = i
+                .checked_sub(1)
+                .and_then(|prev| windows.get(prev))
+                .is_some_and(|(prev_min, _)| prev_min == min);
+            !contains_next && !contains_prev
+        })
+        .map(|(_, range)| *range)
+        .collect()
 }
 
-fn get_seconds_range_with_coverage(
-    values: &[Option<f64>],
+///
+/// Time range (starting at 0 seconds) covering at least `coverage` of the values (`None` values are never covered)
+///
+/// The values are the delays to the closest matching event, while the resulting constraints only require
+/// _some_ matching event within the time window.
+/// A window starting at 0 covers exactly the instances whose closest event is within it;
+/// a window with a positive start could also be satisfied by later events, so its coverage would not match the values.
+///
+pub(crate) fn duration_range_with_coverage(
+    seconds: &[Option<f64>],
     coverage: f32,
-    start: f64,
-    step_size: f64,
-    direction: Direction,
 ) -> Option<(f64, f64)> {
-    let mut min = start;
-    let mut max = start;
-    let mut steps = 0;
-    let coverage_min_count = (values.len() as f32 * coverage).ceil() as usize;
-    while steps < 1000
-        && values
-            .iter()
-            .filter(|v| v.is_some_and(|v| v >= min && v <= max))
-            .count()
-            < coverage_min_count
-    {
-        // Watch out for overflows!
-        if direction != Direction::Increase {
-            if min >= step_size {
-                min -= step_size;
-            } else {
-                min = 0.0;
-            }
-        }
+    ranges_with_coverage(
+        seconds.iter().flatten().copied(),
+        seconds.len(),
+        coverage,
+        Some(0.0),
+    )
+    .into_iter()
+    .next()
+}
 
-        if direction != Direction::Decrease {
-            max += step_size;
-        }
-        steps += 1;
-    }
-    if steps >= 1000 {
-        println!("[Warning!] Could not find coverage range after 1000 steps.");
-        return None;
-    }
-    Some((min, max))
+/// Fraction of values which are set
+pub(crate) fn fraction_some<T>(values: &[Option<T>]) -> f32 {
+    values.iter().filter(|v| v.is_some()).count() as f32 / values.len() as f32
 }
 
 // fn plot_scatter<S: AsRef<str>, P: AsRef<std::path::Path>>(
@@ -534,10 +471,15 @@ pub fn discover_ef_constraints(
     ocel: &IndexLinkedOCEL,
     coverage: f32,
     object_type: &String,
+    sampling: &DiscoverySamplingOptions,
 ) -> Vec<EFConstraint> {
     let _now = Instant::now();
     let mut ret = Vec::new();
-    let instances: Vec<_> = get_instances(ocel, &EventOrObjectType::Object(object_type.clone()));
+    let instances: Vec<_> = get_instances(
+        ocel,
+        &EventOrObjectType::Object(object_type.clone()),
+        sampling,
+    );
     ret.extend(discover_ef_constraints_for_supporting_instances(
         ocel,
         coverage,
@@ -546,6 +488,7 @@ pub fn discover_ef_constraints(
             _ => None,
         }),
         object_type,
+        sampling,
     ));
 
     // println!("Graph Count Discovery took {:?}", now.elapsed());
@@ -560,10 +503,11 @@ pub fn discover_ef_constraints_for_supporting_instances<
     coverage: f32,
     supporting_instances: I,
     supporting_object_type: &String,
+    sampling: &DiscoverySamplingOptions,
 ) -> Vec<EFConstraint> {
     let _now = Instant::now();
     let mut ret = Vec::new();
-    let mut rng = StdRng::seed_from_u64(RNG_SEED);
+    let mut rng = sampling.rng();
     let mut total_map: HashMap<(&String, &String), Vec<Option<f64>>> = HashMap::new();
     for o_index in supporting_instances {
         if let Some(rels) = ocel.get_symmetric_rels_ob(o_index.borrow()) {
@@ -574,13 +518,7 @@ pub fn discover_ef_constraints_for_supporting_instances<
                     EventOrObjectIndex::Object(_) => None,
                 })
                 .collect_vec();
-            let evs_num = evs.len();
-            let evs = if evs_num >= 1000 {
-                evs.into_iter()
-                    .choose_multiple(&mut rng, (SAMPLE_FRAC * evs_num as f32).ceil() as usize)
-            } else {
-                evs
-            };
+            let evs = sampling.sample(evs, SAMPLE_MIN_NUM_EVENTS_PER_OBJECT, &mut rng);
             // println!("Selected {} out of {} events",evs.len(), evs_num);
             for i in 0..evs.len() {
                 let mut min_delay_to: HashMap<&String, Option<f64>> = ocel
@@ -633,13 +571,7 @@ pub fn discover_ef_constraints_for_supporting_instances<
                 // TODO: Decide if > 0?
                 if std_deviation >= 0.0 {
                     // Otherwise, not interesting (no deviation between values)
-                    if let Some((min, max)) = get_seconds_range_with_coverage(
-                        seconds,
-                        coverage,
-                        0.0,
-                        std_deviation,
-                        Direction::Increase,
-                    ) {
+                    if let Some((min, max)) = duration_range_with_coverage(seconds, coverage) {
                         ret.push(EFConstraint {
                             from_ev_type: (*from_ev_type).clone(),
                             to_ev_type: (*to_ev_type).clone(),
@@ -749,10 +681,11 @@ pub fn discover_or_constraints_new(
     ocel: &IndexLinkedOCEL,
     ocel_type: &EventOrObjectType,
     coverage: f32,
+    sampling: &DiscoverySamplingOptions,
 ) -> Vec<(String, BindingBoxTree)> {
     let mut now = Instant::now();
     let mut ret = Vec::new();
-    let instances: Vec<_> = get_instances(ocel, ocel_type);
+    let instances: Vec<_> = get_instances(ocel, ocel_type, sampling);
     let mut count_constraints: HashSet<CountConstraint> =
         discover_count_constraints_for_supporting_instances(
             ocel,
@@ -778,7 +711,8 @@ pub fn discover_or_constraints_new(
         EventOrObjectType::Event(_) => Variable::Event(EventVariable(0)),
         EventOrObjectType::Object(_) => Variable::Object(ObjectVariable(0)),
     };
-    let bindings = generate_sample_bindings(ocel, &vec![ocel_type.clone()], variable.clone());
+    let bindings =
+        generate_sample_bindings(ocel, &vec![ocel_type.clone()], variable.clone(), sampling);
     let max_sat_count: usize = (1.1 * coverage * bindings.len() as f32).ceil() as usize;
     let b_instances = binding_to_instances(&bindings, variable.clone());
     count_constraints.into_iter().for_each(|cc| {
@@ -803,6 +737,7 @@ pub fn discover_or_constraints_new(
                             EventOrObjectIndex::Event(_) => None,
                         }),
                     object_type,
+                    sampling,
                 );
             ef_constraints.into_iter().take(20).for_each(|ef_c| {
                 // Check if cc OR ef_c is a good candidate
@@ -887,6 +822,7 @@ pub fn discover_or_constraints_new(
                 EventOrObjectIndex::Event(_) => None,
             }),
             object_type,
+            sampling,
         );
         ef_constraints.into_iter().take(20).for_each(|ef_1| {
             let ef1_subtree = ef_1.to_subtree("Y".to_string(), variable.to_inner(), 2, 3);
@@ -908,6 +844,7 @@ pub fn discover_or_constraints_new(
                             })
                             .cloned(),
                         object_type,
+                        sampling,
                     );
                 // println!("\t{} ef2_constraints",ef2_constraints.len());
                 ef2_constraints.into_iter().take(20).for_each(|ef_2| {
@@ -1068,3 +1005,67 @@ pub fn merge_or_tree(
         }));
     or_tree
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranges(values: &[usize], coverage: f32) -> Vec<(usize, usize)> {
+        ranges_with_coverage(values.iter().copied(), values.len(), coverage, None)
+    }
+
+    #[test]
+    fn ranges_of_unimodal_values() {
+        let values = [1, 2, 2, 3, 3, 3, 4, 4, 5, 20];
+        // Both tightest ranges exclude the outlier
+        assert_eq!(ranges(&values, 0.8), vec![(1, 4), (2, 5)]);
+        assert_eq!(ranges(&values, 1.0), vec![(1, 20)]);
+    }
+
+    #[test]
+    fn ranges_of_bimodal_values() {
+        let values = [1, 1, 1, 2, 2, 9, 10, 10, 10, 11];
+        // One range per mode, and the range in between (not containing either of them)
+        assert_eq!(ranges(&values, 0.5), vec![(1, 2), (2, 10), (9, 11)]);
+        assert_eq!(ranges(&values, 0.6), vec![(1, 9), (2, 10)]);
+    }
+
+    #[test]
+    fn ranges_of_equal_values() {
+        assert_eq!(ranges(&[7; 5], 0.5), vec![(7, 7)]);
+        assert_eq!(ranges(&[7; 5], 1.0), vec![(7, 7)]);
+    }
+
+    #[test]
+    fn ranges_without_coverage() {
+        // At least one value is always covered
+        assert_eq!(ranges(&[3, 1, 2, 2], 0.0), vec![(1, 1), (2, 2), (3, 3)]);
+    }
+
+    #[test]
+    fn ranges_with_too_few_values() {
+        assert_eq!(ranges(&[], 0.5), vec![]);
+        // Values missing from the iterator count as uncovered
+        assert_eq!(
+            ranges_with_coverage([1, 2].into_iter(), 4, 0.75, None),
+            vec![]
+        );
+        assert_eq!(
+            duration_range_with_coverage(&[Some(5.0), None, None, Some(1.0)], 0.75),
+            None
+        );
+    }
+
+    #[test]
+    fn duration_ranges_start_at_zero() {
+        let seconds = [Some(30.0), Some(10.0), None, Some(20.0), Some(600.0)];
+        assert_eq!(
+            duration_range_with_coverage(&seconds, 0.6),
+            Some((0.0, 30.0))
+        );
+        assert_eq!(
+            duration_range_with_coverage(&seconds, 0.8),
+            Some((0.0, 600.0))
+        );
+    }
+}
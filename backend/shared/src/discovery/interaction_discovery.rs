@@ -0,0 +1,291 @@
+//! Discovery of eventually-follows constraints anchored on event types
+//!
+//! Other than [`discover_ef_constraints`](super::graph_discovery::discover_ef_constraints), which relates two events through one object (of a fixed type),
+//! the constraints discovered here state that after every event of a type, an event of another type eventually follows, which is
+//!
+//! * related to one of the objects of the first event (e.g., "after `place order`, `pay order` occurs for the same order"), or
+//! * related to an object linked (O2O) to one of the objects of the first event
+//!   (e.g., "after `place order`, a `send package` for an item of that order occurs").
+use std::collections::{HashMap, HashSet};
+
+use itertools::Itertools;
+
+use crate::{
+    binding_box::{
+        structs::{
+            BindingBoxTreeNode, Constraint, EventVariable, Filter, ObjectVariable, SizeFilter,
+            Variable,
+        },
+        BindingBox, BindingBoxTree,
+    },
+    preprocessing::linked_ocel::{EventOrObjectIndex, IndexLinkedOCEL},
+};
+
+use super::{
+    advanced::EventOrObjectType,
+    graph_discovery::{duration_range_with_coverage, fraction_some, get_instances},
+    DiscoverySamplingOptions,
+};
+
+/// How the follow-up event is linked to the first event
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum EFObjectPath {
+    /// Both events are related to the same object of this type
+    SharedObject { object_type: String },
+    /// The follow-up event is related to an object of `related_object_type`,
+    /// which is linked (O2O) to an object of `object_type` related to the first event
+    O2OHop {
+        object_type: String,
+        qualifier: String,
+        related_object_type: String,
+        /// If true, the related object is the source of the O2O relationship
+        reversed: bool,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct InteractionEFConstraint {
+    pub from_ev_type: String,
+    pub to_ev_type: String,
+    pub min_duration_sec: Option<f64>,
+    pub max_duration_sec: Option<f64>,
+    pub path: EFObjectPath,
+}
+
+impl InteractionEFConstraint {
+    pub fn get_constraint_name(&self) -> String {
+        let via = match &self.path {
+            EFObjectPath::SharedObject { object_type } => format!("'{object_type}'"),
+            EFObjectPath::O2OHop {
+                object_type,
+                qualifier,
+                related_object_type,
+                reversed,
+            } => format!(
+                "'{object_type}' {} '{related_object_type}' ('{qualifier}')",
+                if *reversed { "<-" } else { "->" }
+            ),
+        };
+        format!(
+            "Quick '{}' -> '{}' via {via}",
+            self.from_ev_type, self.to_ev_type
+        )
+    }
+    pub fn get_full_tree(&self) -> BindingBoxTree {
+        let child_name = "A".to_string();
+        let (from_ev, to_ev) = (EventVariable(0), EventVariable(1));
+        let (object, related_object) = (ObjectVariable(0), ObjectVariable(1));
+        let bbox0 = BindingBoxTreeNode::Box(
+            BindingBox {
+                new_event_vars: vec![(
+                    from_ev,
+                    vec![self.from_ev_type.clone()].into_iter().collect(),
+                )]
+                .into_iter()
+                .collect(),
+                new_object_vars: HashMap::default(),
+                filters: vec![],
+                size_filters: vec![],
+                constraints: vec![Constraint::SizeFilter {
+                    filter: SizeFilter::NumChilds {
+                        child_name: child_name.clone(),
+                        min: Some(1),
+                        max: None,
+                    },
+                }],
+            },
+            vec![1],
+        );
+
+        let mut new_object_vars = HashMap::new();
+        let mut filters = Vec::new();
+        let to_ev_object = match &self.path {
+            EFObjectPath::SharedObject { object_type } => {
+                new_object_vars.insert(object, vec![object_type.clone()].into_iter().collect());
+                object
+            }
+            EFObjectPath::O2OHop {
+                object_type,
+                qualifier,
+                related_object_type,
+                reversed,
+            } => {
+                new_object_vars.insert(object, vec![object_type.clone()].into_iter().collect());
+                new_object_vars.insert(
+                    related_object,
+                    vec![related_object_type.clone()].into_iter().collect(),
+                );
+                filters.push(Filter::O2O {
+                    object: if !reversed { object } else { related_object },
+                    other_object: if !reversed { related_object } else { object },
+                    qualifier: Some(qualifier.clone()),
+                });
+                related_object
+            }
+        };
+        filters.push(Filter::O2E {
+            object,
+            event: from_ev,
+            qualifier: None,
+        });
+        filters.push(Filter::O2E {
+            object: to_ev_object,
+            event: to_ev,
+            qualifier: None,
+        });
+        filters.push(Filter::TimeBetweenEvents {
+            from_event: from_ev,
+            to_event: to_ev,
+            min_seconds: self.min_duration_sec,
+            max_seconds: self.max_duration_sec,
+        });
+        if self.from_ev_type == self.to_ev_type {
+            filters.push(Filter::NotEqual {
+                var_1: Variable::Event(from_ev),
+                var_2: Variable::Event(to_ev),
+            });
+        }
+        let bbox1 = BindingBoxTreeNode::Box(
+            BindingBox {
+                new_event_vars: vec![(to_ev, vec![self.to_ev_type.clone()].into_iter().collect())]
+                    .into_iter()
+                    .collect(),
+                new_object_vars,
+                filters,
+                size_filters: vec![],
+                constraints: vec![],
+            },
+            vec![],
+        );
+        BindingBoxTree {
+            nodes: vec![bbox0, bbox1],
+            edge_names: vec![((0, 1), child_name)].into_iter().collect(),
+        }
+    }
+}
+
+/// (object type, optional O2O hop (qualifier, related object type, reversed), follow-up event type)
+type PathKey<'a> = (
+    &'a String,
+    Option<(&'a String, &'a String, bool)>,
+    &'a String,
+);
+
+///
+/// Discover eventually-follows constraints (see [module-level documentation](self)) for events of the given type
+///
+/// Every returned constraint is satisfied by at least `coverage` (fraction) of the (sampled) events of the type
+///
+pub fn discover_interaction_ef_constraints<'a>(
+    ocel: &'a IndexLinkedOCEL,
+    coverage: f32,
+    event_type: &str,
+    sampling: &DiscoverySamplingOptions,
+) -> Vec<InteractionEFConstraint> {
+    let related = |index: &EventOrObjectIndex| {
+        ocel.symmetric_rels
+            .get(index)
+            .into_iter()
+            .flatten()
+            .map(|(other, reversed, qualifier)| (*other, *reversed, qualifier))
+    };
+    let instances = get_instances(
+        ocel,
+        &EventOrObjectType::Event(event_type.to_string()),
+        sampling,
+    );
+    // Min. delay to a follow-up event per path (for each event of the type)
+    let min_delays_per_event: Vec<HashMap<PathKey, f64>> = instances
+        .iter()
+        .flat_map(|index| match index {
+            EventOrObjectIndex::Event(ei) => ocel.ev_by_index(ei).map(|ev| (index, ev)),
+            EventOrObjectIndex::Object(_) => None,
+        })
+        .map(|(index, ev)| {
+            // Types of and delays to the later events of an object
+            let follow_ups = |ob_index: &EventOrObjectIndex| {
+                related(ob_index)
+                    .filter(|(other, _, _)| other != index)
+                    .filter_map(|(other, _, _)| match other {
+                        EventOrObjectIndex::Event(other_ei) => ocel.ev_by_index(&other_ei),
+                        EventOrObjectIndex::Object(_) => None,
+                    })
+                    .filter(|other_ev| other_ev.time >= ev.time)
+                    .map(|other_ev| {
+                        (
+                            &other_ev.event_type,
+                            (other_ev.time - ev.time).num_milliseconds() as f64 / 1000.0,
+                        )
+                    })
+                    .collect_vec()
+            };
+            let mut min_delays: HashMap<PathKey, f64> = HashMap::new();
+            let mut add_min_delay = |key: PathKey<'a>, delay: f64| {
+                let v = min_delays.entry(key).or_insert(delay);
+                *v = v.min(delay);
+            };
+            let objects: HashSet<_> = related(index)
+                .filter_map(|(other, _, _)| match other {
+                    EventOrObjectIndex::Object(oi) => Some(oi),
+                    EventOrObjectIndex::Event(_) => None,
+                })
+                .collect();
+            for oi in objects {
+                let object_type = &ocel.ocel.objects[oi.0].object_type;
+                let ob_index = EventOrObjectIndex::Object(oi);
+                for (to_ev_type, delay) in follow_ups(&ob_index) {
+                    add_min_delay((object_type, None, to_ev_type), delay);
+                }
+                for (related_ob_index, reversed, qualifier) in related(&ob_index) {
+                    if let EventOrObjectIndex::Object(related_oi) = related_ob_index {
+                        let related_type = &ocel.ocel.objects[related_oi.0].object_type;
+                        for (to_ev_type, delay) in follow_ups(&related_ob_index) {
+                            add_min_delay(
+                                (
+                                    object_type,
+                                    Some((qualifier, related_type, reversed)),
+                                    to_ev_type,
+                                ),
+                                delay,
+                            );
+                        }
+                    }
+                }
+            }
+            min_delays
+        })
+        .collect();
+
+    let keys: HashSet<&PathKey> = min_delays_per_event.iter().flat_map(|m| m.keys()).collect();
+    let mut ret = Vec::new();
+    for key in keys.into_iter().sorted() {
+        let seconds = min_delays_per_event
+            .iter()
+            .map(|m| m.get(key).copied())
+            .collect_vec();
+        if fraction_some(&seconds) < coverage {
+            continue;
+        }
+        if let Some((min, max)) = duration_range_with_coverage(&seconds, coverage) {
+            let (object_type, hop, to_ev_type) = key;
+            ret.push(InteractionEFConstraint {
+                from_ev_type: event_type.to_string(),
+                to_ev_type: (*to_ev_type).clone(),
+                min_duration_sec: Some(min),
+                max_duration_sec: Some(max),
+                path: match hop {
+                    None => EFObjectPath::SharedObject {
+                        object_type: (*object_type).clone(),
+                    },
+                    Some((qualifier, related_object_type, reversed)) => EFObjectPath::O2OHop {
+                        object_type: (*object_type).clone(),
+                        qualifier: (*qualifier).clone(),
+                        related_object_type: (*related_object_type).clone(),
+                        reversed: *reversed,
+                    },
+                },
+            });
+        }
+    }
+    ret
+}
@@ -0,0 +1,56 @@
+//! Quality metrics of discovered constraints
+//!
+//! Discovery works on a sample of the OCEL (see [`DiscoverySamplingOptions`](super::DiscoverySamplingOptions)),
+//! so the metrics are computed by evaluating the discovered [`BindingBoxTree`] on the full OCEL.
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    binding_box::{sampling::wilson_score_interval, BindingBoxTree},
+    preprocessing::linked_ocel::IndexLinkedOCEL,
+};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveredConstraintMetrics {
+    /// Number of situations (i.e., bindings of the root node) the constraint applies to
+    pub support: usize,
+    /// Number of violated situations
+    pub violated: usize,
+    /// Fraction of satisfied situations (1 if there are no situations)
+    pub coverage: f64,
+    /// Lower bound of the 95% confidence interval (Wilson score) of the coverage
+    ///
+    /// Ranks constraints with the same coverage but less support lower (0 if there are no situations)
+    pub confidence: f64,
+}
+
+///
+/// Evaluate the tree on the full OCEL and compute its quality metrics
+///
+pub fn get_constraint_metrics(
+    tree: &BindingBoxTree,
+    ocel: &IndexLinkedOCEL,
+) -> DiscoveredConstraintMetrics {
+    let (support, violated) = tree
+        .evaluate(ocel)
+        .iter()
+        .filter(|(node_index, _binding, _viol)| *node_index == 0)
+        .fold((0, 0), |(support, violated), (_, _, viol)| {
+            (support + 1, violated + usize::from(viol.is_some()))
+        });
+    if support == 0 {
+        return DiscoveredConstraintMetrics {
+            support,
+            violated,
+            coverage: 1.0,
+            confidence: 0.0,
+        };
+    }
+    let coverage = 1.0 - violated as f64 / support as f64;
+    DiscoveredConstraintMetrics {
+        support,
+        violated,
+        coverage,
+        confidence: wilson_score_interval(coverage, support as f64).0.max(0.0),
+    }
+}
@@ -2,11 +2,20 @@ use core::f32;
 use std::collections::HashMap;
 
 use advanced::EventOrObjectType;
+use attribute_discovery::discover_attribute_constraints;
+use conditional_discovery::discover_conditional_constraints;
 use graph_discovery::{
     discover_count_constraints, discover_ef_constraints, discover_or_constraints_new,
 };
+use interaction_discovery::discover_interaction_ef_constraints;
 use itertools::Itertools;
+use metrics::{get_constraint_metrics, DiscoveredConstraintMetrics};
+use o2o_discovery::discover_o2o_constraints;
+use ordering_discovery::discover_ordering_constraints;
+use redundancy::{remove_redundant_constraints, RedundancyOptions, RedundantConstraint};
 
+use rand::{rngs::StdRng, seq::IteratorRandom, SeedableRng};
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use crate::{binding_box::BindingBoxTree, preprocessing::linked_ocel::IndexLinkedOCEL};
@@ -14,12 +23,67 @@ use crate::{binding_box::BindingBoxTree, preprocessing::linked_ocel::IndexLinked
 // use self::evaluation::{get_count_constraint_fraction, get_ef_constraint_fraction};
 
 pub mod advanced;
+pub mod attribute_discovery;
+pub mod conditional_discovery;
+pub mod dfg;
 pub mod evaluation;
+pub mod example_discovery;
 pub mod graph_discovery;
+pub mod interaction_discovery;
+pub mod metrics;
+pub mod o2o_discovery;
+pub mod ordering_discovery;
+pub mod redundancy;
 
 pub static SAMPLE_MIN_NUM_INSTANCES: usize = 3000;
 pub static SAMPLE_FRAC: f32 = 0.1;
 pub static RNG_SEED: u64 = 13375050;
+/// Objects with at least this many events only have a sample of their events considered (e.g., for eventually-follows discovery)
+pub static SAMPLE_MIN_NUM_EVENTS_PER_OBJECT: usize = 1000;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DiscoverySamplingOptions {
+    /// Use all events/objects (no sampling)
+    pub full_data: bool,
+    /// Only types with at least this many events/objects are sampled
+    pub min_num_instances: usize,
+    /// Fraction of events/objects to sample
+    pub fraction: f32,
+    /// Seed for selecting the sample
+    pub seed: u64,
+}
+
+impl Default for DiscoverySamplingOptions {
+    fn default() -> Self {
+        Self {
+            full_data: false,
+            min_num_instances: SAMPLE_MIN_NUM_INSTANCES,
+            fraction: SAMPLE_FRAC,
+            seed: RNG_SEED,
+        }
+    }
+}
+
+impl DiscoverySamplingOptions {
+    pub fn rng(&self) -> StdRng {
+        StdRng::seed_from_u64(self.seed)
+    }
+
+    ///
+    /// Sample [`DiscoverySamplingOptions::fraction`] of the items, if there are at least `min_num_items` items
+    ///
+    /// Returns all items in full-data mode
+    ///
+    pub fn sample<T>(&self, items: Vec<T>, min_num_items: usize, rng: &mut StdRng) -> Vec<T> {
+        if self.full_data || items.len() < min_num_items {
+            items
+        } else {
+            let sample_count = (items.len() as f32 * self.fraction).ceil() as usize;
+            items.into_iter().choose_multiple(rng, sample_count)
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -32,6 +96,16 @@ pub struct CountConstraintOptions {
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct EventuallyFollowsConstraintOptions {
+    pub object_types: Vec<String>,
+    /// Event types to discover constraints for (linked through shared or O2O-related objects)
+    #[serde(default)]
+    pub event_types: Vec<String>,
+    pub cover_fraction: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderingConstraintOptions {
     pub object_types: Vec<String>,
     pub cover_fraction: f32,
 }
@@ -44,17 +118,60 @@ pub struct ORConstraintOptions {
     pub cover_fraction: f32,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributeConstraintOptions {
+    pub object_types: Vec<String>,
+    pub event_types: Vec<String>,
+    pub cover_fraction: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct O2OConstraintOptions {
+    pub object_types: Vec<String>,
+    pub cover_fraction: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ConditionalConstraintOptions {
+    pub object_types: Vec<String>,
+    pub event_types: Vec<String>,
+    pub cover_fraction: f32,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct AutoDiscoverConstraintsRequest {
     pub count_constraints: Option<CountConstraintOptions>,
     pub eventually_follows_constraints: Option<EventuallyFollowsConstraintOptions>,
+    pub ordering_constraints: Option<OrderingConstraintOptions>,
     pub or_constraints: Option<ORConstraintOptions>,
+    pub attribute_constraints: Option<AttributeConstraintOptions>,
+    pub o2o_constraints: Option<O2OConstraintOptions>,
+    /// Count/eventually-follows constraints only holding for events/objects with certain attribute values
+    pub conditional_constraints: Option<ConditionalConstraintOptions>,
+    /// Sampling of events/objects used for discovery (defaults to [`DiscoverySamplingOptions::default`])
+    pub sampling: Option<DiscoverySamplingOptions>,
+    /// Remove redundant (equivalent or implied) constraints and rank the remaining ones
+    pub redundancy_removal: Option<RedundancyOptions>,
+    /// Compute the [`DiscoveredConstraintMetrics`] of all discovered constraints
+    ///
+    /// This evaluates every constraint on the full OCEL, which can take long for large OCELs.
+    #[serde(default)]
+    pub compute_metrics: bool,
 }
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct AutoDiscoverConstraintsResponse {
     pub constraints: Vec<(String, BindingBoxTree)>,
+    /// Quality metrics of the discovered constraints (in the same order as `constraints`)
+    ///
+    /// Empty, unless [`AutoDiscoverConstraintsRequest::compute_metrics`] is set
+    pub metrics: Vec<DiscoveredConstraintMetrics>,
+    /// Constraints removed as redundant (only if redundancy removal was requested)
+    pub redundant_constraints: Vec<RedundantConstraint>,
 }
 
 pub fn auto_discover_constraints_with_options(
@@ -62,12 +179,20 @@ pub fn auto_discover_constraints_with_options(
     options: AutoDiscoverConstraintsRequest,
 ) -> AutoDiscoverConstraintsResponse {
     let mut trees_per_type: HashMap<EventOrObjectType, Vec<BindingBoxTree>> = HashMap::new();
+    let sampling = options.sampling.unwrap_or_default();
     let mut ret = AutoDiscoverConstraintsResponse {
         constraints: Vec::new(),
+        metrics: Vec::new(),
+        redundant_constraints: Vec::new(),
     };
     if let Some(eventually_follows_options) = options.eventually_follows_constraints {
         for ot in &eventually_follows_options.object_types {
-            for c in discover_ef_constraints(ocel, eventually_follows_options.cover_fraction, ot) {
+            for c in discover_ef_constraints(
+                ocel,
+                eventually_follows_options.cover_fraction,
+                ot,
+                &sampling,
+            ) {
                 ret.constraints
                     .push((c.get_constraint_name(), c.get_full_tree()));
                 trees_per_type
@@ -76,7 +201,28 @@ pub fn auto_discover_constraints_with_options(
                     .push(c.to_subtree("X".to_string(), 0, 2, 3))
             }
         }
+        for et in &eventually_follows_options.event_types {
+            for c in discover_interaction_ef_constraints(
+                ocel,
+                eventually_follows_options.cover_fraction,
+                et,
+                &sampling,
+            ) {
+                ret.constraints
+                    .push((c.get_constraint_name(), c.get_full_tree()));
+            }
+        }
     };
+    if let Some(ordering_opts) = &options.ordering_constraints {
+        for ot in &ordering_opts.object_types {
+            for c in
+                discover_ordering_constraints(ocel, ordering_opts.cover_fraction, ot, &sampling)
+            {
+                ret.constraints
+                    .push((c.get_constraint_name(), c.get_full_tree()));
+            }
+        }
+    }
     if let Some(count_opts) = &options.count_constraints {
         let mut types = count_opts
             .object_types
@@ -91,7 +237,9 @@ pub fn auto_discover_constraints_with_options(
                 .map(|et| EventOrObjectType::Event(et.clone())),
         );
         for t in types {
-            for cc in discover_count_constraints(ocel, count_opts.cover_fraction, t.clone()) {
+            for cc in
+                discover_count_constraints(ocel, count_opts.cover_fraction, t.clone(), &sampling)
+            {
                 ret.constraints
                     .push((cc.get_constraint_name(), cc.get_full_tree()));
 
@@ -109,6 +257,7 @@ pub fn auto_discover_constraints_with_options(
                 ocel,
                 &ocel_type,
                 or_constraint_option.cover_fraction,
+                &sampling,
             ));
         }
         for et in &or_constraint_option.event_types {
@@ -117,9 +266,76 @@ pub fn auto_discover_constraints_with_options(
                 ocel,
                 &ocel_type,
                 or_constraint_option.cover_fraction,
+                &sampling,
             ));
         }
     }
+    if let Some(attribute_opts) = &options.attribute_constraints {
+        let types = attribute_opts
+            .object_types
+            .iter()
+            .map(|ot| EventOrObjectType::Object(ot.clone()))
+            .chain(
+                attribute_opts
+                    .event_types
+                    .iter()
+                    .map(|et| EventOrObjectType::Event(et.clone())),
+            );
+        for t in types {
+            for c in
+                discover_attribute_constraints(ocel, attribute_opts.cover_fraction, t, &sampling)
+            {
+                ret.constraints
+                    .push((c.get_constraint_name(), c.get_full_tree()));
+            }
+        }
+    }
+    if let Some(o2o_opts) = &options.o2o_constraints {
+        for ot in &o2o_opts.object_types {
+            for c in discover_o2o_constraints(ocel, o2o_opts.cover_fraction, ot, &sampling) {
+                ret.constraints
+                    .push((c.get_constraint_name(), c.get_full_tree()));
+            }
+        }
+    }
+    if let Some(conditional_opts) = &options.conditional_constraints {
+        let types = conditional_opts
+            .object_types
+            .iter()
+            .map(|ot| EventOrObjectType::Object(ot.clone()))
+            .chain(
+                conditional_opts
+                    .event_types
+                    .iter()
+                    .map(|et| EventOrObjectType::Event(et.clone())),
+            );
+        for t in types {
+            for c in discover_conditional_constraints(
+                ocel,
+                conditional_opts.cover_fraction,
+                t,
+                &sampling,
+            ) {
+                ret.constraints
+                    .push((c.get_constraint_name(), c.get_full_tree()));
+            }
+        }
+    }
+
+    if let Some(redundancy_opts) = &options.redundancy_removal {
+        let (kept, redundant) =
+            remove_redundant_constraints(ocel, ret.constraints, redundancy_opts, &sampling);
+        ret.constraints = kept;
+        ret.redundant_constraints = redundant;
+    }
 
+    // Metrics are always computed on the full OCEL
+    if options.compute_metrics {
+        ret.metrics = ret
+            .constraints
+            .par_iter()
+            .map(|(_name, tree)| get_constraint_metrics(tree, ocel))
+            .collect();
+    }
     ret
 }
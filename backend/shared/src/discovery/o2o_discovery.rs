@@ -0,0 +1,192 @@
+//! Discovery of O2O cardinality constraints
+//!
+//! For each object type, the number of related objects per O2O qualifier and related object type is counted,
+//! both for outgoing (e.g., "every order has exactly one customer") and incoming relationships
+//! (e.g., "a customer is the customer of at most 5 orders").
+//! Count ranges covering the requested fraction of objects are then turned into constraints.
+use std::collections::{HashMap, HashSet};
+
+use itertools::Itertools;
+
+use crate::{
+    binding_box::{
+        structs::{BindingBoxTreeNode, Constraint, Filter, ObjectVariable, SizeFilter},
+        BindingBox, BindingBoxTree,
+    },
+    preprocessing::linked_ocel::{EventOrObjectIndex, IndexLinkedOCEL},
+};
+
+use super::{
+    advanced::EventOrObjectType,
+    graph_discovery::{get_instances, get_range_with_coverage},
+    DiscoverySamplingOptions,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct O2OCardinalityConstraint {
+    pub min_count: Option<usize>,
+    pub max_count: Option<usize>,
+    pub object_type: String,
+    // This is the type of objects we constrain in their count!
+    pub related_object_type: String,
+    pub qualifier: String,
+    /// If true, the related objects are the source of the O2O relationship
+    pub reversed: bool,
+}
+
+impl O2OCardinalityConstraint {
+    pub fn get_constraint_name(&self) -> String {
+        let range = match (self.min_count, self.max_count) {
+            (None, None) => "any number of".to_string(),
+            (_, Some(0)) => "=0".to_string(),
+            (None, Some(max)) => format!("≤{max}"),
+            (Some(0), Some(max)) => format!("≤{max}"),
+            (Some(min), Some(max)) if min == max => format!("={min}"),
+            (Some(min), None) => format!("≥{min}"),
+            (Some(min), Some(max)) => format!("{min}-{max}"),
+        };
+        format!(
+            "{range} '{}' per '{}' {} '{}'",
+            self.related_object_type,
+            self.object_type,
+            if self.reversed { "<-" } else { "->" },
+            self.qualifier
+        )
+    }
+    pub fn get_full_tree(&self) -> BindingBoxTree {
+        let child_name = "A".to_string();
+        let (object, related_object) = (ObjectVariable(0), ObjectVariable(1));
+        let bbox0 = BindingBoxTreeNode::Box(
+            BindingBox {
+                new_event_vars: HashMap::default(),
+                new_object_vars: vec![(
+                    object,
+                    vec![self.object_type.clone()].into_iter().collect(),
+                )]
+                .into_iter()
+                .collect(),
+                filters: vec![],
+                size_filters: vec![],
+                constraints: vec![Constraint::SizeFilter {
+                    filter: SizeFilter::NumChilds {
+                        child_name: child_name.clone(),
+                        min: self.min_count,
+                        max: self.max_count,
+                    },
+                }],
+            },
+            vec![1],
+        );
+        let bbox1 = BindingBoxTreeNode::Box(
+            BindingBox {
+                new_event_vars: HashMap::default(),
+                new_object_vars: vec![(
+                    related_object,
+                    vec![self.related_object_type.clone()].into_iter().collect(),
+                )]
+                .into_iter()
+                .collect(),
+                filters: vec![Filter::O2O {
+                    object: if !self.reversed {
+                        object
+                    } else {
+                        related_object
+                    },
+                    other_object: if !self.reversed {
+                        related_object
+                    } else {
+                        object
+                    },
+                    qualifier: Some(self.qualifier.clone()),
+                }],
+                size_filters: vec![],
+                constraints: vec![],
+            },
+            vec![],
+        );
+        BindingBoxTree {
+            nodes: vec![bbox0, bbox1],
+            edge_names: vec![((0, 1), child_name)].into_iter().collect(),
+        }
+    }
+}
+
+///
+/// Discover O2O cardinality constraints for objects of the given type
+///
+/// Considers all (qualifier, related object type) combinations of outgoing and incoming O2O relationships
+/// (see [`IndexLinkedOCEL::object_rels_per_type`])
+///
+pub fn discover_o2o_constraints(
+    ocel: &IndexLinkedOCEL,
+    coverage: f32,
+    object_type: &String,
+    sampling: &DiscoverySamplingOptions,
+) -> Vec<O2OCardinalityConstraint> {
+    // (qualifier, related object type, reversed)
+    let mut candidates: HashSet<(&String, &String, bool)> = ocel
+        .object_rels_per_type
+        .get(object_type)
+        .into_iter()
+        .flatten()
+        .map(|(qualifier, related_type)| (qualifier, related_type, false))
+        .collect();
+    for (other_type, rels) in &ocel.object_rels_per_type {
+        for (qualifier, target_type) in rels {
+            if target_type == object_type {
+                candidates.insert((qualifier, other_type, true));
+            }
+        }
+    }
+    let mut counts: HashMap<(&String, &String, bool), Vec<usize>> = candidates
+        .into_iter()
+        .map(|candidate| (candidate, Vec::new()))
+        .collect();
+    for index in get_instances(
+        ocel,
+        &EventOrObjectType::Object(object_type.clone()),
+        sampling,
+    ) {
+        let mut instance_counts: HashMap<(&String, &String, bool), usize> = HashMap::new();
+        for (other, reversed, qualifier) in ocel.symmetric_rels.get(&index).into_iter().flatten() {
+            if let EventOrObjectIndex::Object(other_ob) = other {
+                let related_type = &ocel.ocel.objects[other_ob.0].object_type;
+                *instance_counts
+                    .entry((qualifier, related_type, *reversed))
+                    .or_default() += 1;
+            }
+        }
+        for (candidate, candidate_counts) in counts.iter_mut() {
+            candidate_counts.push(instance_counts.get(candidate).copied().unwrap_or_default());
+        }
+    }
+
+    let mut ret = Vec::new();
+    for ((qualifier, related_type, reversed), counts) in
+        counts.into_iter().sorted_by(|(a, _), (b, _)| a.cmp(b))
+    {
+        let n = counts.len() as f32;
+        let mean = counts.iter().sum::<usize>() as f32 / n;
+        if mean <= 0.0 {
+            // Never related, not interesting
+            continue;
+        }
+        let min = counts.iter().min().unwrap_or(&0);
+        let max = counts.iter().max().unwrap_or(&usize::MAX);
+        for (c_min, c_max) in get_range_with_coverage(&counts, coverage) {
+            ret.push(O2OCardinalityConstraint {
+                min_count: if c_min > 0 && c_min < *min {
+                    None
+                } else {
+                    Some(c_min)
+                },
+                max_count: if c_max > *max { None } else { Some(c_max) },
+                object_type: object_type.clone(),
+                related_object_type: related_type.clone(),
+                qualifier: qualifier.clone(),
+                reversed,
+            });
+        }
+    }
+    ret
+}
@@ -0,0 +1,435 @@
+//! Discovery of ordering constraints between event types (per object)
+//!
+//! In addition to the eventually-follows (response) constraints of [`discover_ef_constraints`](super::graph_discovery::discover_ef_constraints),
+//! the following Declare-like templates are discovered for the events of each object of a type:
+//!
+//! * Precedence: Every `B` event is preceded by an `A` event (within a time window)
+//! * Chain Response: Every `A` event is directly followed by a `B` event (within a time window)
+//! * Chain Precedence: Every `B` event is directly preceded by an `A` event (within a time window)
+//! * Not Succession: No `A` event is followed by a `B` event (at all or within a time window of at least [`MIN_NOT_SUCCESSION_WINDOW_SEC`])
+//!
+//! Events with the same timestamp count as following each other (in both directions).
+//! An event is directly followed by another event, if no third event of the object occurs in between.
+use std::collections::{HashMap, HashSet};
+
+use itertools::Itertools;
+use process_mining::ocel::ocel_struct::OCELEvent;
+
+use crate::{
+    binding_box::{
+        structs::{
+            BindingBoxTreeNode, Constraint, EventVariable, Filter, ObjectVariable, SizeFilter,
+            Variable,
+        },
+        BindingBox, BindingBoxTree,
+    },
+    preprocessing::linked_ocel::{EventOrObjectIndex, IndexLinkedOCEL},
+};
+
+use super::{
+    advanced::EventOrObjectType,
+    graph_discovery::{duration_range_with_coverage, fraction_some, get_instances},
+    DiscoverySamplingOptions, SAMPLE_MIN_NUM_EVENTS_PER_OBJECT,
+};
+
+/// Minimal time window (in seconds) of discovered Not Succession constraints
+///
+/// Smaller windows are (nearly) always satisfied and thus not interesting.
+pub static MIN_NOT_SUCCESSION_WINDOW_SEC: f64 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OrderingConstraintKind {
+    Precedence,
+    ChainResponse,
+    ChainPrecedence,
+    NotSuccession,
+}
+
+#[derive(Debug, Clone)]
+pub struct OrderingConstraint {
+    pub kind: OrderingConstraintKind,
+    /// Type of the earlier event
+    pub from_ev_type: String,
+    /// Type of the later event
+    pub to_ev_type: String,
+    pub min_duration_sec: Option<f64>,
+    pub max_duration_sec: Option<f64>,
+    pub for_object_type: String,
+    /// Event types of events that may not occur in between (only used for chain constraints)
+    pub between_ev_types: Vec<String>,
+}
+
+impl OrderingConstraint {
+    pub fn get_constraint_name(&self) -> String {
+        let kind = match self.kind {
+            OrderingConstraintKind::Precedence => "Precedence",
+            OrderingConstraintKind::ChainResponse => "Chain Response",
+            OrderingConstraintKind::ChainPrecedence => "Chain Precedence",
+            OrderingConstraintKind::NotSuccession => "Not Succession",
+        };
+        // The time window is part of the constraint for Not Succession
+        let window = match (self.kind, self.max_duration_sec) {
+            (OrderingConstraintKind::NotSuccession, Some(max)) => {
+                format!(" within {}s", (max * 1000.0).ceil() / 1000.0)
+            }
+            _ => String::new(),
+        };
+        format!(
+            "{kind} '{}' -> '{}'{window} for '{}'",
+            self.from_ev_type, self.to_ev_type, self.for_object_type,
+        )
+    }
+    pub fn get_full_tree(&self) -> BindingBoxTree {
+        let object = ObjectVariable(0);
+        let (from_ev, to_ev, between_ev) = (EventVariable(1), EventVariable(2), EventVariable(3));
+        // The root event is the one the constraint is checked for
+        let (root_ev, root_ev_type, other_ev, other_ev_type) = match self.kind {
+            OrderingConstraintKind::ChainResponse | OrderingConstraintKind::NotSuccession => {
+                (from_ev, &self.from_ev_type, to_ev, &self.to_ev_type)
+            }
+            OrderingConstraintKind::Precedence | OrderingConstraintKind::ChainPrecedence => {
+                (to_ev, &self.to_ev_type, from_ev, &self.from_ev_type)
+            }
+        };
+        let is_chain = matches!(
+            self.kind,
+            OrderingConstraintKind::ChainResponse | OrderingConstraintKind::ChainPrecedence
+        );
+        let child_name = "A".to_string();
+        let between_child_name = "B".to_string();
+        let root_constraint = match self.kind {
+            // At least one (directly) following/preceding event without events in between
+            OrderingConstraintKind::ChainResponse | OrderingConstraintKind::ChainPrecedence => {
+                Constraint::ANY {
+                    child_names: vec![child_name.clone()],
+                }
+            }
+            OrderingConstraintKind::Precedence => Constraint::SizeFilter {
+                filter: SizeFilter::NumChilds {
+                    child_name: child_name.clone(),
+                    min: Some(1),
+                    max: None,
+                },
+            },
+            OrderingConstraintKind::NotSuccession => Constraint::SizeFilter {
+                filter: SizeFilter::NumChilds {
+                    child_name: child_name.clone(),
+                    min: None,
+                    max: Some(0),
+                },
+            },
+        };
+        let bbox0 = BindingBoxTreeNode::Box(
+            BindingBox {
+                new_event_vars: vec![(root_ev, vec![root_ev_type.clone()].into_iter().collect())]
+                    .into_iter()
+                    .collect(),
+                new_object_vars: vec![(
+                    object,
+                    vec![self.for_object_type.clone()].into_iter().collect(),
+                )]
+                .into_iter()
+                .collect(),
+                filters: vec![Filter::O2E {
+                    object,
+                    event: root_ev,
+                    qualifier: None,
+                }],
+                size_filters: vec![],
+                constraints: vec![root_constraint],
+            },
+            vec![1],
+        );
+
+        let mut filters = vec![
+            Filter::O2E {
+                object,
+                event: other_ev,
+                qualifier: None,
+            },
+            Filter::TimeBetweenEvents {
+                from_event: from_ev,
+                to_event: to_ev,
+                min_seconds: self.min_duration_sec,
+                max_seconds: self.max_duration_sec,
+            },
+        ];
+        if self.from_ev_type == self.to_ev_type {
+            filters.push(Filter::NotEqual {
+                var_1: Variable::Event(from_ev),
+                var_2: Variable::Event(to_ev),
+            });
+        }
+        let bbox1 = BindingBoxTreeNode::Box(
+            BindingBox {
+                new_event_vars: vec![(other_ev, vec![other_ev_type.clone()].into_iter().collect())]
+                    .into_iter()
+                    .collect(),
+                new_object_vars: HashMap::default(),
+                filters,
+                size_filters: vec![],
+                constraints: if is_chain {
+                    vec![Constraint::SizeFilter {
+                        filter: SizeFilter::NumChilds {
+                            child_name: between_child_name.clone(),
+                            min: None,
+                            max: Some(0),
+                        },
+                    }]
+                } else {
+                    vec![]
+                },
+            },
+            if is_chain { vec![2] } else { vec![] },
+        );
+        if !is_chain {
+            return BindingBoxTree {
+                nodes: vec![bbox0, bbox1],
+                edge_names: vec![((0, 1), child_name)].into_iter().collect(),
+            };
+        }
+
+        // Events of the object in between the two events
+        let bbox2 = BindingBoxTreeNode::Box(
+            BindingBox {
+                new_event_vars: vec![(between_ev, self.between_ev_types.iter().cloned().collect())]
+                    .into_iter()
+                    .collect(),
+                new_object_vars: HashMap::default(),
+                filters: vec![
+                    Filter::O2E {
+                        object,
+                        event: between_ev,
+                        qualifier: None,
+                    },
+                    Filter::NotEqual {
+                        var_1: Variable::Event(between_ev),
+                        var_2: Variable::Event(from_ev),
+                    },
+                    Filter::NotEqual {
+                        var_1: Variable::Event(between_ev),
+                        var_2: Variable::Event(to_ev),
+                    },
+                    Filter::TimeBetweenEvents {
+                        from_event: from_ev,
+                        to_event: between_ev,
+                        min_seconds: Some(0.0),
+                        max_seconds: None,
+                    },
+                    Filter::TimeBetweenEvents {
+                        from_event: between_ev,
+                        to_event: to_ev,
+                        min_seconds: Some(0.0),
+                        max_seconds: None,
+                    },
+                ],
+                size_filters: vec![],
+                constraints: vec![],
+            },
+            vec![],
+        );
+        BindingBoxTree {
+            nodes: vec![bbox0, bbox1, bbox2],
+            edge_names: vec![((0, 1), child_name), ((1, 2), between_child_name)]
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+/// (from event type, to event type)
+type EventTypePair<'a> = (&'a String, &'a String);
+
+/// Seconds between two events
+fn seconds_between(from: &OCELEvent, to: &OCELEvent) -> f64 {
+    (to.time - from.time).num_milliseconds() as f64 / 1000.0
+}
+
+/// The (unique) event directly following (`after == true`) or preceding the event at position `i`
+///
+/// If multiple events share the closest timestamp, none of them is directly following/preceding
+fn direct_neighbor(evs: &[&OCELEvent], i: usize, after: bool) -> Option<usize> {
+    let candidates = (0..evs.len())
+        .filter(|j| *j != i)
+        .filter(|j| {
+            if after {
+                evs[*j].time >= evs[i].time
+            } else {
+                evs[*j].time <= evs[i].time
+            }
+        })
+        .collect_vec();
+    let closest = if after {
+        candidates.iter().map(|j| evs[*j].time).min()
+    } else {
+        candidates.iter().map(|j| evs[*j].time).max()
+    }?;
+    candidates
+        .into_iter()
+        .filter(|j| evs[*j].time == closest)
+        .exactly_one()
+        .ok()
+}
+
+///
+/// Discover ordering constraints (see [module-level documentation](self)) for the events of objects of the given type
+///
+/// Every returned constraint is satisfied for at least `coverage` (fraction) of the checked events of the (sampled) objects
+///
+pub fn discover_ordering_constraints(
+    ocel: &IndexLinkedOCEL,
+    coverage: f32,
+    object_type: &str,
+    sampling: &DiscoverySamplingOptions,
+) -> Vec<OrderingConstraint> {
+    let mut rng = sampling.rng();
+    let event_types = ocel
+        .ocel
+        .event_types
+        .iter()
+        .map(|et| &et.name)
+        .collect_vec();
+    // Min. delay from the last preceding event of a type (per `B` event)
+    let mut precedence_map: HashMap<EventTypePair, Vec<Option<f64>>> = HashMap::new();
+    // Delay to the directly following event (per `A` event)
+    let mut chain_response_map: HashMap<EventTypePair, Vec<Option<f64>>> = HashMap::new();
+    // Delay from the directly preceding event (per `B` event)
+    let mut chain_precedence_map: HashMap<EventTypePair, Vec<Option<f64>>> = HashMap::new();
+    // Min. delay to the next following event of a type and whether the object has any such event at all (per `A` event)
+    let mut succession_map: HashMap<EventTypePair, Vec<(Option<f64>, bool)>> = HashMap::new();
+    for index in get_instances(
+        ocel,
+        &EventOrObjectType::Object(object_type.to_string()),
+        sampling,
+    ) {
+        let evs = ocel
+            .symmetric_rels
+            .get(&index)
+            .into_iter()
+            .flatten()
+            .flat_map(|(o_or_e_index, _reversed, _qualifier)| match o_or_e_index {
+                EventOrObjectIndex::Event(ei) => Some(ei),
+                EventOrObjectIndex::Object(_) => None,
+            })
+            .unique()
+            .flat_map(|ei| ocel.ev_by_index(ei))
+            .collect_vec();
+        // Only the checked events are sampled, all events are still considered as potential partners
+        let checked = sampling.sample(
+            (0..evs.len()).collect(),
+            SAMPLE_MIN_NUM_EVENTS_PER_OBJECT,
+            &mut rng,
+        );
+        for i in checked {
+            let ev = evs[i];
+            let mut min_delay_from: HashMap<&String, f64> = HashMap::new();
+            let mut min_delay_to: HashMap<&String, f64> = HashMap::new();
+            let mut has_other_of_type: HashSet<&String> = HashSet::new();
+            for (j, other) in evs.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+                has_other_of_type.insert(&other.event_type);
+                if other.time <= ev.time {
+                    let delay = seconds_between(other, ev);
+                    let v = min_delay_from.entry(&other.event_type).or_insert(delay);
+                    *v = v.min(delay);
+                }
+                if other.time >= ev.time {
+                    let delay = seconds_between(ev, other);
+                    let v = min_delay_to.entry(&other.event_type).or_insert(delay);
+                    *v = v.min(delay);
+                }
+            }
+            let next = direct_neighbor(&evs, i, true);
+            let prev = direct_neighbor(&evs, i, false);
+            for t in &event_types {
+                precedence_map
+                    .entry((t, &ev.event_type))
+                    .or_default()
+                    .push(min_delay_from.get(t).copied());
+                chain_response_map
+                    .entry((&ev.event_type, t))
+                    .or_default()
+                    .push(
+                        next.filter(|j| &&evs[*j].event_type == t)
+                            .map(|j| seconds_between(ev, evs[j])),
+                    );
+                chain_precedence_map
+                    .entry((t, &ev.event_type))
+                    .or_default()
+                    .push(
+                        prev.filter(|j| &&evs[*j].event_type == t)
+                            .map(|j| seconds_between(evs[j], ev)),
+                    );
+                succession_map
+                    .entry((&ev.event_type, t))
+                    .or_default()
+                    .push((min_delay_to.get(t).copied(), has_other_of_type.contains(t)));
+            }
+        }
+    }
+
+    let between_ev_types = event_types.iter().map(|t| (*t).clone()).collect_vec();
+    let to_constraint = |kind: OrderingConstraintKind,
+                         (from_ev_type, to_ev_type): (&String, &String),
+                         (min, max): (Option<f64>, Option<f64>)| {
+        OrderingConstraint {
+            kind,
+            from_ev_type: from_ev_type.clone(),
+            to_ev_type: to_ev_type.clone(),
+            min_duration_sec: min,
+            max_duration_sec: max,
+            for_object_type: object_type.to_string(),
+            between_ev_types: between_ev_types.clone(),
+        }
+    };
+    let mut ret = Vec::new();
+    for (kind, map) in [
+        (OrderingConstraintKind::Precedence, &precedence_map),
+        (OrderingConstraintKind::ChainResponse, &chain_response_map),
+        (
+            OrderingConstraintKind::ChainPrecedence,
+            &chain_precedence_map,
+        ),
+    ] {
+        for (types, seconds) in map.iter().sorted_by_key(|(types, _)| *types) {
+            if fraction_some(seconds) >= coverage {
+                if let Some((min, max)) = duration_range_with_coverage(seconds, coverage) {
+                    ret.push(to_constraint(kind, *types, (Some(min), Some(max))));
+                }
+            }
+        }
+    }
+    for (types, values) in succession_map.iter().sorted_by_key(|(types, _)| *types) {
+        let co_occurring = values.iter().filter(|(_, co)| *co).count() as f32;
+        // Only interesting if the events usually occur together (but not in this order)
+        if co_occurring / (values.len() as f32) < coverage {
+            continue;
+        }
+        let num_required = (values.len() as f32 * coverage).ceil() as usize;
+        let num_allowed_violations = values.len() - num_required;
+        let delays = values
+            .iter()
+            .flat_map(|(delay, _)| *delay)
+            .sorted_by(f64::total_cmp)
+            .collect_vec();
+        match delays.get(num_allowed_violations) {
+            None => ret.push(to_constraint(
+                OrderingConstraintKind::NotSuccession,
+                *types,
+                (Some(0.0), None),
+            )),
+            // Exclude the first violating delay (time differences are in ms)
+            Some(threshold) if threshold - 0.0005 >= MIN_NOT_SUCCESSION_WINDOW_SEC => {
+                ret.push(to_constraint(
+                    OrderingConstraintKind::NotSuccession,
+                    *types,
+                    (Some(0.0), Some(threshold - 0.0005)),
+                ))
+            }
+            Some(_) => {}
+        }
+    }
+    ret
+}
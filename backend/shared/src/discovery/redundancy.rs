@@ -0,0 +1,593 @@
+//! Removal of redundant discovered constraints
+//!
+//! Two constraints are compared if their root nodes bind the same single (anchor) object or event variable type.
+//! For each constraint, the set of satisfying (sampled) anchor instances is computed using [`label_bindings`].
+//!
+//! * Constraints satisfied by exactly the same instances are _equivalent_: Only the simplest (and tightest) one is kept.
+//! * A constraint is _implied_ by a stronger one, if all instances satisfying the stronger constraint also satisfy it,
+//!   and it is satisfied by at most [`RedundancyOptions::tolerance`] (fraction) more instances.
+//!   Additionally, constraints differing only in their ranges are compared by interval containment
+//!   (see [`is_tighter_variant`]).
+//!
+//! The remaining constraints are ranked by the lower bound of the confidence interval of their (sampled) coverage.
+use std::collections::{HashMap, HashSet};
+
+use itertools::Itertools;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    binding_box::{
+        sampling::wilson_score_interval,
+        structs::{
+            BindingBoxTreeNode, Constraint, EventVariable, Filter, ObjectVariable, SizeFilter,
+            ValueFilter, Variable,
+        },
+        BindingBoxTree,
+    },
+    preprocessing::linked_ocel::IndexLinkedOCEL,
+};
+
+use super::{
+    advanced::{generate_sample_bindings, label_bindings, EventOrObjectType},
+    DiscoverySamplingOptions,
+};
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RedundancyOptions {
+    /// An implied constraint is only removed if it is satisfied by at most this fraction of additional instances
+    pub tolerance: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedundancyReason {
+    /// Satisfied by the same instances as the kept constraint
+    Equivalent,
+    /// Implied by the (stronger) kept constraint
+    Implied,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RedundantConstraint {
+    pub name: String,
+    pub tree: BindingBoxTree,
+    /// Name of the kept constraint this constraint is redundant to
+    pub kept_name: String,
+    pub reason: RedundancyReason,
+}
+
+/// Whether the anchor variable is an object variable, and the types it is bound to
+pub(crate) type AnchorKey = (bool, Vec<String>);
+
+struct LabeledConstraint {
+    name: String,
+    tree: BindingBoxTree,
+    anchor: Option<AnchorKey>,
+    /// For each (sampled) anchor instance, if it satisfies the constraint
+    labels: Option<Vec<bool>>,
+    num_satisfied: usize,
+}
+
+impl LabeledConstraint {
+    fn coverage(&self) -> Option<f64> {
+        self.labels
+            .as_ref()
+            .filter(|labels| !labels.is_empty())
+            .map(|labels| self.num_satisfied as f64 / labels.len() as f64)
+    }
+
+    fn confidence(&self) -> Option<f64> {
+        let n = self.labels.as_ref()?.len() as f64;
+        self.coverage()
+            .map(|coverage| wilson_score_interval(coverage, n).0.max(0.0))
+    }
+
+    /// Whether this constraint is satisfied by all instances satisfying the other one
+    fn is_implied_by(&self, other: &LabeledConstraint) -> bool {
+        match (&self.labels, &other.labels) {
+            (Some(labels), Some(other_labels)) if self.anchor == other.anchor => labels
+                .iter()
+                .zip(other_labels)
+                .all(|(sat, other_sat)| *sat || !*other_sat),
+            _ => false,
+        }
+    }
+}
+
+///
+/// Remove redundant constraints (see [module-level documentation](self))
+///
+/// Returns the kept constraints (ranked) and the removed ones
+///
+pub fn remove_redundant_constraints(
+    ocel: &IndexLinkedOCEL,
+    constraints: Vec<(String, BindingBoxTree)>,
+    options: &RedundancyOptions,
+    sampling: &DiscoverySamplingOptions,
+) -> (Vec<(String, BindingBoxTree)>, Vec<RedundantConstraint>) {
+    let labeled: Vec<LabeledConstraint> = constraints
+        .into_par_iter()
+        .map(|(name, tree)| {
+            let anchored = anchor_subtree(&tree);
+            let anchor = anchored.as_ref().map(|(key, _, _)| key.clone());
+            let labels = anchored.map(|((is_object, types), variable, subtree)| {
+                let types = types
+                    .into_iter()
+                    .map(|t| {
+                        if is_object {
+                            EventOrObjectType::Object(t)
+                        } else {
+                            EventOrObjectType::Event(t)
+                        }
+                    })
+                    .collect_vec();
+                let bindings = generate_sample_bindings(ocel, &types, variable, sampling);
+                label_bindings(ocel, &bindings, &subtree)
+            });
+            let num_satisfied = labels
+                .iter()
+                .flatten()
+                .filter(|satisfied| **satisfied)
+                .count();
+            LabeledConstraint {
+                name,
+                tree,
+                anchor,
+                labels,
+                num_satisfied,
+            }
+        })
+        .collect();
+
+    // Group equivalent constraints, the simplest and tightest one represents the group
+    let mut groups: Vec<Vec<LabeledConstraint>> = Vec::new();
+    let mut group_of_labels: HashMap<(AnchorKey, Vec<bool>), usize> = HashMap::new();
+    for c in labeled {
+        let key = c.anchor.clone().zip(c.labels.clone());
+        match key.as_ref().and_then(|key| group_of_labels.get(key)) {
+            Some(group) => groups[*group].push(c),
+            None => {
+                if let Some(key) = key {
+                    group_of_labels.insert(key, groups.len());
+                }
+                groups.push(vec![c]);
+            }
+        }
+    }
+    let mut redundant = Vec::new();
+    let mut representatives = Vec::new();
+    for mut group in groups {
+        group.sort_by(|a, b| {
+            a.tree
+                .nodes
+                .len()
+                .cmp(&b.tree.nodes.len())
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        let rep_index = (0..group.len())
+            .find(|i| {
+                !group.iter().enumerate().any(|(j, other)| {
+                    *i != j
+                        && is_tighter_variant(&other.tree, &group[*i].tree)
+                        && !is_tighter_variant(&group[*i].tree, &other.tree)
+                })
+            })
+            .unwrap_or(0);
+        let rep = group.remove(rep_index);
+        redundant.extend(group.into_iter().map(|c| RedundantConstraint {
+            name: c.name,
+            tree: c.tree,
+            kept_name: rep.name.clone(),
+            reason: RedundancyReason::Equivalent,
+        }));
+        representatives.push(rep);
+    }
+
+    // Stronger constraints (satisfied by fewer instances) first
+    representatives.sort_by(|a, b| {
+        a.labels
+            .is_none()
+            .cmp(&b.labels.is_none())
+            .then_with(|| a.num_satisfied.cmp(&b.num_satisfied))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    let mut kept: Vec<LabeledConstraint> = Vec::new();
+    for c in representatives {
+        let implied_by = kept.iter().find(|k| {
+            let within_tolerance = match (c.coverage(), k.coverage()) {
+                (Some(c_cov), Some(k_cov)) => c_cov - k_cov <= options.tolerance as f64,
+                _ => false,
+            };
+            within_tolerance && (is_tighter_variant(&k.tree, &c.tree) || c.is_implied_by(k))
+        });
+        match implied_by {
+            Some(k) => redundant.push(RedundantConstraint {
+                kept_name: k.name.clone(),
+                name: c.name,
+                tree: c.tree,
+                reason: RedundancyReason::Implied,
+            }),
+            None => kept.push(c),
+        }
+    }
+
+    kept.sort_by(|a, b| {
+        b.confidence()
+            .unwrap_or(-1.0)
+            .total_cmp(&a.confidence().unwrap_or(-1.0))
+            .then_with(|| a.tree.nodes.len().cmp(&b.tree.nodes.len()))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    (
+        kept.into_iter().map(|c| (c.name, c.tree)).collect(),
+        redundant,
+    )
+}
+
+///
+/// The anchor of the tree (i.e., the single object variable, or otherwise single event variable, bound by the root)
+///
+/// Returns the anchor key and variable, as well as the tree without the anchor variable being bound by the root
+/// (to be evaluated for bindings of the anchor)
+///
+pub(crate) fn anchor_subtree(
+    tree: &BindingBoxTree,
+) -> Option<(AnchorKey, Variable, BindingBoxTree)> {
+    let mut subtree = tree.clone();
+    let root = match subtree.nodes.first_mut()? {
+        BindingBoxTreeNode::Box(root, _) => root,
+        _ => return None,
+    };
+    let (key, variable) = if root.new_object_vars.len() == 1 {
+        let (ob_var, types): (ObjectVariable, HashSet<String>) =
+            root.new_object_vars.drain().next()?;
+        (
+            (true, types.into_iter().sorted().collect_vec()),
+            Variable::Object(ob_var),
+        )
+    } else if root.new_object_vars.is_empty() && root.new_event_vars.len() == 1 {
+        let (ev_var, types): (EventVariable, HashSet<String>) =
+            root.new_event_vars.drain().next()?;
+        (
+            (false, types.into_iter().sorted().collect_vec()),
+            Variable::Event(ev_var),
+        )
+    } else {
+        return None;
+    };
+    Some((key, variable, subtree))
+}
+
+/// Whether the range `(min, max)` is contained in `(other_min, other_max)` (`None` representing no restriction)
+fn range_contained<T: PartialOrd>(
+    (min, max): (Option<T>, Option<T>),
+    (other_min, other_max): (Option<T>, Option<T>),
+) -> bool {
+    let min_ok = match (min, other_min) {
+        (_, None) => true,
+        (Some(min), Some(other_min)) => min >= other_min,
+        (None, Some(_)) => false,
+    };
+    let max_ok = match (max, other_max) {
+        (_, None) => true,
+        (Some(max), Some(other_max)) => max <= other_max,
+        (None, Some(_)) => false,
+    };
+    min_ok && max_ok
+}
+
+fn value_filter_contained(filter: &ValueFilter, other: &ValueFilter) -> bool {
+    match (filter, other) {
+        (
+            ValueFilter::Float { min, max },
+            ValueFilter::Float {
+                min: o_min,
+                max: o_max,
+            },
+        ) => range_contained((*min, *max), (*o_min, *o_max)),
+        (
+            ValueFilter::Integer { min, max },
+            ValueFilter::Integer {
+                min: o_min,
+                max: o_max,
+            },
+        ) => range_contained((*min, *max), (*o_min, *o_max)),
+        (
+            ValueFilter::Time { from, to },
+            ValueFilter::Time {
+                from: o_from,
+                to: o_to,
+            },
+        ) => range_contained((*from, *to), (*o_from, *o_to)),
+        (ValueFilter::String { is_in }, ValueFilter::String { is_in: o_is_in }) => {
+            is_in.iter().all(|v| o_is_in.contains(v))
+        }
+        _ => filter == other,
+    }
+}
+
+/// Whether the root constraint is at least as strict as the other one (see [`is_tighter_variant`])
+fn root_constraint_contained(constraint: &Constraint, other: &Constraint) -> bool {
+    match (constraint, other) {
+        (
+            Constraint::SizeFilter {
+                filter:
+                    SizeFilter::NumChilds {
+                        child_name,
+                        min,
+                        max,
+                    },
+            },
+            Constraint::SizeFilter {
+                filter:
+                    SizeFilter::NumChilds {
+                        child_name: o_child_name,
+                        min: o_min,
+                        max: o_max,
+                    },
+            },
+        ) => child_name == o_child_name && range_contained((*min, *max), (*o_min, *o_max)),
+        (Constraint::Filter { filter }, Constraint::Filter { filter: o_filter }) => {
+            match (filter, o_filter) {
+                (
+                    Filter::EventAttributeValueFilter {
+                        event,
+                        attribute_name,
+                        value_filter,
+                    },
+                    Filter::EventAttributeValueFilter {
+                        event: o_event,
+                        attribute_name: o_attribute_name,
+                        value_filter: o_value_filter,
+                    },
+                ) => {
+                    event == o_event
+                        && attribute_name == o_attribute_name
+                        && value_filter_contained(value_filter, o_value_filter)
+                }
+                (
+                    Filter::ObjectAttributeValueFilter {
+                        object,
+                        attribute_name,
+                        at_time,
+                        value_filter,
+                    },
+                    Filter::ObjectAttributeValueFilter {
+                        object: o_object,
+                        attribute_name: o_attribute_name,
+                        at_time: o_at_time,
+                        value_filter: o_value_filter,
+                    },
+                ) => {
+                    object == o_object
+                        && attribute_name == o_attribute_name
+                        && at_time == o_at_time
+                        && value_filter_contained(value_filter, o_value_filter)
+                }
+                _ => filter == o_filter,
+            }
+        }
+        _ => constraint == other,
+    }
+}
+
+///
+/// Whether tree `a` is a tighter variant of tree `b`, i.e., `a` implies `b` on any OCEL
+///
+/// Both trees have to be equal, except for
+/// * the ranges of count constraints and value filter constraints of the root node, and
+/// * the time windows of children of the root, which are required to occur at least once (without an upper bound),
+///
+/// where all ranges of `a` are contained in the ones of `b`.
+///
+pub fn is_tighter_variant(a: &BindingBoxTree, b: &BindingBoxTree) -> bool {
+    if a.nodes.len() != b.nodes.len() || a.edge_names != b.edge_names {
+        return false;
+    }
+    let (a_root, b_root) = match (a.nodes.first(), b.nodes.first()) {
+        (
+            Some(BindingBoxTreeNode::Box(a_root, a_children)),
+            Some(BindingBoxTreeNode::Box(b_root, b_children)),
+        ) if a_children == b_children => (a_root, b_root),
+        _ => return false,
+    };
+    if a_root.new_event_vars != b_root.new_event_vars
+        || a_root.new_object_vars != b_root.new_object_vars
+        || a_root.filters != b_root.filters
+        || a_root.size_filters != b_root.size_filters
+        || a_root.constraints.len() != b_root.constraints.len()
+        || !a_root
+            .constraints
+            .iter()
+            .zip(&b_root.constraints)
+            .all(|(a_c, b_c)| root_constraint_contained(a_c, b_c))
+    {
+        return false;
+    }
+    // Children of the root, which only need to exist (in both trees)
+    let existential_children: HashSet<usize> = a
+        .edge_names
+        .iter()
+        .filter(|((from, _to), _name)| *from == 0)
+        .filter(|(_edge, name)| {
+            [&a_root.constraints, &b_root.constraints]
+                .iter()
+                .all(|constraints| {
+                    constraints.iter().any(|c| {
+                        matches!(c, Constraint::SizeFilter { filter: SizeFilter::NumChilds { child_name, min: Some(min), max: None } } if child_name == *name && *min >= 1)
+                    })
+                })
+        })
+        .map(|((_from, to), _name)| *to)
+        .collect();
+    a.nodes
+        .iter()
+        .zip(&b.nodes)
+        .enumerate()
+        .skip(1)
+        .all(|(index, (a_node, b_node))| match (a_node, b_node) {
+            (
+                BindingBoxTreeNode::Box(a_box, a_children),
+                BindingBoxTreeNode::Box(b_box, b_children),
+            ) if existential_children.contains(&index) => {
+                a_children == b_children
+                    && a_box.new_event_vars == b_box.new_event_vars
+                    && a_box.new_object_vars == b_box.new_object_vars
+                    && a_box.size_filters == b_box.size_filters
+                    && a_box.constraints == b_box.constraints
+                    && a_box.filters.len() == b_box.filters.len()
+                    && a_box
+                        .filters
+                        .iter()
+                        .zip(&b_box.filters)
+                        .all(|(a_f, b_f)| match (a_f, b_f) {
+                            (
+                                Filter::TimeBetweenEvents {
+                                    from_event,
+                                    to_event,
+                                    min_seconds,
+                                    max_seconds,
+                                },
+                                Filter::TimeBetweenEvents {
+                                    from_event: o_from_event,
+                                    to_event: o_to_event,
+                                    min_seconds: o_min_seconds,
+                                    max_seconds: o_max_seconds,
+                                },
+                            ) => {
+                                from_event == o_from_event
+                                    && to_event == o_to_event
+                                    && range_contained(
+                                        (*min_seconds, *max_seconds),
+                                        (*o_min_seconds, *o_max_seconds),
+                                    )
+                            }
+                            _ => a_f == b_f,
+                        })
+            }
+            _ => a_node == b_node,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        binding_box::test::example_linked_ocel,
+        discovery::graph_discovery::{CountConstraint, EFConstraint},
+    };
+
+    use super::*;
+
+    fn items_per_order(min: usize, max: usize) -> (String, BindingBoxTree) {
+        let c = CountConstraint {
+            min_count: Some(min),
+            max_count: Some(max),
+            root_type: EventOrObjectType::Object("orders".to_string()),
+            related_type: EventOrObjectType::Object("items".to_string()),
+            ocel_relation_flipped: false,
+        };
+        (c.get_constraint_name(), c.get_full_tree())
+    }
+
+    fn place_then_pick(max_duration_sec: f64) -> BindingBoxTree {
+        EFConstraint {
+            from_ev_type: "place order".to_string(),
+            to_ev_type: "pick item".to_string(),
+            min_duration_sec: Some(0.0),
+            max_duration_sec: Some(max_duration_sec),
+            for_object_type: "orders".to_string(),
+        }
+        .get_full_tree()
+    }
+
+    fn full_data() -> DiscoverySamplingOptions {
+        DiscoverySamplingOptions {
+            full_data: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn tighter_count_ranges() {
+        let (_, narrow) = items_per_order(1, 2);
+        let (_, wide) = items_per_order(0, 5);
+        assert!(is_tighter_variant(&narrow, &wide));
+        assert!(!is_tighter_variant(&wide, &narrow));
+        assert!(is_tighter_variant(&narrow, &narrow));
+
+        let pays = CountConstraint {
+            min_count: Some(0),
+            max_count: Some(5),
+            root_type: EventOrObjectType::Object("orders".to_string()),
+            related_type: EventOrObjectType::Event("pay order".to_string()),
+            ocel_relation_flipped: false,
+        }
+        .get_full_tree();
+        assert!(!is_tighter_variant(&narrow, &pays));
+    }
+
+    #[test]
+    fn tighter_time_windows() {
+        assert!(is_tighter_variant(
+            &place_then_pick(600.0),
+            &place_then_pick(3600.0)
+        ));
+        assert!(!is_tighter_variant(
+            &place_then_pick(3600.0),
+            &place_then_pick(600.0)
+        ));
+    }
+
+    #[test]
+    fn equivalent_constraints_keep_the_tightest() {
+        let ocel = example_linked_ocel();
+        // Every order has one to three items
+        let (kept, redundant) = remove_redundant_constraints(
+            &ocel,
+            vec![items_per_order(0, 5), items_per_order(1, 3)],
+            &RedundancyOptions { tolerance: 0.0 },
+            &full_data(),
+        );
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].0, items_per_order(1, 3).0);
+        assert_eq!(redundant.len(), 1);
+        assert_eq!(redundant[0].name, items_per_order(0, 5).0);
+        assert_eq!(redundant[0].kept_name, kept[0].0);
+        assert_eq!(redundant[0].reason, RedundancyReason::Equivalent);
+    }
+
+    #[test]
+    fn implied_constraints_within_tolerance() {
+        let ocel = example_linked_ocel();
+        let constraints = || vec![items_per_order(1, 3), items_per_order(1, 2)];
+        // Six of eight orders have at most two items
+        let (kept, redundant) = remove_redundant_constraints(
+            &ocel,
+            constraints(),
+            &RedundancyOptions { tolerance: 0.3 },
+            &full_data(),
+        );
+        assert_eq!(
+            kept.iter().map(|(name, _)| name).collect_vec(),
+            vec![&items_per_order(1, 2).0]
+        );
+        assert_eq!(redundant.len(), 1);
+        assert_eq!(redundant[0].name, items_per_order(1, 3).0);
+        assert_eq!(redundant[0].reason, RedundancyReason::Implied);
+
+        let (kept, redundant) = remove_redundant_constraints(
+            &ocel,
+            constraints(),
+            &RedundancyOptions { tolerance: 0.1 },
+            &full_data(),
+        );
+        assert!(redundant.is_empty());
+        // Ranked by confidence
+        assert_eq!(
+            kept.iter().map(|(name, _)| name).collect_vec(),
+            vec![&items_per_order(1, 3).0, &items_per_order(1, 2).0]
+        );
+    }
+}
@@ -0,0 +1,20 @@
+//! Escaping of strings for the text-based export formats (GraphViz DOT and XML)
+
+/// Quoted DOT string (e.g., for ids and labels)
+pub fn dot_string(s: &str) -> String {
+    format!(
+        "\"{}\"",
+        s.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
+
+/// Escaped XML attribute value or text content
+pub fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -9,13 +9,22 @@ pub mod ocel_qualifiers {
     pub mod qualifiers;
 }
 pub mod binding_box;
+pub mod comparison;
 pub mod constraints_2;
 pub mod discovery;
+pub mod escape;
 pub mod ocel_graph;
 pub mod preprocessing {
+    pub mod attribute_index;
+    pub mod cache;
+    pub mod filter;
+    pub mod flatten;
     pub mod linked_ocel;
     pub mod preprocess;
+    pub mod process_executions;
+    pub mod profile;
     pub mod tests;
+    pub mod variants;
 }
 pub mod cel;
 
@@ -0,0 +1,294 @@
+//! Export of [`OCELGraph`]s to common graph formats (GraphViz DOT, GEXF and GraphML)
+//!
+//! Nodes carry their kind (`event` or `object`), OCEL id, type and (for events) time,
+//! while links are labeled by their qualifier.
+//! As events and objects can share ids, node ids are prefixed by their kind (`ev:` or `ob:`).
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::escape::{dot_string, xml_escape};
+
+use super::{GraphLink, GraphNode, OCELGraph, OCELGraphOptions};
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum OCELGraphExportFormat {
+    DOT,
+    GEXF,
+    GraphML,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportOCELGraphRequest {
+    pub options: OCELGraphOptions,
+    pub format: OCELGraphExportFormat,
+}
+
+/// Node id in the exported graph (unique across events and objects)
+fn node_id(ocel_id: &str, is_object: bool) -> String {
+    match is_object {
+        true => format!("ob:{ocel_id}"),
+        false => format!("ev:{ocel_id}"),
+    }
+}
+
+/// Node id, OCEL id, kind, type and time (only for events) of a graph node
+fn node_info(node: &GraphNode) -> (String, &String, &'static str, &String, Option<String>) {
+    match node {
+        GraphNode::Event(ev) => (
+            node_id(&ev.id, false),
+            &ev.id,
+            "event",
+            &ev.event_type,
+            Some(ev.time.to_rfc3339()),
+        ),
+        GraphNode::Object(ob) => (
+            node_id(&ob.id, true),
+            &ob.id,
+            "object",
+            &ob.object_type,
+            None,
+        ),
+    }
+}
+
+/// Node ids of the source and target of a link
+fn link_node_ids(link: &GraphLink) -> (String, String) {
+    (
+        node_id(&link.source, link.source_is_object),
+        node_id(&link.target, true),
+    )
+}
+
+///
+/// Serialize the graph in the given format
+///
+pub fn export_ocel_graph(graph: &OCELGraph, format: OCELGraphExportFormat) -> String {
+    match format {
+        OCELGraphExportFormat::DOT => ocel_graph_to_dot(graph),
+        OCELGraphExportFormat::GEXF => ocel_graph_to_gexf(graph),
+        OCELGraphExportFormat::GraphML => ocel_graph_to_graphml(graph),
+    }
+}
+
+///
+/// Render the graph in the DOT format (Graphviz)
+///
+/// Events are drawn as boxes and objects as ellipses
+///
+pub fn ocel_graph_to_dot(graph: &OCELGraph) -> String {
+    let mut dot = String::from("digraph OCELGraph {\n");
+    for node in &graph.nodes {
+        let (id, ocel_id, kind, node_type, time) = node_info(node);
+        let shape = if kind == "event" { "box" } else { "ellipse" };
+        let label = match time {
+            Some(time) => format!("{ocel_id}\n{node_type}\n{time}"),
+            None => format!("{ocel_id}\n{node_type}"),
+        };
+        writeln!(
+            dot,
+            "  {} [shape={shape}, label={}, kind={kind}, ocel_id={}, type={}];",
+            dot_string(&id),
+            dot_string(&label),
+            dot_string(ocel_id),
+            dot_string(node_type)
+        )
+        .unwrap();
+    }
+    for link in &graph.links {
+        let (source, target) = link_node_ids(link);
+        writeln!(
+            dot,
+            "  {} -> {} [label={}];",
+            dot_string(&source),
+            dot_string(&target),
+            dot_string(&link.qualifier)
+        )
+        .unwrap();
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+///
+/// Serialize the graph in the GEXF format (e.g., for Gephi)
+///
+pub fn ocel_graph_to_gexf(graph: &OCELGraph) -> String {
+    let mut gexf = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">\n",
+        "  <graph defaultedgetype=\"directed\">\n",
+        "    <attributes class=\"node\">\n",
+        "      <attribute id=\"kind\" title=\"kind\" type=\"string\"/>\n",
+        "      <attribute id=\"type\" title=\"type\" type=\"string\"/>\n",
+        "      <attribute id=\"time\" title=\"time\" type=\"string\"/>\n",
+        "    </attributes>\n",
+        "    <nodes>\n"
+    ));
+    for node in &graph.nodes {
+        let (id, ocel_id, kind, node_type, time) = node_info(node);
+        writeln!(
+            gexf,
+            "      <node id=\"{}\" label=\"{}\">",
+            xml_escape(&id),
+            xml_escape(ocel_id)
+        )
+        .unwrap();
+        gexf.push_str("        <attvalues>\n");
+        writeln!(gexf, "          <attvalue for=\"kind\" value=\"{kind}\"/>").unwrap();
+        writeln!(
+            gexf,
+            "          <attvalue for=\"type\" value=\"{}\"/>",
+            xml_escape(node_type)
+        )
+        .unwrap();
+        if let Some(time) = time {
+            writeln!(gexf, "          <attvalue for=\"time\" value=\"{time}\"/>").unwrap();
+        }
+        gexf.push_str("        </attvalues>\n      </node>\n");
+    }
+    gexf.push_str("    </nodes>\n    <edges>\n");
+    for (i, link) in graph.links.iter().enumerate() {
+        let (source, target) = link_node_ids(link);
+        writeln!(
+            gexf,
+            "      <edge id=\"{i}\" source=\"{}\" target=\"{}\" label=\"{}\"/>",
+            xml_escape(&source),
+            xml_escape(&target),
+            xml_escape(&link.qualifier)
+        )
+        .unwrap();
+    }
+    gexf.push_str("    </edges>\n  </graph>\n</gexf>\n");
+    gexf
+}
+
+///
+/// Serialize the graph in the GraphML format
+///
+pub fn ocel_graph_to_graphml(graph: &OCELGraph) -> String {
+    let mut graphml = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+        "  <key id=\"kind\" for=\"node\" attr.name=\"kind\" attr.type=\"string\"/>\n",
+        "  <key id=\"ocel_id\" for=\"node\" attr.name=\"ocel_id\" attr.type=\"string\"/>\n",
+        "  <key id=\"type\" for=\"node\" attr.name=\"type\" attr.type=\"string\"/>\n",
+        "  <key id=\"time\" for=\"node\" attr.name=\"time\" attr.type=\"string\"/>\n",
+        "  <key id=\"qualifier\" for=\"edge\" attr.name=\"qualifier\" attr.type=\"string\"/>\n",
+        "  <graph id=\"OCELGraph\" edgedefault=\"directed\">\n"
+    ));
+    for node in &graph.nodes {
+        let (id, ocel_id, kind, node_type, time) = node_info(node);
+        writeln!(graphml, "    <node id=\"{}\">", xml_escape(&id)).unwrap();
+        writeln!(graphml, "      <data key=\"kind\">{kind}</data>").unwrap();
+        writeln!(
+            graphml,
+            "      <data key=\"ocel_id\">{}</data>",
+            xml_escape(ocel_id)
+        )
+        .unwrap();
+        writeln!(
+            graphml,
+            "      <data key=\"type\">{}</data>",
+            xml_escape(node_type)
+        )
+        .unwrap();
+        if let Some(time) = time {
+            writeln!(graphml, "      <data key=\"time\">{time}</data>").unwrap();
+        }
+        graphml.push_str("    </node>\n");
+    }
+    for link in &graph.links {
+        let (source, target) = link_node_ids(link);
+        writeln!(
+            graphml,
+            "    <edge source=\"{}\" target=\"{}\">",
+            xml_escape(&source),
+            xml_escape(&target)
+        )
+        .unwrap();
+        writeln!(
+            graphml,
+            "      <data key=\"qualifier\">{}</data>",
+            xml_escape(&link.qualifier)
+        )
+        .unwrap();
+        graphml.push_str("    </edge>\n");
+    }
+    graphml.push_str("  </graph>\n</graphml>\n");
+    graphml
+}
+
+#[cfg(test)]
+mod tests {
+    use process_mining::ocel::ocel_struct::{OCELEvent, OCELObject};
+
+    use crate::binding_box::test::example_start_time;
+
+    use super::*;
+
+    /// An event and an object sharing the same id (which needs escaping)
+    fn colliding_graph() -> OCELGraph {
+        let id = "x\"<&>'1".to_string();
+        OCELGraph {
+            nodes: vec![
+                GraphNode::Event(OCELEvent {
+                    id: id.clone(),
+                    event_type: "place order".to_string(),
+                    time: example_start_time(),
+                    attributes: Vec::new(),
+                    relationships: Vec::new(),
+                }),
+                GraphNode::Object(OCELObject {
+                    id: id.clone(),
+                    object_type: "orders".to_string(),
+                    attributes: Vec::new(),
+                    relationships: Vec::new(),
+                }),
+            ],
+            links: vec![GraphLink {
+                source: id.clone(),
+                target: id,
+                qualifier: "placed\nby".to_string(),
+                source_is_object: false,
+            }],
+        }
+    }
+
+    #[test]
+    fn dot_export_distinguishes_colliding_ids() {
+        let dot = ocel_graph_to_dot(&colliding_graph());
+        assert!(dot.contains(r#"  "ev:x\"<&>'1" [shape=box, "#));
+        assert!(dot.contains(r#"  "ob:x\"<&>'1" [shape=ellipse, "#));
+        assert!(dot.contains(r#"ocel_id="x\"<&>'1""#));
+        assert!(dot.contains(r#"  "ev:x\"<&>'1" -> "ob:x\"<&>'1" [label="placed\nby"];"#));
+    }
+
+    #[test]
+    fn xml_exports_distinguish_colliding_ids() {
+        let escaped = "x&quot;&lt;&amp;&gt;&apos;1";
+        let graphml = ocel_graph_to_graphml(&colliding_graph());
+        assert!(graphml.contains(&format!("<node id=\"ev:{escaped}\">")));
+        assert!(graphml.contains(&format!("<node id=\"ob:{escaped}\">")));
+        assert_eq!(
+            graphml
+                .matches(&format!("<data key=\"ocel_id\">{escaped}</data>"))
+                .count(),
+            2
+        );
+        assert!(graphml.contains(&format!(
+            "<edge source=\"ev:{escaped}\" target=\"ob:{escaped}\">"
+        )));
+
+        let gexf = ocel_graph_to_gexf(&colliding_graph());
+        assert!(gexf.contains(&format!("<node id=\"ev:{escaped}\" label=\"{escaped}\">")));
+        assert!(gexf.contains(&format!("<node id=\"ob:{escaped}\" label=\"{escaped}\">")));
+        assert!(gexf.contains(&format!(
+            "<edge id=\"0\" source=\"ev:{escaped}\" target=\"ob:{escaped}\" label=\"placed\nby\"/>"
+        )));
+    }
+}
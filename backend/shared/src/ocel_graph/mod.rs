@@ -1,23 +1,36 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
 use process_mining::ocel::ocel_struct::{OCELEvent, OCELObject};
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
 use crate::preprocessing::linked_ocel::{EventOrObjectIndex, IndexLinkedOCEL};
 
+pub mod export;
+pub mod schema;
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(untagged)]
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
 pub enum GraphNode {
-    Event(OCELEvent),
-    Object(OCELObject),
+    Event(#[ts(type = "import(\"../ocel\").OCELEvent")] OCELEvent),
+    Object(#[ts(type = "import(\"../ocel\").OCELObject")] OCELObject),
 }
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
 pub struct GraphLink {
     source: String,
     target: String,
     qualifier: String,
+    /// Whether the source is an object (otherwise, an event); the target is always an object
+    #[serde(default)]
+    source_is_object: bool,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
 pub struct OCELGraph {
     nodes: Vec<GraphNode>,
     links: Vec<GraphLink>,
@@ -33,75 +46,498 @@ pub struct OCELGraphOptions {
     root_is_object: bool,
     rels_size_ignore_threshold: usize,
     spanning_tree: bool,
+    /// Only include events of these types (all if not set)
+    event_types: Option<Vec<String>>,
+    /// Exclude events of these types
+    exclude_event_types: Option<Vec<String>>,
+    /// Only include objects of these types (all if not set)
+    object_types: Option<Vec<String>>,
+    /// Exclude objects of these types
+    exclude_object_types: Option<Vec<String>>,
+    /// Only include relationships with these qualifiers (all if not set)
+    qualifiers: Option<Vec<String>>,
+    /// Exclude relationships with these qualifiers
+    exclude_qualifiers: Option<Vec<String>>,
+    /// Maximal number of nodes (unbounded if not set)
+    ///
+    /// Nodes closer to the root are included first
+    max_nodes: Option<usize>,
 }
 
-pub fn get_ocel_graph(ocel: &IndexLinkedOCEL, options: OCELGraphOptions) -> Option<OCELGraph> {
-    let root_index_opt = match options.root_is_object {
+impl OCELGraphOptions {
+    fn includes_node(&self, ocel: &IndexLinkedOCEL, index: &EventOrObjectIndex) -> bool {
+        let (included, excluded, node_type) = match index {
+            EventOrObjectIndex::Event(e_index) => (
+                &self.event_types,
+                &self.exclude_event_types,
+                &ocel.ocel.events[e_index.0].event_type,
+            ),
+            EventOrObjectIndex::Object(o_index) => (
+                &self.object_types,
+                &self.exclude_object_types,
+                &ocel.ocel.objects[o_index.0].object_type,
+            ),
+        };
+        is_included(node_type, included, excluded)
+    }
+
+    fn includes_qualifier(&self, qualifier: &String) -> bool {
+        is_included(qualifier, &self.qualifiers, &self.exclude_qualifiers)
+    }
+}
+
+fn is_included(
+    value: &String,
+    included: &Option<Vec<String>>,
+    excluded: &Option<Vec<String>>,
+) -> bool {
+    included.as_ref().is_none_or(|inc| inc.contains(value))
+        && !excluded.as_ref().is_some_and(|exc| exc.contains(value))
+}
+
+/// Relationship arc (source, target, qualifier), oriented as in the OCEL
+type Arc = (EventOrObjectIndex, EventOrObjectIndex, String);
+
+fn get_index(ocel: &IndexLinkedOCEL, id: &String, is_object: bool) -> Option<EventOrObjectIndex> {
+    match is_object {
         true => ocel
             .object_index_map
-            .get(&options.root)
+            .get(id)
             .map(|o_index| EventOrObjectIndex::Object(*o_index)),
 
         false => ocel
             .event_index_map
-            .get(&options.root)
+            .get(id)
             .map(|e_index| EventOrObjectIndex::Event(*e_index)),
-    };
-    if let Some(root_index) = root_index_opt {
-        let mut queue = vec![(root_index, 0)];
-        let mut done_indices: Vec<EventOrObjectIndex> = Vec::new();
-        let mut expanded_arcs: Vec<(EventOrObjectIndex, EventOrObjectIndex, String)> = Vec::new();
-        done_indices.push(root_index);
-        let max_distance = options.max_distance;
-        while let Some((index, distance)) = queue.pop() {
-            if distance < max_distance {
-                if let Some(rels) = ocel.symmetric_rels.get(&index) {
-                    // Check for rels_size_ignore_threshold but also continue if at the root node (root node always gets expanded)
-                    if root_index == index || rels.len() < options.rels_size_ignore_threshold {
-                        for (r, reversed, qualifier) in rels {
-                            let arc = if !reversed {
-                                (index, *r, qualifier.clone())
-                            } else {
-                                (*r, index, qualifier.clone())
-                            };
-                            if !done_indices.contains(r) {
-                                expanded_arcs.push(arc);
-                                queue.push((*r, distance + 1));
-                                done_indices.push(*r);
-                            } else if !options.spanning_tree {
-                                expanded_arcs.push(arc);
+    }
+}
+
+fn to_graph_node(ocel: &IndexLinkedOCEL, index: &EventOrObjectIndex) -> GraphNode {
+    match index {
+        EventOrObjectIndex::Object(o_index) => {
+            GraphNode::Object(ocel.ob_by_index(o_index).unwrap().clone())
+        }
+        EventOrObjectIndex::Event(e_index) => {
+            GraphNode::Event(ocel.ev_by_index(e_index).unwrap().clone())
+        }
+    }
+}
+
+fn to_graph_link(ocel: &IndexLinkedOCEL, (from, to, qualifier): &Arc) -> GraphLink {
+    let source_is_object = matches!(from, EventOrObjectIndex::Object(_));
+    let from = ocel.ob_or_ev_by_index(*from).unwrap().cloned();
+    let to = ocel.ob_or_ev_by_index(*to).unwrap().cloned();
+    GraphLink {
+        source: from.get_id().clone(),
+        target: to.get_id().clone(),
+        qualifier: qualifier.clone(),
+        source_is_object,
+    }
+}
+
+fn to_arc(
+    index: EventOrObjectIndex,
+    other: EventOrObjectIndex,
+    reversed: bool,
+    qualifier: &str,
+) -> Arc {
+    if !reversed {
+        (index, other, qualifier.to_string())
+    } else {
+        (other, index, qualifier.to_string())
+    }
+}
+
+pub fn get_ocel_graph(ocel: &IndexLinkedOCEL, options: OCELGraphOptions) -> Option<OCELGraph> {
+    let root_index = get_index(ocel, &options.root, options.root_is_object)?;
+    let mut queue = VecDeque::from([(root_index, 0)]);
+    // Nodes in the order they were discovered (the set is used for fast lookups)
+    let mut done_indices: Vec<EventOrObjectIndex> = vec![root_index];
+    let mut done_set: HashSet<EventOrObjectIndex> = HashSet::from([root_index]);
+    let mut expanded_arcs: Vec<Arc> = Vec::new();
+    let max_distance = options.max_distance;
+    while let Some((index, distance)) = queue.pop_front() {
+        if distance < max_distance {
+            if let Some(rels) = ocel.symmetric_rels.get(&index) {
+                // Check for rels_size_ignore_threshold but also continue if at the root node (root node always gets expanded)
+                if root_index == index || rels.len() < options.rels_size_ignore_threshold {
+                    for (r, reversed, qualifier) in rels {
+                        if !options.includes_qualifier(qualifier) {
+                            continue;
+                        }
+                        let arc = to_arc(index, *r, *reversed, qualifier);
+                        if !done_set.contains(r) {
+                            if !options.includes_node(ocel, r)
+                                || options
+                                    .max_nodes
+                                    .is_some_and(|max| done_indices.len() >= max)
+                            {
+                                continue;
                             }
+                            done_set.insert(*r);
+                            expanded_arcs.push(arc);
+                            queue.push_back((*r, distance + 1));
+                            done_indices.push(*r);
+                        } else if !options.spanning_tree {
+                            expanded_arcs.push(arc);
                         }
                     }
                 }
             }
         }
-        let nodes = done_indices
-            .iter()
-            .map(|i| match i {
-                EventOrObjectIndex::Object(o_index) => {
-                    GraphNode::Object(ocel.ob_by_index(o_index).unwrap().clone())
-                }
-                EventOrObjectIndex::Event(e_index) => {
-                    GraphNode::Event(ocel.ev_by_index(e_index).unwrap().clone())
+    }
+    let nodes = done_indices
+        .iter()
+        .map(|i| to_graph_node(ocel, i))
+        .collect();
+    let links = expanded_arcs
+        .iter()
+        .map(|arc| to_graph_link(ocel, arc))
+        .collect();
+    Some(OCELGraph { nodes, links })
+}
+
+const DEFAULT_MAX_PATHS: usize = 100;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+pub struct OCELPathOptions {
+    pub from: String,
+    pub from_is_object: bool,
+    pub to: String,
+    pub to_is_object: bool,
+    /// Maximal number of links of a path
+    pub max_length: usize,
+    /// If true, all paths (without repeated nodes) of at most `max_length` links are returned;
+    /// Otherwise, only the shortest paths are returned
+    pub all_paths: bool,
+    /// Only traverse relationships with one of these qualifiers (all if not set)
+    pub qualifiers: Option<Vec<String>>,
+    /// Only traverse intermediate events of these types (all if not set)
+    pub event_types: Option<Vec<String>>,
+    /// Only traverse intermediate objects of these types (all if not set)
+    pub object_types: Option<Vec<String>>,
+    /// Maximal number of returned paths (defaults to 100)
+    pub max_paths: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+pub struct OCELPaths {
+    /// Paths, sorted by length
+    ///
+    /// The nodes of a path are ordered from start to end, and the i-th link connects the i-th and (i+1)-th node
+    /// (links keep the direction of the relationship in the OCEL)
+    pub paths: Vec<OCELGraph>,
+    /// Union of all paths (e.g., for displaying them in a single graph)
+    pub graph: OCELGraph,
+}
+
+/// Path as a sequence of nodes and the arcs between consecutive nodes
+type IndexPath = (Vec<EventOrObjectIndex>, Vec<Arc>);
+
+/// Relationships of a node which can be traversed according to the path options
+fn path_neighbors<'a>(
+    ocel: &'a IndexLinkedOCEL,
+    options: &'a OCELPathOptions,
+    index: EventOrObjectIndex,
+    endpoints: [EventOrObjectIndex; 2],
+) -> impl Iterator<Item = (EventOrObjectIndex, Arc)> + 'a {
+    ocel.symmetric_rels
+        .get(&index)
+        .into_iter()
+        .flatten()
+        .filter(move |(r, _reversed, qualifier)| {
+            options
+                .qualifiers
+                .as_ref()
+                .is_none_or(|qs| qs.contains(qualifier))
+                && (endpoints.contains(r)
+                    || match r {
+                        EventOrObjectIndex::Event(e_index) => {
+                            options.event_types.as_ref().is_none_or(|types| {
+                                types.contains(&ocel.ocel.events[e_index.0].event_type)
+                            })
+                        }
+                        EventOrObjectIndex::Object(o_index) => {
+                            options.object_types.as_ref().is_none_or(|types| {
+                                types.contains(&ocel.ocel.objects[o_index.0].object_type)
+                            })
+                        }
+                    })
+        })
+        .map(move |(r, reversed, qualifier)| (*r, to_arc(index, *r, *reversed, qualifier)))
+}
+
+/// Predecessor nodes (and the arcs connecting them) of every node on shortest paths
+type Predecessors = HashMap<EventOrObjectIndex, Vec<(EventOrObjectIndex, Arc)>>;
+
+/// BFS distances from `start` (up to `max_length`), together with all predecessor arcs on shortest paths
+fn bfs_distances(
+    ocel: &IndexLinkedOCEL,
+    options: &OCELPathOptions,
+    start: EventOrObjectIndex,
+    end: EventOrObjectIndex,
+) -> (HashMap<EventOrObjectIndex, usize>, Predecessors) {
+    let mut distances = HashMap::from([(start, 0)]);
+    let mut predecessors: Predecessors = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(index) = queue.pop_front() {
+        let distance = distances[&index];
+        // Paths only pass through the endpoints at their start or end
+        if distance >= options.max_length || (index == end && index != start) {
+            continue;
+        }
+        for (r, arc) in path_neighbors(ocel, options, index, [start, end]) {
+            match distances.get(&r) {
+                None => {
+                    distances.insert(r, distance + 1);
+                    predecessors.insert(r, vec![(index, arc)]);
+                    queue.push_back(r);
                 }
-            })
-            .collect();
-        let links = expanded_arcs
-            .iter()
-            .map(|(from, to, qualifier)| {
-                let from = ocel.ob_or_ev_by_index(*from).unwrap().cloned();
-                let to = ocel.ob_or_ev_by_index(*to).unwrap().cloned();
-
-                GraphLink {
-                    source: from.get_id().clone(),
-                    target: to.get_id().clone(),
-                    qualifier: qualifier.clone(),
+                Some(d) if *d == distance + 1 => {
+                    predecessors.entry(r).or_default().push((index, arc));
                 }
-            })
-            .collect();
-        Some(OCELGraph { nodes, links })
+                Some(_) => {}
+            }
+        }
+    }
+    (distances, predecessors)
+}
+
+/// All shortest paths from `start` to `end` (at most `max_paths`)
+fn shortest_paths(
+    ocel: &IndexLinkedOCEL,
+    options: &OCELPathOptions,
+    start: EventOrObjectIndex,
+    end: EventOrObjectIndex,
+    max_paths: usize,
+) -> Vec<IndexPath> {
+    let (distances, predecessors) = bfs_distances(ocel, options, start, end);
+    let mut paths = Vec::new();
+    if !distances.contains_key(&end) {
+        return paths;
+    }
+    // Walk back from the end along predecessor arcs
+    let mut stack: Vec<(EventOrObjectIndex, Vec<EventOrObjectIndex>, Vec<Arc>)> =
+        vec![(end, vec![end], Vec::new())];
+    while let Some((index, nodes, arcs)) = stack.pop() {
+        if paths.len() >= max_paths {
+            break;
+        }
+        if index == start {
+            paths.push((
+                nodes.into_iter().rev().collect(),
+                arcs.into_iter().rev().collect(),
+            ));
+            continue;
+        }
+        for (pred, arc) in predecessors.get(&index).into_iter().flatten() {
+            let mut nodes = nodes.clone();
+            let mut arcs = arcs.clone();
+            nodes.push(*pred);
+            arcs.push(arc.clone());
+            stack.push((*pred, nodes, arcs));
+        }
+    }
+    paths
+}
+
+/// All paths without repeated nodes from `start` to `end` with at most `max_length` links (at most `max_paths`)
+///
+/// Paths are enumerated by increasing length (iterative deepening), so that only the longest paths are left out
+/// if there are more than `max_paths` paths.
+fn all_simple_paths(
+    ocel: &IndexLinkedOCEL,
+    options: &OCELPathOptions,
+    start: EventOrObjectIndex,
+    end: EventOrObjectIndex,
+    max_paths: usize,
+) -> Vec<IndexPath> {
+    // Distances to the end are used to prune branches which cannot reach it in time
+    let (distances_to_end, _) = bfs_distances(ocel, options, end, start);
+    let mut paths = Vec::new();
+    let Some(min_length) = distances_to_end.get(&start) else {
+        return paths;
+    };
+    for length in *min_length..=options.max_length {
+        let mut nodes = vec![start];
+        let mut arcs = Vec::new();
+        let mut on_path = HashSet::from([start]);
+        extend_simple_paths(
+            ocel,
+            options,
+            [start, end],
+            &distances_to_end,
+            (length, max_paths),
+            &mut nodes,
+            &mut arcs,
+            &mut on_path,
+            &mut paths,
+        );
+        if paths.len() >= max_paths {
+            break;
+        }
+    }
+    paths
+}
+
+/// Extend the path (`nodes` and `arcs`) to all paths with exactly `length` links (until there are `max_paths` paths)
+#[allow(clippy::too_many_arguments)]
+fn extend_simple_paths(
+    ocel: &IndexLinkedOCEL,
+    options: &OCELPathOptions,
+    [start, end]: [EventOrObjectIndex; 2],
+    distances_to_end: &HashMap<EventOrObjectIndex, usize>,
+    (length, max_paths): (usize, usize),
+    nodes: &mut Vec<EventOrObjectIndex>,
+    arcs: &mut Vec<Arc>,
+    on_path: &mut HashSet<EventOrObjectIndex>,
+    paths: &mut Vec<IndexPath>,
+) {
+    let index = *nodes.last().unwrap();
+    if index == end {
+        if arcs.len() == length {
+            paths.push((nodes.clone(), arcs.clone()));
+        }
+        return;
+    }
+    for (r, arc) in path_neighbors(ocel, options, index, [start, end]) {
+        if paths.len() >= max_paths {
+            return;
+        }
+        let reachable = distances_to_end
+            .get(&r)
+            .is_some_and(|d| arcs.len() + 1 + d <= length);
+        if reachable && on_path.insert(r) {
+            nodes.push(r);
+            arcs.push(arc);
+            extend_simple_paths(
+                ocel,
+                options,
+                [start, end],
+                distances_to_end,
+                (length, max_paths),
+                nodes,
+                arcs,
+                on_path,
+                paths,
+            );
+            nodes.pop();
+            arcs.pop();
+            on_path.remove(&r);
+        }
+    }
+}
+
+///
+/// Find paths connecting two events/objects (see [`OCELPathOptions`])
+///
+/// Returns `None` if one of the events/objects does not exist
+///
+pub fn get_ocel_paths(ocel: &IndexLinkedOCEL, options: &OCELPathOptions) -> Option<OCELPaths> {
+    let start = get_index(ocel, &options.from, options.from_is_object)?;
+    let end = get_index(ocel, &options.to, options.to_is_object)?;
+    let max_paths = options.max_paths.unwrap_or(DEFAULT_MAX_PATHS);
+    let index_paths = if options.all_paths {
+        all_simple_paths(ocel, options, start, end, max_paths)
     } else {
-        None
+        shortest_paths(ocel, options, start, end, max_paths)
+    };
+
+    let mut graph_nodes: Vec<EventOrObjectIndex> = Vec::new();
+    let mut graph_arcs: Vec<&Arc> = Vec::new();
+    let mut seen_nodes: HashSet<EventOrObjectIndex> = HashSet::new();
+    let mut seen_arcs: HashSet<&Arc> = HashSet::new();
+    for (nodes, arcs) in &index_paths {
+        graph_nodes.extend(nodes.iter().filter(|n| seen_nodes.insert(**n)));
+        graph_arcs.extend(arcs.iter().filter(|a| seen_arcs.insert(*a)));
+    }
+    let graph = OCELGraph {
+        nodes: graph_nodes.iter().map(|i| to_graph_node(ocel, i)).collect(),
+        links: graph_arcs.iter().map(|a| to_graph_link(ocel, a)).collect(),
+    };
+    let paths = index_paths
+        .iter()
+        .map(|(nodes, arcs)| OCELGraph {
+            nodes: nodes.iter().map(|i| to_graph_node(ocel, i)).collect(),
+            links: arcs.iter().map(|a| to_graph_link(ocel, a)).collect(),
+        })
+        .collect();
+    Some(OCELPaths { paths, graph })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binding_box::test::example_linked_ocel;
+
+    fn path_options(
+        all_paths: bool,
+        max_length: usize,
+        max_paths: Option<usize>,
+    ) -> OCELPathOptions {
+        OCELPathOptions {
+            from: "o2".to_string(),
+            from_is_object: true,
+            to: "i2-0".to_string(),
+            to_is_object: true,
+            max_length,
+            all_paths,
+            qualifiers: None,
+            event_types: None,
+            object_types: None,
+            max_paths,
+        }
+    }
+
+    fn node_id(node: &GraphNode) -> &str {
+        match node {
+            GraphNode::Event(ev) => &ev.id,
+            GraphNode::Object(ob) => &ob.id,
+        }
+    }
+
+    fn path_lengths(paths: &OCELPaths) -> Vec<usize> {
+        paths.paths.iter().map(|p| p.links.len()).collect()
+    }
+
+    #[test]
+    fn shortest_paths_only_contain_direct_relationship() {
+        let ocel = example_linked_ocel();
+        let paths = get_ocel_paths(&ocel, &path_options(false, 4, None)).unwrap();
+        assert_eq!(path_lengths(&paths), vec![1]);
+        let path = &paths.paths[0];
+        assert_eq!(
+            path.nodes.iter().map(node_id).collect::<Vec<_>>(),
+            vec!["o2", "i2-0"]
+        );
+        assert_eq!(path.links[0].qualifier, "contains");
+    }
+
+    #[test]
+    fn all_paths_are_sorted_by_length() {
+        let ocel = example_linked_ocel();
+        // Length 1: contains; Length 2: via place-2 or pick-2-0;
+        // Length 3: via another item and place-2
+        let paths = get_ocel_paths(&ocel, &path_options(true, 3, None)).unwrap();
+        assert_eq!(path_lengths(&paths), vec![1, 2, 2, 3, 3]);
+        for path in &paths.paths {
+            let ids: HashSet<_> = path.nodes.iter().map(node_id).collect();
+            assert_eq!(ids.len(), path.nodes.len());
+        }
+    }
+
+    #[test]
+    fn truncated_all_paths_keep_the_shortest() {
+        let ocel = example_linked_ocel();
+        let paths = get_ocel_paths(&ocel, &path_options(true, 8, Some(3))).unwrap();
+        assert_eq!(path_lengths(&paths), vec![1, 2, 2]);
+        let paths = get_ocel_paths(&ocel, &path_options(true, 8, Some(2))).unwrap();
+        assert_eq!(path_lengths(&paths), vec![1, 2]);
     }
 }
@@ -0,0 +1,306 @@
+//! Type-level relationship schema of an OCEL
+//!
+//! Instead of single events and objects, the nodes of the schema graph are event and object types.
+//! Edges are the E2O qualifiers of event types (see [`get_qualifiers_for_event_types`])
+//! and the O2O qualifiers of object types (see [`IndexLinkedOCEL::object_rels_per_type`]),
+//! annotated with cardinality statistics over all events/objects of the source type.
+//! As for the qualifier statistics, every relationship counts (including duplicate relationships),
+//! so that the E2O cardinalities are consistent with the qualifier statistics
+//! (i.e., a qualifier is `multiple` exactly if one of its edges has a maximum above one).
+use std::{collections::HashMap, fmt::Write};
+
+use itertools::Itertools;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{
+    discovery::advanced::EventOrObjectType,
+    escape::dot_string,
+    ocel_qualifiers::qualifiers::get_qualifiers_for_event_types,
+    preprocessing::linked_ocel::{EventOrObjectIndex, IndexLinkedOCEL},
+};
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum SchemaRelationshipKind {
+    E2O,
+    O2O,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaNode {
+    pub name: String,
+    pub is_object_type: bool,
+    /// Number of events/objects of the type
+    pub count: usize,
+    /// Average number of E2O/O2O relationships an event/object of the type is involved in
+    pub avg_relationships: f32,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaEdge {
+    pub kind: SchemaRelationshipKind,
+    /// Event type (E2O) or object type (O2O)
+    pub source: String,
+    /// Object type
+    pub target: String,
+    pub qualifier: String,
+    /// Total number of relationships
+    pub count: usize,
+    /// Min. number of relationships per event/object of the source type
+    pub min: usize,
+    /// Avg. number of relationships per event/object of the source type
+    pub avg: f64,
+    /// Max. number of relationships per event/object of the source type
+    pub max: usize,
+    /// Fraction of events/objects of the source type with at least one such relationship
+    pub share_with_any: f64,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OCELSchemaGraph {
+    /// Event types (in the order of the OCEL), followed by object types
+    pub nodes: Vec<SchemaNode>,
+    /// Edges, sorted by kind, source, qualifier and target
+    pub edges: Vec<SchemaEdge>,
+}
+
+/// Accumulated counts of one edge (sum, min. of non-zero counts, max., number of instances with a non-zero count)
+type EdgeCounts = (usize, usize, usize, usize);
+
+/// Cardinality statistics of all (qualifier, object type) relationships of the given instances of a type
+fn relationship_counts(
+    ocel: &IndexLinkedOCEL,
+    instances: impl Iterator<Item = EventOrObjectIndex>,
+) -> HashMap<(&String, &String), EdgeCounts> {
+    let mut ret: HashMap<(&String, &String), EdgeCounts> = HashMap::new();
+    for index in instances {
+        let counts = ocel
+            .rels
+            .get(&index)
+            .into_iter()
+            .flatten()
+            .map(|(ob_index, qualifier)| (qualifier, &ocel.ocel.objects[ob_index.0].object_type))
+            .counts();
+        for (key, count) in counts {
+            let (sum, min, max, non_zero) = ret.entry(key).or_insert((0, usize::MAX, 0, 0));
+            *sum += count;
+            *min = (*min).min(count);
+            *max = (*max).max(count);
+            *non_zero += 1;
+        }
+    }
+    ret
+}
+
+fn to_schema_edge(
+    kind: SchemaRelationshipKind,
+    source: &str,
+    (qualifier, target): (&String, &String),
+    (sum, min, max, non_zero): EdgeCounts,
+    num_instances: usize,
+) -> SchemaEdge {
+    SchemaEdge {
+        kind,
+        source: source.to_string(),
+        target: target.clone(),
+        qualifier: qualifier.clone(),
+        count: sum,
+        // Instances without any such relationship count as zero
+        min: if non_zero < num_instances { 0 } else { min },
+        avg: sum as f64 / num_instances as f64,
+        max,
+        share_with_any: non_zero as f64 / num_instances as f64,
+    }
+}
+
+///
+/// Compute the type-level relationship schema graph of the OCEL (see [module-level documentation](self))
+///
+pub fn get_schema_graph(ocel: &IndexLinkedOCEL) -> OCELSchemaGraph {
+    let avg_relationships = |t: EventOrObjectType| {
+        ocel.avg_rels_of_type_per_type
+            .get(&t)
+            .copied()
+            .unwrap_or_default()
+    };
+    let event_type_nodes = ocel.ocel.event_types.iter().map(|et| SchemaNode {
+        name: et.name.clone(),
+        is_object_type: false,
+        count: ocel.events_of_type.get(&et.name).map_or(0, Vec::len),
+        avg_relationships: avg_relationships(EventOrObjectType::Event(et.name.clone())),
+    });
+    let object_type_nodes = ocel.ocel.object_types.iter().map(|ot| SchemaNode {
+        name: ot.name.clone(),
+        is_object_type: true,
+        count: ocel.objects_of_type.get(&ot.name).map_or(0, Vec::len),
+        avg_relationships: avg_relationships(EventOrObjectType::Object(ot.name.clone())),
+    });
+
+    let qualifiers = get_qualifiers_for_event_types(&ocel.ocel);
+    let e2o_edges = ocel.ocel.event_types.par_iter().flat_map(|et| {
+        let events = ocel
+            .events_of_type
+            .get(&et.name)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let counts =
+            relationship_counts(ocel, events.iter().map(|e| EventOrObjectIndex::Event(*e)));
+        qualifiers
+            .get(&et.name)
+            .into_iter()
+            .flat_map(|qs| qs.values())
+            .flat_map(|q| q.object_types.iter().map(move |ot| (&q.qualifier, ot)))
+            .filter_map(|(qualifier, target)| {
+                counts.get(&(qualifier, target)).map(|c| {
+                    to_schema_edge(
+                        SchemaRelationshipKind::E2O,
+                        &et.name,
+                        (qualifier, target),
+                        *c,
+                        events.len(),
+                    )
+                })
+            })
+            .collect_vec()
+    });
+    let o2o_edges = ocel.ocel.object_types.par_iter().flat_map(|ot| {
+        let objects = ocel
+            .objects_of_type
+            .get(&ot.name)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let counts =
+            relationship_counts(ocel, objects.iter().map(|o| EventOrObjectIndex::Object(*o)));
+        ocel.object_rels_per_type
+            .get(&ot.name)
+            .into_iter()
+            .flatten()
+            .filter_map(|(qualifier, target)| {
+                counts.get(&(qualifier, target)).map(|c| {
+                    to_schema_edge(
+                        SchemaRelationshipKind::O2O,
+                        &ot.name,
+                        (qualifier, target),
+                        *c,
+                        objects.len(),
+                    )
+                })
+            })
+            .collect_vec()
+    });
+    let mut edges: Vec<SchemaEdge> = e2o_edges.chain(o2o_edges).collect();
+    edges.sort_by(|a, b| {
+        (a.kind, &a.source, &a.qualifier, &a.target).cmp(&(
+            b.kind,
+            &b.source,
+            &b.qualifier,
+            &b.target,
+        ))
+    });
+    OCELSchemaGraph {
+        nodes: event_type_nodes.chain(object_type_nodes).collect(),
+        edges,
+    }
+}
+
+///
+/// Render the schema graph in the DOT format (Graphviz)
+///
+/// Event types are drawn as boxes and object types as ellipses; O2O edges are dashed.
+/// Edge labels show the qualifier, the min./avg./max. cardinality and the share of source instances with at least one relationship.
+/// Edges whose source or target type is not a node of the graph are skipped.
+///
+pub fn schema_graph_to_dot(graph: &OCELSchemaGraph) -> String {
+    let mut dot = String::from("digraph OCELSchema {\n  rankdir=LR;\n");
+    let node_id = |name: &String, is_object_type: bool| {
+        let prefix = if is_object_type { "o" } else { "e" };
+        let i = graph
+            .nodes
+            .iter()
+            .position(|n| n.is_object_type == is_object_type && &n.name == name)?;
+        Some(format!("{prefix}{i}"))
+    };
+    for node in &graph.nodes {
+        let shape = if node.is_object_type {
+            "ellipse"
+        } else {
+            "box"
+        };
+        let label = dot_string(&format!("{}\n{}", node.name, node.count));
+        let id = node_id(&node.name, node.is_object_type).unwrap();
+        writeln!(dot, "  {id} [shape={shape}, label={label}];").unwrap();
+    }
+    for edge in &graph.edges {
+        let is_o2o = edge.kind == SchemaRelationshipKind::O2O;
+        let (Some(source), Some(target)) =
+            (node_id(&edge.source, is_o2o), node_id(&edge.target, true))
+        else {
+            continue;
+        };
+        let label = dot_string(&format!(
+            "{}\n{}..{} (avg. {:.2}, {:.0}%)",
+            edge.qualifier,
+            edge.min,
+            edge.max,
+            edge.avg,
+            edge.share_with_any * 100.0
+        ));
+        let style = if is_o2o { ", style=dashed" } else { "" };
+        writeln!(dot, "  {source} -> {target} [label={label}{style}];").unwrap();
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binding_box::test::example_linked_ocel;
+
+    #[test]
+    fn e2o_edges_match_qualifier_statistics() {
+        let ocel = example_linked_ocel();
+        let qualifiers = get_qualifiers_for_event_types(&ocel.ocel);
+        let schema = get_schema_graph(&ocel);
+        let e2o_edges = schema
+            .edges
+            .iter()
+            .filter(|e| e.kind == SchemaRelationshipKind::E2O)
+            .collect_vec();
+        let expected_edges: usize = qualifiers
+            .values()
+            .flat_map(|qs| qs.values())
+            .map(|q| q.object_types.len())
+            .sum();
+        assert_eq!(e2o_edges.len(), expected_edges);
+        for edge in &e2o_edges {
+            let q = &qualifiers[&edge.source][&edge.qualifier];
+            assert!(q.object_types.contains(&edge.target));
+            let multiple = e2o_edges
+                .iter()
+                .any(|e| e.source == edge.source && e.qualifier == edge.qualifier && e.max > 1);
+            assert_eq!(q.multiple, multiple);
+        }
+        let place_items = schema
+            .edges
+            .iter()
+            .find(|e| e.source == "place order" && e.qualifier == "item")
+            .unwrap();
+        assert_eq!(
+            (place_items.min, place_items.max, place_items.count),
+            (1, 3, 15)
+        );
+    }
+}
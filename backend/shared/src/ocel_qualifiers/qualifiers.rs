@@ -16,6 +16,11 @@ pub type QualifierAndObjectType = (String, String);
 pub fn get_qualifiers_for_event_types(
     ocel: &OCEL,
 ) -> HashMap<String, HashMap<String, QualifiersForEventType>> {
+    let object_types: HashMap<&String, &String> = ocel
+        .objects
+        .iter()
+        .map(|o| (&o.id, &o.object_type))
+        .collect();
     let qualifiers_per_event_type: Vec<(String, HashMap<QualifierAndObjectType, Vec<i32>>)> = ocel
         .event_types
         .par_iter()
@@ -28,8 +33,8 @@ pub fn get_qualifiers_for_event_types(
                     .map(|ev| ev.relationships
                             .iter()
                             .filter_map(|r| {
-                                let obj = ocel.objects.iter().find(|o| o.id == r.object_id);
-                                obj.map(|obj| (r.qualifier.clone(), obj.object_type.clone()))
+                                let obj_type = object_types.get(&r.object_id);
+                                obj_type.map(|ot| (r.qualifier.clone(), (*ot).clone()))
                             })
                             .fold(HashMap::new(), |mut acc, c| {
                                 *acc.entry(c).or_insert(0) += 1;
@@ -56,7 +61,7 @@ pub fn get_qualifiers_for_event_types(
                             pre_val.object_types.push(obj_type.clone());
                         }
                         for c in counts {
-                            if *c > 0 {
+                            if *c > 1 {
                                 pre_val.multiple = true;
                             }
                             // pre_val.counts.push(*c);
@@ -0,0 +1,223 @@
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+};
+
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use once_cell::sync::OnceCell;
+use process_mining::ocel::ocel_struct::{OCELAttributeValue, OCELType};
+
+use crate::binding_box::structs::ValueFilter;
+
+/// Hashable attribute values, used as keys of the equality (hash) index
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub enum HashableAttributeValue {
+    String(String),
+    Boolean(bool),
+}
+
+/// Attribute value indices keyed by (event/object type, attribute name)
+///
+/// Each index is only built on its first access (see [`AttributeValueIndex::build`])
+pub type LazyAttributeIndices<I> = HashMap<(String, String), OnceCell<AttributeValueIndex<I>>>;
+
+/// Not yet built attribute value indices for all attributes declared on the given event or object types
+pub fn declared_attribute_indices<I>(types: &[OCELType]) -> LazyAttributeIndices<I> {
+    types
+        .iter()
+        .flat_map(|t| {
+            t.attributes
+                .iter()
+                .map(|a| ((t.name.clone(), a.name.clone()), OnceCell::new()))
+        })
+        .collect()
+}
+
+///
+/// Index over the values of one attribute for all events or objects of one type
+///
+/// String and boolean values are stored in a hash index (for equality lookups),
+/// integer, float and time values are stored in sorted indices (for range lookups).
+/// Lookups mirror the semantics of [`ValueFilter::check_value`]: Integer and float values are both matched by
+/// integer and float filters, and `NaN` values satisfy every numeric range.
+///
+#[derive(Debug, Clone)]
+pub struct AttributeValueIndex<I> {
+    pub by_value: HashMap<HashableAttributeValue, Vec<I>>,
+    pub integer: Vec<(i64, I)>,
+    /// Float values (without `NaN`)
+    pub float: Vec<(f64, I)>,
+    /// Instances with a `NaN` float value
+    pub nan: Vec<I>,
+    pub time: Vec<(DateTime<Utc>, I)>,
+}
+
+impl<I> Default for AttributeValueIndex<I> {
+    fn default() -> Self {
+        Self {
+            by_value: HashMap::default(),
+            integer: Vec::default(),
+            float: Vec::default(),
+            nan: Vec::default(),
+            time: Vec::default(),
+        }
+    }
+}
+
+/// Range of the sorted `values` which are neither below `min` nor above `max`
+///
+/// Incomparable values and bounds (i.e., `NaN` bounds) are considered to be in range, as in [`ValueFilter::check_value`]
+fn sorted_range<V: Copy, B: Copy, I>(
+    values: &[(V, I)],
+    min: Option<B>,
+    max: Option<B>,
+    cmp: impl Fn(V, B) -> Option<Ordering>,
+) -> &[(V, I)] {
+    let from = min.map_or(0, |min| {
+        values.partition_point(|(v, _)| cmp(*v, min) == Some(Ordering::Less))
+    });
+    let to = max.map_or(values.len(), |max| {
+        values.partition_point(|(v, _)| cmp(*v, max) != Some(Ordering::Greater))
+    });
+    if from < to {
+        &values[from..to]
+    } else {
+        &[]
+    }
+}
+
+impl<I: Copy + Eq + std::hash::Hash> AttributeValueIndex<I> {
+    /// Build an index from (instance, value) pairs
+    ///
+    /// Instances may occur multiple times (e.g., for object attributes changing over time)
+    pub fn build<'a, It: IntoIterator<Item = (I, &'a OCELAttributeValue)>>(values: It) -> Self {
+        let mut index = Self::default();
+        for (instance, value) in values {
+            match value {
+                OCELAttributeValue::String(s) => index
+                    .by_value
+                    .entry(HashableAttributeValue::String(s.clone()))
+                    .or_default()
+                    .push(instance),
+                OCELAttributeValue::Boolean(b) => index
+                    .by_value
+                    .entry(HashableAttributeValue::Boolean(*b))
+                    .or_default()
+                    .push(instance),
+                OCELAttributeValue::Integer(i) => index.integer.push((*i, instance)),
+                OCELAttributeValue::Float(f) if f.is_nan() => index.nan.push(instance),
+                OCELAttributeValue::Float(f) => index.float.push((*f, instance)),
+                OCELAttributeValue::Time(t) => index.time.push((t.to_utc(), instance)),
+                OCELAttributeValue::Null => {}
+            }
+        }
+        index.integer.sort_by_key(|(i, _)| *i);
+        index.float.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        index.time.sort_by_key(|(t, _)| *t);
+        index
+    }
+
+    /// Integer entries in the numeric range of the value filter (as compared by [`ValueFilter::check_value`])
+    fn integer_range(&self, value_filter: &ValueFilter) -> &[(i64, I)] {
+        match value_filter {
+            // Integers are compared as floats
+            ValueFilter::Float { min, max } => {
+                sorted_range(&self.integer, *min, *max, |v, b| (v as f64).partial_cmp(&b))
+            }
+            ValueFilter::Integer { min, max } => {
+                sorted_range(&self.integer, *min, *max, |v, b| v.partial_cmp(&b))
+            }
+            _ => &[],
+        }
+    }
+
+    /// Float entries in the numeric range of the value filter (as compared by [`ValueFilter::check_value`])
+    fn float_range(&self, value_filter: &ValueFilter) -> &[(f64, I)] {
+        match value_filter {
+            ValueFilter::Float { min, max } => {
+                sorted_range(&self.float, *min, *max, |v, b| v.partial_cmp(&b))
+            }
+            // Floats are compared to the integer bounds converted to floats
+            ValueFilter::Integer { min, max } => {
+                sorted_range(&self.float, *min, *max, |v, b| v.partial_cmp(&(b as f64)))
+            }
+            _ => &[],
+        }
+    }
+
+    fn matching_entries<'a>(
+        &'a self,
+        value_filter: &'a ValueFilter,
+    ) -> Box<dyn Iterator<Item = I> + 'a> {
+        match value_filter {
+            ValueFilter::Float { .. } | ValueFilter::Integer { .. } => Box::new(
+                self.integer_range(value_filter)
+                    .iter()
+                    .map(|(_, instance)| *instance)
+                    .chain(
+                        self.float_range(value_filter)
+                            .iter()
+                            .map(|(_, instance)| *instance),
+                    )
+                    .chain(self.nan.iter().copied()),
+            ),
+            ValueFilter::Boolean { is_true } => Box::new(
+                self.by_value
+                    .get(&HashableAttributeValue::Boolean(*is_true))
+                    .into_iter()
+                    .flatten()
+                    .copied(),
+            ),
+            ValueFilter::String { is_in } => Box::new(
+                is_in
+                    .iter()
+                    .flat_map(|s| {
+                        self.by_value
+                            .get(&HashableAttributeValue::String(s.clone()))
+                    })
+                    .flatten()
+                    .copied(),
+            ),
+            ValueFilter::Time { from, to } => Box::new(
+                sorted_range(&self.time, *from, *to, |v, b| v.partial_cmp(&b))
+                    .iter()
+                    .map(|(_, instance)| *instance),
+            ),
+        }
+    }
+
+    /// Get all (distinct) instances with at least one attribute value satisfying the value filter
+    pub fn lookup(&self, value_filter: &ValueFilter) -> Vec<I> {
+        let mut seen = HashSet::new();
+        self.matching_entries(value_filter)
+            .filter(|instance| seen.insert(*instance))
+            .collect_vec()
+    }
+
+    /// Upper bound on the number of instances returned by [`AttributeValueIndex::lookup`]
+    ///
+    /// Computed from the lengths of the matching sorted ranges and posting lists (without iterating or deduplicating
+    /// the instances), and thus suited for planning binding steps
+    pub fn estimate(&self, value_filter: &ValueFilter) -> usize {
+        match value_filter {
+            ValueFilter::Float { .. } | ValueFilter::Integer { .. } => {
+                self.integer_range(value_filter).len()
+                    + self.float_range(value_filter).len()
+                    + self.nan.len()
+            }
+            ValueFilter::Time { from, to } => {
+                sorted_range(&self.time, *from, *to, |v, b| v.partial_cmp(&b)).len()
+            }
+            ValueFilter::Boolean { is_true } => self
+                .by_value
+                .get(&HashableAttributeValue::Boolean(*is_true))
+                .map_or(0, Vec::len),
+            ValueFilter::String { is_in } => is_in
+                .iter()
+                .filter_map(|s| self.by_value.get(&HashableAttributeValue::String(s.clone())))
+                .map(Vec::len)
+                .sum(),
+        }
+    }
+}
@@ -0,0 +1,535 @@
+//! Persistent on-disk cache of [`IndexLinkedOCEL`]
+//!
+//! Linking large OCELs (see [`link_ocel_info`]) is expensive, so the fully linked structure can be written
+//! to a compact binary cache file next to the source OCEL.
+//! The cache is keyed by a content hash of the source file and only used if it is still valid.
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use chrono::{DateTime, FixedOffset};
+use once_cell::sync::OnceCell;
+use process_mining::{
+    ocel::ocel_struct::{
+        OCELAttributeValue, OCELEvent, OCELEventAttribute, OCELObject, OCELObjectAttribute,
+        OCELRelationship, OCELType,
+    },
+    OCEL,
+};
+use serde::{Deserialize, Serialize, Serializer};
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::{
+    discovery::advanced::EventOrObjectType, ocel_qualifiers::qualifiers::QualifierAndObjectType,
+};
+
+use super::{
+    attribute_index::declared_attribute_indices,
+    linked_ocel::{link_ocel_info, EventIndex, EventOrObjectIndex, IndexLinkedOCEL, ObjectIndex},
+    profile::OCELProfile,
+};
+
+const CACHE_MAGIC: &[u8; 8] = b"OCEDCACH";
+/// Bump when the layout of the cached data changes, so that old cache files are ignored
+const CACHE_VERSION: u32 = 2;
+pub const CACHE_FILE_EXTENSION: &str = "ocedeclare-cache";
+
+/// Path of the cache file belonging to the given source OCEL file
+pub fn cache_path_for<P: AsRef<Path>>(source_path: P) -> PathBuf {
+    let mut file_name = source_path
+        .as_ref()
+        .file_name()
+        .unwrap_or_default()
+        .to_os_string();
+    file_name.push(".");
+    file_name.push(CACHE_FILE_EXTENSION);
+    source_path.as_ref().with_file_name(file_name)
+}
+
+/// Content hash (XXH3) of a file, streamed in chunks
+pub fn hash_file<P: AsRef<Path>>(path: P) -> io::Result<u64> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut hasher = Xxh3::new();
+    let mut buf = vec![0; 1 << 20];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher.digest())
+}
+
+///
+/// Load the [`IndexLinkedOCEL`] for the OCEL file at `source_path`, using the cache file next to it if it is valid
+///
+/// Otherwise, the OCEL is imported using `import`, linked and the cache file is (re-)written.
+/// Failing to read or write the cache is not an error: it only results in (re-)linking the OCEL.
+///
+pub fn load_linked_ocel_with_cache<P: AsRef<Path>, E, F: FnOnce() -> Result<OCEL, E>>(
+    source_path: P,
+    import: F,
+) -> Result<IndexLinkedOCEL, E> {
+    let cache_path = cache_path_for(&source_path);
+    let source_hash = match hash_file(&source_path) {
+        Ok(hash) => Some(hash),
+        Err(e) => {
+            eprintln!("Could not hash OCEL file for cache: {e:?}");
+            None
+        }
+    };
+    if let Some(source_hash) = source_hash {
+        if let Some(linked_ocel) = read_linked_ocel_cache(&cache_path, source_hash) {
+            return Ok(linked_ocel);
+        }
+    }
+    let linked_ocel = link_ocel_info(import()?);
+    if let Some(source_hash) = source_hash {
+        if let Err(e) = write_linked_ocel_cache(&linked_ocel, source_hash, &cache_path) {
+            eprintln!("Could not write OCEL cache file: {e:?}");
+        }
+    }
+    Ok(linked_ocel)
+}
+
+/// Write the linked OCEL to a cache file, keyed by the content hash of its source
+///
+/// The profile of the OCEL (see [`IndexLinkedOCEL::profile`]) is computed if needed and cached as well.
+/// The cache is first written to a temporary file, which then replaces the cache file,
+/// so that readers never observe a partially written cache file.
+pub fn write_linked_ocel_cache<P: AsRef<Path>>(
+    linked_ocel: &IndexLinkedOCEL,
+    source_hash: u64,
+    cache_path: P,
+) -> io::Result<()> {
+    let now = Instant::now();
+    let cache_path = cache_path.as_ref();
+    let mut tmp_file_name = cache_path.file_name().unwrap_or_default().to_os_string();
+    tmp_file_name.push(".tmp");
+    let tmp_path = cache_path.with_file_name(tmp_file_name);
+    let write_tmp = || -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        writer.write_all(CACHE_MAGIC)?;
+        writer.write_all(&CACHE_VERSION.to_le_bytes())?;
+        writer.write_all(&source_hash.to_le_bytes())?;
+        bincode::serialize_into(&mut writer, &CachedLinkedOCEL::from(linked_ocel))
+            .map_err(io::Error::other)?;
+        writer.into_inner().map_err(|e| e.into_error())?.sync_all()
+    };
+    if let Err(e) = write_tmp().and_then(|_| fs::rename(&tmp_path, cache_path)) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+    println!("Writing OCEL cache took {:?}", now.elapsed());
+    Ok(())
+}
+
+/// Read a linked OCEL from a cache file
+///
+/// Returns `None` if the cache file does not exist, is invalid or does not match `expected_source_hash`
+pub fn read_linked_ocel_cache<P: AsRef<Path>>(
+    cache_path: P,
+    expected_source_hash: u64,
+) -> Option<IndexLinkedOCEL> {
+    let now = Instant::now();
+    let mut reader = BufReader::new(File::open(cache_path).ok()?);
+    let mut magic = [0; 8];
+    let mut version = [0; 4];
+    let mut source_hash = [0; 8];
+    reader.read_exact(&mut magic).ok()?;
+    reader.read_exact(&mut version).ok()?;
+    reader.read_exact(&mut source_hash).ok()?;
+    if &magic != CACHE_MAGIC
+        || u32::from_le_bytes(version) != CACHE_VERSION
+        || u64::from_le_bytes(source_hash) != expected_source_hash
+    {
+        return None;
+    }
+    match bincode::deserialize_from::<_, CachedLinkedOCEL<'static>>(reader) {
+        Ok(cached) => {
+            println!("Reading OCEL cache took {:?}", now.elapsed());
+            Some(cached.into())
+        }
+        Err(e) => {
+            eprintln!("Invalid OCEL cache file: {e:?}");
+            None
+        }
+    }
+}
+
+//
+// Cached representation
+//
+// OCEL attribute values are (de-)serialized untagged, which compact binary formats do not support.
+// Thus, events and objects are mirrored with an explicitly tagged attribute value type.
+//
+
+#[derive(Serialize, Deserialize)]
+enum CachedAttributeValue<'a> {
+    Time(DateTime<FixedOffset>),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    String(Cow<'a, str>),
+    Null,
+}
+
+impl<'a> From<&'a OCELAttributeValue> for CachedAttributeValue<'a> {
+    fn from(value: &'a OCELAttributeValue) -> Self {
+        match value {
+            OCELAttributeValue::Time(t) => CachedAttributeValue::Time(*t),
+            OCELAttributeValue::Integer(i) => CachedAttributeValue::Integer(*i),
+            OCELAttributeValue::Float(f) => CachedAttributeValue::Float(*f),
+            OCELAttributeValue::Boolean(b) => CachedAttributeValue::Boolean(*b),
+            OCELAttributeValue::String(s) => CachedAttributeValue::String(Cow::Borrowed(s)),
+            OCELAttributeValue::Null => CachedAttributeValue::Null,
+        }
+    }
+}
+
+impl From<CachedAttributeValue<'_>> for OCELAttributeValue {
+    fn from(value: CachedAttributeValue<'_>) -> Self {
+        match value {
+            CachedAttributeValue::Time(t) => OCELAttributeValue::Time(t),
+            CachedAttributeValue::Integer(i) => OCELAttributeValue::Integer(i),
+            CachedAttributeValue::Float(f) => OCELAttributeValue::Float(f),
+            CachedAttributeValue::Boolean(b) => OCELAttributeValue::Boolean(b),
+            CachedAttributeValue::String(s) => OCELAttributeValue::String(s.into_owned()),
+            CachedAttributeValue::Null => OCELAttributeValue::Null,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedEvent<'a> {
+    id: Cow<'a, str>,
+    event_type: Cow<'a, str>,
+    time: DateTime<FixedOffset>,
+    attributes: Vec<(Cow<'a, str>, CachedAttributeValue<'a>)>,
+    relationships: Cow<'a, [OCELRelationship]>,
+}
+
+impl<'a> From<&'a OCELEvent> for CachedEvent<'a> {
+    fn from(ev: &'a OCELEvent) -> Self {
+        CachedEvent {
+            id: Cow::Borrowed(&ev.id),
+            event_type: Cow::Borrowed(&ev.event_type),
+            time: ev.time,
+            attributes: ev
+                .attributes
+                .iter()
+                .map(|a| (Cow::Borrowed(a.name.as_str()), (&a.value).into()))
+                .collect(),
+            relationships: Cow::Borrowed(&ev.relationships),
+        }
+    }
+}
+
+impl From<CachedEvent<'_>> for OCELEvent {
+    fn from(ev: CachedEvent<'_>) -> Self {
+        OCELEvent {
+            id: ev.id.into_owned(),
+            event_type: ev.event_type.into_owned(),
+            time: ev.time,
+            attributes: ev
+                .attributes
+                .into_iter()
+                .map(|(name, value)| OCELEventAttribute {
+                    name: name.into_owned(),
+                    value: value.into(),
+                })
+                .collect(),
+            relationships: ev.relationships.into_owned(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedObject<'a> {
+    id: Cow<'a, str>,
+    object_type: Cow<'a, str>,
+    attributes: Vec<(
+        Cow<'a, str>,
+        CachedAttributeValue<'a>,
+        DateTime<FixedOffset>,
+    )>,
+    relationships: Cow<'a, [OCELRelationship]>,
+}
+
+impl<'a> From<&'a OCELObject> for CachedObject<'a> {
+    fn from(ob: &'a OCELObject) -> Self {
+        CachedObject {
+            id: Cow::Borrowed(&ob.id),
+            object_type: Cow::Borrowed(&ob.object_type),
+            attributes: ob
+                .attributes
+                .iter()
+                .map(|a| (Cow::Borrowed(a.name.as_str()), (&a.value).into(), a.time))
+                .collect(),
+            relationships: Cow::Borrowed(&ob.relationships),
+        }
+    }
+}
+
+impl From<CachedObject<'_>> for OCELObject {
+    fn from(ob: CachedObject<'_>) -> Self {
+        OCELObject {
+            id: ob.id.into_owned(),
+            object_type: ob.object_type.into_owned(),
+            attributes: ob
+                .attributes
+                .into_iter()
+                .map(|(name, value, time)| OCELObjectAttribute {
+                    name: name.into_owned(),
+                    value: value.into(),
+                    time,
+                })
+                .collect(),
+            relationships: ob.relationships.into_owned(),
+        }
+    }
+}
+
+/// Serialized as a sequence of [`CachedEvent`]s, without collecting them first
+struct SerializeEvents<'a>(&'a [OCELEvent]);
+
+impl Serialize for SerializeEvents<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(CachedEvent::from))
+    }
+}
+
+/// Serialized as a sequence of [`CachedObject`]s, without collecting them first
+struct SerializeObjects<'a>(&'a [OCELObject]);
+
+impl Serialize for SerializeObjects<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(CachedObject::from))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedLinkedOCEL<'a> {
+    event_types: Cow<'a, [OCELType]>,
+    object_types: Cow<'a, [OCELType]>,
+    #[serde(serialize_with = "serialize_events")]
+    events: CachedSeq<'a, OCELEvent, CachedEvent<'a>>,
+    #[serde(serialize_with = "serialize_objects")]
+    objects: CachedSeq<'a, OCELObject, CachedObject<'a>>,
+    object_events_map: Cow<'a, HashMap<ObjectIndex, Vec<EventIndex>>>,
+    object_rels_per_type: Cow<'a, HashMap<String, HashSet<QualifierAndObjectType>>>,
+    events_of_type: Cow<'a, HashMap<String, Vec<EventIndex>>>,
+    objects_of_type: Cow<'a, HashMap<String, Vec<ObjectIndex>>>,
+    event_index_map: Cow<'a, HashMap<String, EventIndex>>,
+    object_index_map: Cow<'a, HashMap<String, ObjectIndex>>,
+    rels: Cow<'a, HashMap<EventOrObjectIndex, Vec<(ObjectIndex, String)>>>,
+    symmetric_rels: Cow<'a, SymmetricRels>,
+    avg_rels_of_type_per_type: Cow<'a, HashMap<EventOrObjectType, f32>>,
+    profile: Cow<'a, OCELProfile>,
+}
+
+type SymmetricRels = HashMap<EventOrObjectIndex, HashSet<(EventOrObjectIndex, bool, String)>>;
+
+/// Either borrowed original items (when writing) or deserialized cached items (when reading)
+enum CachedSeq<'a, T, C> {
+    Borrowed(&'a [T]),
+    Owned(Vec<C>),
+}
+
+impl<'de, 'a, T, C: Deserialize<'de>> Deserialize<'de> for CachedSeq<'a, T, C> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::<C>::deserialize(deserializer).map(CachedSeq::Owned)
+    }
+}
+
+fn serialize_events<S: Serializer>(
+    events: &CachedSeq<'_, OCELEvent, CachedEvent<'_>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match events {
+        CachedSeq::Borrowed(evs) => SerializeEvents(evs).serialize(serializer),
+        CachedSeq::Owned(evs) => evs.serialize(serializer),
+    }
+}
+
+fn serialize_objects<S: Serializer>(
+    objects: &CachedSeq<'_, OCELObject, CachedObject<'_>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match objects {
+        CachedSeq::Borrowed(obs) => SerializeObjects(obs).serialize(serializer),
+        CachedSeq::Owned(obs) => obs.serialize(serializer),
+    }
+}
+
+impl<'a> From<&'a IndexLinkedOCEL> for CachedLinkedOCEL<'a> {
+    fn from(linked_ocel: &'a IndexLinkedOCEL) -> Self {
+        CachedLinkedOCEL {
+            event_types: Cow::Borrowed(&linked_ocel.ocel.event_types),
+            object_types: Cow::Borrowed(&linked_ocel.ocel.object_types),
+            events: CachedSeq::Borrowed(&linked_ocel.ocel.events),
+            objects: CachedSeq::Borrowed(&linked_ocel.ocel.objects),
+            object_events_map: Cow::Borrowed(&linked_ocel.object_events_map),
+            object_rels_per_type: Cow::Borrowed(&linked_ocel.object_rels_per_type),
+            events_of_type: Cow::Borrowed(&linked_ocel.events_of_type),
+            objects_of_type: Cow::Borrowed(&linked_ocel.objects_of_type),
+            event_index_map: Cow::Borrowed(&linked_ocel.event_index_map),
+            object_index_map: Cow::Borrowed(&linked_ocel.object_index_map),
+            rels: Cow::Borrowed(&linked_ocel.rels),
+            symmetric_rels: Cow::Borrowed(&linked_ocel.symmetric_rels),
+            avg_rels_of_type_per_type: Cow::Borrowed(&linked_ocel.avg_rels_of_type_per_type),
+            profile: Cow::Borrowed(linked_ocel.profile()),
+        }
+    }
+}
+
+impl From<CachedLinkedOCEL<'_>> for IndexLinkedOCEL {
+    fn from(cached: CachedLinkedOCEL<'_>) -> Self {
+        let events = match cached.events {
+            CachedSeq::Owned(evs) => evs.into_iter().map(OCELEvent::from).collect(),
+            CachedSeq::Borrowed(evs) => evs.to_vec(),
+        };
+        let objects = match cached.objects {
+            CachedSeq::Owned(obs) => obs.into_iter().map(OCELObject::from).collect(),
+            CachedSeq::Borrowed(obs) => obs.to_vec(),
+        };
+        let event_types = cached.event_types.into_owned();
+        let object_types = cached.object_types.into_owned();
+        IndexLinkedOCEL {
+            event_attribute_indices: declared_attribute_indices(&event_types),
+            object_attribute_indices: declared_attribute_indices(&object_types),
+            ocel: OCEL {
+                event_types,
+                object_types,
+                events,
+                objects,
+            },
+            object_events_map: cached.object_events_map.into_owned(),
+            object_rels_per_type: cached.object_rels_per_type.into_owned(),
+            events_of_type: cached.events_of_type.into_owned(),
+            objects_of_type: cached.objects_of_type.into_owned(),
+            event_index_map: cached.event_index_map.into_owned(),
+            object_index_map: cached.object_index_map.into_owned(),
+            rels: cached.rels.into_owned(),
+            symmetric_rels: cached.symmetric_rels.into_owned(),
+            avg_rels_of_type_per_type: cached.avg_rels_of_type_per_type.into_owned(),
+            profile: OnceCell::with_value(cached.profile.into_owned()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::binding_box::test::example_ocel;
+
+    use super::*;
+
+    fn temp_source_file(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("ocedeclare-cache-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, serde_json::to_vec(&example_ocel()).unwrap()).unwrap();
+        path
+    }
+
+    #[test]
+    fn cache_round_trip() {
+        let linked_ocel = link_ocel_info(example_ocel());
+        let source_path = temp_source_file("round-trip.json");
+        let cache_path = cache_path_for(&source_path);
+        write_linked_ocel_cache(&linked_ocel, 42, &cache_path).unwrap();
+        assert!(read_linked_ocel_cache(&cache_path, 43).is_none());
+        let cached = read_linked_ocel_cache(&cache_path, 42).unwrap();
+
+        // Attribute values (including NaN) are compared through their debug representation
+        assert_eq!(
+            format!("{:?}", cached.ocel.events),
+            format!("{:?}", linked_ocel.ocel.events)
+        );
+        assert_eq!(
+            format!("{:?}", cached.ocel.objects),
+            format!("{:?}", linked_ocel.ocel.objects)
+        );
+        assert_eq!(cached.event_index_map, linked_ocel.event_index_map);
+        assert_eq!(cached.object_index_map, linked_ocel.object_index_map);
+        assert_eq!(cached.events_of_type, linked_ocel.events_of_type);
+        assert_eq!(cached.objects_of_type, linked_ocel.objects_of_type);
+        assert_eq!(cached.object_events_map, linked_ocel.object_events_map);
+        assert_eq!(cached.rels, linked_ocel.rels);
+        assert_eq!(cached.symmetric_rels, linked_ocel.symmetric_rels);
+        assert_eq!(
+            cached.avg_rels_of_type_per_type,
+            linked_ocel.avg_rels_of_type_per_type
+        );
+        // The profile is restored from the cache instead of being recomputed
+        assert!(cached.profile.get().is_some());
+        assert_eq!(
+            format!("{:?}", cached.profile()),
+            format!("{:?}", linked_ocel.profile())
+        );
+        assert_eq!(
+            cached
+                .object_attribute_indices
+                .keys()
+                .sorted()
+                .collect_vec(),
+            linked_ocel
+                .object_attribute_indices
+                .keys()
+                .sorted()
+                .collect_vec()
+        );
+        fs::remove_file(&cache_path).unwrap();
+        fs::remove_file(&source_path).unwrap();
+    }
+
+    #[test]
+    fn load_uses_valid_cache() {
+        let source_path = temp_source_file("load.json");
+        let cache_path = cache_path_for(&source_path);
+        let _ = fs::remove_file(&cache_path);
+        let import = || -> Result<OCEL, ()> { Ok(example_ocel()) };
+        let linked_ocel = load_linked_ocel_with_cache(&source_path, import).unwrap();
+        assert!(cache_path.exists());
+        // No temporary file is left behind
+        assert_eq!(
+            fs::read_dir(source_path.parent().unwrap())
+                .unwrap()
+                .filter(|entry| entry
+                    .as_ref()
+                    .unwrap()
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with("load.json"))
+                .count(),
+            2
+        );
+
+        let from_cache = load_linked_ocel_with_cache(&source_path, || -> Result<OCEL, ()> {
+            panic!("OCEL should be loaded from the cache")
+        })
+        .unwrap();
+        assert_eq!(from_cache.event_index_map, linked_ocel.event_index_map);
+
+        // Changing the source invalidates the cache
+        fs::write(&source_path, b"{}").unwrap();
+        let mut imported = false;
+        load_linked_ocel_with_cache(&source_path, || -> Result<OCEL, ()> {
+            imported = true;
+            Ok(example_ocel())
+        })
+        .unwrap();
+        assert!(imported);
+        fs::remove_file(&cache_path).unwrap();
+        fs::remove_file(&source_path).unwrap();
+    }
+}
@@ -0,0 +1,214 @@
+//! Slicing and filtering of OCELs
+//!
+//! [`filter_ocel`] builds a new, consistent [`OCEL`] containing only the events and objects matching an [`OCELFilter`].
+//! Relationships to removed objects are dropped.
+use std::{
+    collections::{HashSet, VecDeque},
+    fs::File,
+    io::BufWriter,
+    path::Path,
+};
+
+use chrono::{DateTime, Utc};
+use process_mining::{
+    export_ocel_sqlite_to_path, ocel::xml_ocel_export::export_ocel_xml_path, OCEL,
+};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::binding_box::structs::ValueFilter;
+
+use super::linked_ocel::{EventIndex, EventOrObjectIndex, IndexLinkedOCEL, ObjectIndex};
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OCELFilter {
+    /// Only keep events at or after this time
+    pub from: Option<DateTime<Utc>>,
+    /// Only keep events at or before this time
+    pub to: Option<DateTime<Utc>>,
+    /// Only keep events of these types (all types if not set)
+    pub event_types: Option<Vec<String>>,
+    /// Only keep objects of these types (all types if not set)
+    pub object_types: Option<Vec<String>>,
+    #[serde(default)]
+    pub object_attribute_filters: Vec<ObjectAttributeFilter>,
+    /// Only keep events and objects connected to the given objects
+    pub connected_to: Option<ConnectedObjectsFilter>,
+}
+
+/// Only keep objects of the given type if they have a matching value for the attribute at some point in time
+///
+/// Objects of other types are not affected
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectAttributeFilter {
+    pub object_type: String,
+    pub attribute_name: String,
+    pub value_filter: ValueFilter,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectedObjectsFilter {
+    pub object_ids: Vec<String>,
+    /// Maximum number of E2O/O2O relationships between a kept event/object and one of the given objects
+    /// (unbounded if not set)
+    pub max_distance: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilterOCELRequest {
+    pub filter: OCELFilter,
+    /// If set, the filtered OCEL is exported to this path instead of replacing the current OCEL
+    /// (the format is chosen based on the file extension, see [`export_ocel_by_extension`])
+    pub fork_path: Option<String>,
+}
+
+///
+/// Build a new OCEL containing only the events and objects matching the filter
+///
+/// The time, type and attribute filters are applied first.
+/// The connected objects filter then only keeps events/objects reachable from the given objects
+/// via the remaining events and objects.
+///
+pub fn filter_ocel(linked_ocel: &IndexLinkedOCEL, filter: &OCELFilter) -> OCEL {
+    let ocel = &linked_ocel.ocel;
+    let type_allowed = |types: &Option<Vec<String>>, t: &String| {
+        types.as_ref().is_none_or(|types| types.contains(t))
+    };
+
+    let mut keep_ev: Vec<bool> = ocel
+        .events
+        .iter()
+        .map(|ev| {
+            let time = ev.time.with_timezone(&Utc);
+            type_allowed(&filter.event_types, &ev.event_type)
+                && filter.from.is_none_or(|from| time >= from)
+                && filter.to.is_none_or(|to| time <= to)
+        })
+        .collect();
+    let mut keep_ob: Vec<bool> = ocel
+        .objects
+        .iter()
+        .map(|ob| {
+            type_allowed(&filter.object_types, &ob.object_type)
+                && filter
+                    .object_attribute_filters
+                    .iter()
+                    .filter(|f| f.object_type == ob.object_type)
+                    .all(|f| {
+                        ob.attributes.iter().any(|a| {
+                            a.name == f.attribute_name && f.value_filter.check_value(&a.value)
+                        })
+                    })
+        })
+        .collect();
+
+    if let Some(connected) = &filter.connected_to {
+        let is_kept = |index: &EventOrObjectIndex, keep_ev: &[bool], keep_ob: &[bool]| match index {
+            EventOrObjectIndex::Event(EventIndex(i)) => keep_ev[*i],
+            EventOrObjectIndex::Object(ObjectIndex(i)) => keep_ob[*i],
+        };
+        let mut reached: HashSet<EventOrObjectIndex> = HashSet::new();
+        let mut queue: VecDeque<(EventOrObjectIndex, usize)> = connected
+            .object_ids
+            .iter()
+            .filter_map(|id| linked_ocel.index_of_ob(id))
+            .map(|ob_index| (EventOrObjectIndex::Object(*ob_index), 0))
+            .filter(|(index, _)| is_kept(index, &keep_ev, &keep_ob))
+            .collect();
+        reached.extend(queue.iter().map(|(index, _)| *index));
+        while let Some((index, distance)) = queue.pop_front() {
+            if connected.max_distance.is_some_and(|max| distance >= max) {
+                continue;
+            }
+            for (other, _reversed, _qualifier) in
+                linked_ocel.symmetric_rels.get(&index).into_iter().flatten()
+            {
+                if is_kept(other, &keep_ev, &keep_ob) && reached.insert(*other) {
+                    queue.push_back((*other, distance + 1));
+                }
+            }
+        }
+        keep_ev = (0..keep_ev.len())
+            .map(|i| reached.contains(&EventOrObjectIndex::Event(EventIndex(i))))
+            .collect();
+        keep_ob = (0..keep_ob.len())
+            .map(|i| reached.contains(&EventOrObjectIndex::Object(ObjectIndex(i))))
+            .collect();
+    }
+
+    let kept_object_ids: HashSet<&String> = ocel
+        .objects
+        .iter()
+        .zip(&keep_ob)
+        .filter(|(_, keep)| **keep)
+        .map(|(ob, _)| &ob.id)
+        .collect();
+    OCEL {
+        event_types: ocel
+            .event_types
+            .iter()
+            .filter(|t| type_allowed(&filter.event_types, &t.name))
+            .cloned()
+            .collect(),
+        object_types: ocel
+            .object_types
+            .iter()
+            .filter(|t| type_allowed(&filter.object_types, &t.name))
+            .cloned()
+            .collect(),
+        events: ocel
+            .events
+            .iter()
+            .zip(&keep_ev)
+            .filter(|(_, keep)| **keep)
+            .map(|(ev, _)| {
+                let mut ev = ev.clone();
+                ev.relationships
+                    .retain(|rel| kept_object_ids.contains(&rel.object_id));
+                ev
+            })
+            .collect(),
+        objects: ocel
+            .objects
+            .iter()
+            .zip(&keep_ob)
+            .filter(|(_, keep)| **keep)
+            .map(|(ob, _)| {
+                let mut ob = ob.clone();
+                ob.relationships
+                    .retain(|rel| kept_object_ids.contains(&rel.object_id));
+                ob
+            })
+            .collect(),
+    }
+}
+
+/// Filter the OCEL (see [`filter_ocel`]) and link the result
+pub fn filter_linked_ocel(linked_ocel: &IndexLinkedOCEL, filter: &OCELFilter) -> IndexLinkedOCEL {
+    IndexLinkedOCEL::new(filter_ocel(linked_ocel, filter))
+}
+
+///
+/// Export an OCEL as XML (`.xml`), SQLite (`.sqlite`) or otherwise JSON
+///
+pub fn export_ocel_by_extension<P: AsRef<Path>>(ocel: &OCEL, path: P) -> std::io::Result<()> {
+    let path = path.as_ref();
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("xml") => export_ocel_xml_path(ocel, path).map_err(std::io::Error::other),
+        Some("sqlite") => export_ocel_sqlite_to_path(path, ocel).map_err(std::io::Error::other),
+        _ => {
+            let writer = BufWriter::new(File::create(path)?);
+            serde_json::to_writer(writer, ocel).map_err(std::io::Error::other)
+        }
+    }
+}
@@ -0,0 +1,378 @@
+//! Flattening of OCELs into classical (case-centric) event logs
+//!
+//! Each case corresponds to one object of a chosen object type.
+//! The events of a case are either the events directly related to this object ([`FlatteningCaseNotion::ObjectType`]),
+//! or all events of the leading-type process execution of this object ([`FlatteningCaseNotion::LeadingType`]).
+//!
+//! OCEL event attributes whose names clash with the standard XES keys (`concept:name`, `time:timestamp`, `ocel:eid`)
+//! are exported with the prefix `ocel:attr:` (e.g., `ocel:attr:concept:name`).
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use itertools::Itertools;
+use process_mining::{
+    event_log::{
+        Attribute, AttributeValue, Attributes, Event, EventLog, Trace, XESEditableAttribute,
+    },
+    export_xes_event_log_to_file_path,
+    ocel::ocel_struct::OCELAttributeValue,
+};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::binding_box::{structs::ObjectVariable, EvaluateBoxTreeResult};
+
+use super::{linked_ocel::IndexLinkedOCEL, process_executions::leading_type_execution};
+
+const CASE_ID_KEY: &str = "concept:name";
+const ACTIVITY_KEY: &str = "concept:name";
+const TIMESTAMP_KEY: &str = "time:timestamp";
+const EVENT_ID_KEY: &str = "ocel:eid";
+const CASE_PREFIX: &str = "case:";
+const DEFAULT_OUTCOME_ATTRIBUTE: &str = "constraint:outcome";
+/// Prefix of OCEL attributes which would otherwise overwrite one of the keys set by the flattening
+const CLASHING_ATTRIBUTE_PREFIX: &str = "ocel:attr:";
+
+/// Key of an OCEL event attribute in the flattened log
+fn event_attribute_key(name: &str) -> String {
+    if [ACTIVITY_KEY, TIMESTAMP_KEY, EVENT_ID_KEY].contains(&name)
+        || name.starts_with(CLASHING_ATTRIBUTE_PREFIX)
+    {
+        format!("{CLASHING_ATTRIBUTE_PREFIX}{name}")
+    } else {
+        name.to_string()
+    }
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum FlatteningCaseNotion {
+    /// One case per object of this type, containing all events related to the object
+    ObjectType { object_type: String },
+    /// One case per object of this (leading) type, containing all events of its process execution
+    ///
+    /// See [`ProcessExecutionOptions::LeadingType`](super::process_executions::ProcessExecutionOptions::LeadingType)
+    LeadingType { object_type: String },
+}
+
+/// Attach the outcome of an evaluated constraint to the cases as a case attribute
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaseConstraintOutcome {
+    pub evaluation: EvaluateBoxTreeResult,
+    /// Index of the node whose situations are considered (defaults to the root node)
+    pub node_index: Option<usize>,
+    /// Variable of the node binding the case object
+    pub case_variable: ObjectVariable,
+    /// Name of the case attribute (defaults to `constraint:outcome`)
+    pub attribute_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlattenOCELRequest {
+    pub case_notion: FlatteningCaseNotion,
+    /// Path of the exported log (see [`export_flat_log_by_extension`] for supported formats)
+    pub export_path: String,
+    pub constraint_outcome: Option<CaseConstraintOutcome>,
+}
+
+///
+/// Flatten the OCEL into an [`EventLog`] using the given case notion
+///
+/// If a constraint outcome is provided, cases with at least one violated situation get the value `violated`,
+/// cases with only satisfied situations get the value `satisfied`, and cases without situations are not annotated.
+///
+pub fn flatten_ocel(
+    ocel: &IndexLinkedOCEL,
+    case_notion: &FlatteningCaseNotion,
+    constraint_outcome: Option<&CaseConstraintOutcome>,
+) -> EventLog {
+    let object_type = match case_notion {
+        FlatteningCaseNotion::ObjectType { object_type }
+        | FlatteningCaseNotion::LeadingType { object_type } => object_type,
+    };
+    let outcomes = constraint_outcome.map(case_outcomes).unwrap_or_default();
+    let outcome_attribute = constraint_outcome
+        .and_then(|o| o.attribute_name.clone())
+        .unwrap_or(DEFAULT_OUTCOME_ATTRIBUTE.to_string());
+    let traces = ocel
+        .objects_of_type
+        .get(object_type)
+        .into_iter()
+        .flatten()
+        .map(|ob_index| {
+            let ob = &ocel.ocel.objects[ob_index.0];
+            let event_indices = match case_notion {
+                FlatteningCaseNotion::ObjectType { .. } => ocel
+                    .object_events_map
+                    .get(ob_index)
+                    .cloned()
+                    .unwrap_or_default(),
+                FlatteningCaseNotion::LeadingType { .. } => {
+                    leading_type_execution(ocel, *ob_index, None).events
+                }
+            };
+            let mut attributes = Attributes::new();
+            attributes.add_to_attributes(
+                CASE_ID_KEY.to_string(),
+                AttributeValue::String(ob.id.clone()),
+            );
+            if let Some(outcome) = outcomes.get(&ob.id) {
+                attributes.add_to_attributes(
+                    outcome_attribute.clone(),
+                    AttributeValue::String(outcome.to_string()),
+                );
+            }
+            Trace {
+                attributes,
+                events: event_indices
+                    .into_iter()
+                    .unique()
+                    .map(|ev_index| &ocel.ocel.events[ev_index.0])
+                    .sorted_by_key(|ev| ev.time)
+                    .map(|ev| {
+                        let mut event = Event::new(ev.event_type.clone());
+                        event.attributes.add_to_attributes(
+                            TIMESTAMP_KEY.to_string(),
+                            AttributeValue::Date(ev.time),
+                        );
+                        event.attributes.add_to_attributes(
+                            EVENT_ID_KEY.to_string(),
+                            AttributeValue::String(ev.id.clone()),
+                        );
+                        for attr in &ev.attributes {
+                            event.attributes.add_to_attributes(
+                                event_attribute_key(&attr.name),
+                                ocel_to_xes_value(&attr.value),
+                            );
+                        }
+                        event
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+    EventLog {
+        attributes: Attributes::new(),
+        traces,
+        extensions: None,
+        classifiers: None,
+        global_trace_attrs: None,
+        global_event_attrs: None,
+    }
+}
+
+/// Outcome (`violated` or `satisfied`) per case object ID
+fn case_outcomes(outcome: &CaseConstraintOutcome) -> HashMap<String, &'static str> {
+    let mut ret: HashMap<String, &'static str> = HashMap::new();
+    let node_result = outcome
+        .evaluation
+        .evaluation_results
+        .get(outcome.node_index.unwrap_or(0));
+    for (binding, viol) in node_result.into_iter().flat_map(|r| &r.situations) {
+        let Some(ob_id) = binding
+            .get_ob_index(&outcome.case_variable)
+            .and_then(|ob_index| outcome.evaluation.object_ids.get(ob_index.0))
+        else {
+            continue;
+        };
+        let entry = ret.entry(ob_id.clone()).or_insert("satisfied");
+        if viol.is_some() {
+            *entry = "violated";
+        }
+    }
+    ret
+}
+
+fn ocel_to_xes_value(value: &OCELAttributeValue) -> AttributeValue {
+    match value {
+        OCELAttributeValue::Time(dt) => AttributeValue::Date(*dt),
+        OCELAttributeValue::Integer(i) => AttributeValue::Int(*i),
+        OCELAttributeValue::Float(f) => AttributeValue::Float(*f),
+        OCELAttributeValue::Boolean(b) => AttributeValue::Boolean(*b),
+        OCELAttributeValue::String(s) => AttributeValue::String(s.clone()),
+        OCELAttributeValue::Null => AttributeValue::None(),
+    }
+}
+
+///
+/// Export a flattened log as CSV (`.csv`) or otherwise XES (gz-compressed for `.gz`)
+///
+pub fn export_flat_log_by_extension<P: AsRef<Path>>(
+    log: &EventLog,
+    path: P,
+) -> std::io::Result<()> {
+    let path = path.as_ref();
+    if path.extension().is_some_and(|ext| ext == "csv") {
+        export_flat_log_csv(log, BufWriter::new(File::create(path)?))
+    } else {
+        export_xes_event_log_to_file_path(log, &path.to_string_lossy())
+            .map_err(std::io::Error::other)
+    }
+}
+
+///
+/// Export a flattened log as CSV, with one row per event
+///
+/// Case attributes are prefixed with `case:` (e.g., `case:concept:name`)
+///
+pub fn export_flat_log_csv<W: Write>(log: &EventLog, mut writer: W) -> std::io::Result<()> {
+    let trace_keys = attribute_keys(log.traces.iter().map(|t| &t.attributes));
+    let event_keys = attribute_keys(
+        log.traces
+            .iter()
+            .flat_map(|t| &t.events)
+            .map(|e| &e.attributes),
+    );
+    let header = trace_keys
+        .iter()
+        .map(|k| format!("{CASE_PREFIX}{k}"))
+        .chain(event_keys.iter().map(|k| k.to_string()));
+    writeln!(writer, "{}", header.map(|h| csv_escape(&h)).join(","))?;
+    for trace in &log.traces {
+        let case_values = trace_keys
+            .iter()
+            .map(|k| csv_value(&trace.attributes, k))
+            .collect_vec();
+        for event in &trace.events {
+            let row = case_values
+                .iter()
+                .cloned()
+                .chain(event_keys.iter().map(|k| csv_value(&event.attributes, k)))
+                .join(",");
+            writeln!(writer, "{row}")?;
+        }
+    }
+    writer.flush()
+}
+
+/// All attribute keys (in order of first occurrence, starting with `concept:name`)
+fn attribute_keys<'a>(attributes: impl Iterator<Item = &'a Attributes>) -> Vec<&'a str> {
+    let mut keys = vec![ACTIVITY_KEY];
+    for attrs in attributes {
+        for a in attrs {
+            if !keys.contains(&a.key.as_str()) {
+                keys.push(&a.key);
+            }
+        }
+    }
+    keys
+}
+
+fn csv_value(attributes: &Attributes, key: &str) -> String {
+    attributes
+        .iter()
+        .find(|a: &&Attribute| a.key == key)
+        .map(|a| match &a.value {
+            AttributeValue::String(s) => csv_escape(s),
+            AttributeValue::Date(dt) => dt.to_rfc3339(),
+            AttributeValue::Int(i) => i.to_string(),
+            AttributeValue::Float(f) => f.to_string(),
+            AttributeValue::Boolean(b) => b.to_string(),
+            AttributeValue::ID(id) => id.to_string(),
+            AttributeValue::List(_) | AttributeValue::Container(_) | AttributeValue::None() => {
+                String::new()
+            }
+        })
+        .unwrap_or_default()
+}
+
+fn csv_escape(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use process_mining::ocel::ocel_struct::OCELEventAttribute;
+
+    use crate::{binding_box::test::example_ocel, preprocessing::linked_ocel::link_ocel_info};
+
+    use super::*;
+
+    #[test]
+    fn clashing_event_attributes_are_prefixed() {
+        let mut ocel = example_ocel();
+        for (name, value) in [
+            (ACTIVITY_KEY, "attribute activity"),
+            (TIMESTAMP_KEY, "attribute timestamp"),
+            (EVENT_ID_KEY, "attribute id"),
+            ("ocel:attr:concept:name", "prefixed attribute"),
+        ] {
+            ocel.events[0].attributes.push(OCELEventAttribute {
+                name: name.to_string(),
+                value: OCELAttributeValue::String(value.to_string()),
+            });
+        }
+        let first_event_id = ocel.events[0].id.clone();
+        let ocel = link_ocel_info(ocel);
+        let log = flatten_ocel(
+            &ocel,
+            &FlatteningCaseNotion::ObjectType {
+                object_type: "orders".to_string(),
+            },
+            None,
+        );
+        let event = log
+            .traces
+            .iter()
+            .flat_map(|t| &t.events)
+            .find(|e| {
+                e.attributes.get_by_key(EVENT_ID_KEY).map(|a| &a.value)
+                    == Some(&AttributeValue::String(first_event_id.clone()))
+            })
+            .unwrap();
+        let keys = event
+            .attributes
+            .iter()
+            .map(|a| a.key.as_str())
+            .collect_vec();
+        assert!(keys.iter().all_unique(), "{keys:?}");
+        let value = |key: &str| event.attributes.get_by_key(key).unwrap().value.clone();
+        assert_eq!(
+            value(ACTIVITY_KEY),
+            AttributeValue::String(ocel.ocel.events[0].event_type.clone())
+        );
+        assert_eq!(
+            value(TIMESTAMP_KEY),
+            AttributeValue::Date(ocel.ocel.events[0].time)
+        );
+        assert_eq!(
+            value("ocel:attr:concept:name"),
+            AttributeValue::String("attribute activity".to_string())
+        );
+        assert_eq!(
+            value("ocel:attr:time:timestamp"),
+            AttributeValue::String("attribute timestamp".to_string())
+        );
+        assert_eq!(
+            value("ocel:attr:ocel:eid"),
+            AttributeValue::String("attribute id".to_string())
+        );
+        assert_eq!(
+            value("ocel:attr:ocel:attr:concept:name"),
+            AttributeValue::String("prefixed attribute".to_string())
+        );
+        assert_eq!(value("channel"), AttributeValue::String("web".to_string()));
+
+        let mut csv = Vec::new();
+        export_flat_log_csv(&log, &mut csv).unwrap();
+        let header = String::from_utf8(csv)
+            .unwrap()
+            .lines()
+            .next()
+            .unwrap()
+            .to_string();
+        assert_eq!(header.split(',').filter(|k| *k == ACTIVITY_KEY).count(), 1);
+    }
+}
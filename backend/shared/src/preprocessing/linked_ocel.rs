@@ -5,6 +5,7 @@ use std::{
 };
 
 use itertools::Itertools;
+use once_cell::sync::OnceCell;
 use process_mining::{
     event_log::ocel::ocel_struct::{OCELEvent, OCELObject, OCELRelationship},
     OCEL,
@@ -16,6 +17,11 @@ use crate::{
     discovery::advanced::EventOrObjectType, ocel_qualifiers::qualifiers::QualifierAndObjectType,
 };
 
+use super::{
+    attribute_index::{declared_attribute_indices, AttributeValueIndex, LazyAttributeIndices},
+    profile::{profile_ocel, OCELProfile},
+};
+
 pub fn get_object_events_map(
     ocel: &OCEL,
     object_map: &HashMap<String, ObjectIndex>,
@@ -203,12 +209,84 @@ pub struct IndexLinkedOCEL {
     pub symmetric_rels: HashMap<EventOrObjectIndex, HashSet<(EventOrObjectIndex, bool, String)>>,
 
     pub avg_rels_of_type_per_type: HashMap<EventOrObjectType, f32>,
+
+    // Lazily built attribute value indices, keyed by (event/object type, attribute name)
+    // See [`IndexLinkedOCEL::event_attribute_index`] and [`IndexLinkedOCEL::object_attribute_index`]
+    pub event_attribute_indices: LazyAttributeIndices<EventIndex>,
+    pub object_attribute_indices: LazyAttributeIndices<ObjectIndex>,
+
+    // Lazily computed statistics of the OCEL, see [`IndexLinkedOCEL::profile`]
+    pub profile: OnceCell<OCELProfile>,
 }
 
 impl IndexLinkedOCEL {
     pub fn new(ocel: OCEL) -> Self {
         link_ocel_info(ocel)
     }
+
+    /// Attribute value index for one event type and attribute (built on first access)
+    ///
+    /// Returns `None` if the attribute is not declared for the event type
+    pub fn event_attribute_index(
+        &self,
+        event_type: &str,
+        attr_name: &str,
+    ) -> Option<&AttributeValueIndex<EventIndex>> {
+        let cell = self
+            .event_attribute_indices
+            .get(&(event_type.to_string(), attr_name.to_string()))?;
+        Some(cell.get_or_init(|| {
+            AttributeValueIndex::build(
+                self.events_of_type
+                    .get(event_type)
+                    .into_iter()
+                    .flatten()
+                    .flat_map(|e_index| {
+                        // Only the first value of an attribute is considered for events (as in the value filter)
+                        self.ocel.events[e_index.0]
+                            .attributes
+                            .iter()
+                            .find(|a| a.name == attr_name)
+                            .map(|a| (*e_index, &a.value))
+                    }),
+            )
+        }))
+    }
+
+    /// Attribute value index for one object type and attribute (built on first access)
+    ///
+    /// All values an object attribute takes over time are indexed.
+    /// Returns `None` if the attribute is not declared for the object type
+    pub fn object_attribute_index(
+        &self,
+        object_type: &str,
+        attr_name: &str,
+    ) -> Option<&AttributeValueIndex<ObjectIndex>> {
+        let cell = self
+            .object_attribute_indices
+            .get(&(object_type.to_string(), attr_name.to_string()))?;
+        Some(cell.get_or_init(|| {
+            AttributeValueIndex::build(
+                self.objects_of_type
+                    .get(object_type)
+                    .into_iter()
+                    .flatten()
+                    .flat_map(|o_index| {
+                        self.ocel.objects[o_index.0]
+                            .attributes
+                            .iter()
+                            .filter(|a| a.name == attr_name)
+                            .map(|a| (*o_index, &a.value))
+                    }),
+            )
+        }))
+    }
+
+    /// Statistics and schema profile of the OCEL (computed on first access and cached afterwards)
+    pub fn profile(&self) -> &OCELProfile {
+        self.profile.get_or_init(|| profile_ocel(self))
+    }
+
     pub fn ev_by_index<'a>(&'a self, index: &EventIndex) -> Option<&'a OCELEvent> {
         self.ocel.events.get(index.0)
     }
@@ -423,6 +501,8 @@ pub fn link_ocel_info(ocel: OCEL) -> IndexLinkedOCEL {
         })
         .collect();
     println!("Linking OCEL took {:?}", now.elapsed());
+    let event_attribute_indices = declared_attribute_indices(&ocel.event_types);
+    let object_attribute_indices = declared_attribute_indices(&ocel.object_types);
     IndexLinkedOCEL {
         events_of_type,
         objects_of_type,
@@ -434,5 +514,8 @@ pub fn link_ocel_info(ocel: OCEL) -> IndexLinkedOCEL {
         rels,
         symmetric_rels,
         avg_rels_of_type_per_type,
+        event_attribute_indices,
+        object_attribute_indices,
+        profile: OnceCell::new(),
     }
 }
@@ -0,0 +1,350 @@
+//! Extraction of object-centric process executions (i.e., the "cases" of an OCEL)
+//!
+//! Process executions are either connected components of the object graph ([`ProcessExecutionOptions::ConnectedComponents`])
+//! or neighborhoods of the objects of a leading object type ([`ProcessExecutionOptions::LeadingType`]).
+//! Two objects are neighbors in the object graph if they share an event or are related through an O2O relationship.
+use std::{
+    borrow::Borrow,
+    collections::{HashMap, HashSet, VecDeque},
+};
+
+use itertools::Itertools;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::binding_box::{structs::EvaluationResults, Binding, BindingBoxTree};
+
+use super::linked_ocel::{EventIndex, EventOrObjectIndex, IndexLinkedOCEL, ObjectIndex};
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ProcessExecutionOptions {
+    /// Connected components of the object graph, only traversing objects of the given types
+    /// (all object types if not set)
+    ConnectedComponents { object_types: Option<Vec<String>> },
+    /// One process execution per object of the leading type
+    ///
+    /// Includes all objects reachable from the leading object in at most `max_hops` steps (unbounded if not set),
+    /// where for each object type only the objects with the smallest distance to the leading object are included.
+    LeadingType {
+        object_type: String,
+        max_hops: Option<usize>,
+    },
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessExecution {
+    /// The leading object (only set for leading type process executions)
+    pub leading_object: Option<ObjectIndex>,
+    /// Objects of the process execution (sorted by index)
+    pub objects: Vec<ObjectIndex>,
+    /// All events related to at least one of the objects (sorted by index)
+    pub events: Vec<EventIndex>,
+}
+
+impl ProcessExecution {
+    fn from_objects(
+        ocel: &IndexLinkedOCEL,
+        leading_object: Option<ObjectIndex>,
+        objects: Vec<ObjectIndex>,
+    ) -> Self {
+        let events = objects
+            .iter()
+            .flat_map(|ob_index| ocel.object_events_map.get(ob_index).into_iter().flatten())
+            .map(|ev_index| ev_index.0)
+            .sorted_unstable()
+            .dedup()
+            .map(EventIndex)
+            .collect();
+        ProcessExecution {
+            leading_object,
+            objects: objects
+                .into_iter()
+                .map(|ob_index| ob_index.0)
+                .sorted_unstable()
+                .map(ObjectIndex)
+                .collect(),
+            events,
+        }
+    }
+
+    pub fn contains_event(&self, ev_index: &EventIndex) -> bool {
+        self.events
+            .binary_search_by_key(&ev_index.0, |e| e.0)
+            .is_ok()
+    }
+
+    pub fn contains_object(&self, ob_index: &ObjectIndex) -> bool {
+        self.objects
+            .binary_search_by_key(&ob_index.0, |o| o.0)
+            .is_ok()
+    }
+
+    /// Check if all events and objects bound in the binding are part of this process execution
+    pub fn contains_binding(&self, binding: &Binding) -> bool {
+        binding.event_map.values().all(|ev| self.contains_event(ev))
+            && binding
+                .object_map
+                .values()
+                .all(|ob| self.contains_object(ob))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckWithBoxTreePerExecutionRequest {
+    pub tree: BindingBoxTree,
+    pub executions: ProcessExecutionOptions,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionEvaluationResult {
+    pub execution: ProcessExecution,
+    /// Number of root situations in the process execution
+    pub situation_count: usize,
+    pub situation_violated_count: usize,
+}
+
+///
+/// Extract the process executions of the OCEL
+///
+pub fn get_process_executions(
+    ocel: &IndexLinkedOCEL,
+    options: &ProcessExecutionOptions,
+) -> Vec<ProcessExecution> {
+    match options {
+        ProcessExecutionOptions::ConnectedComponents { object_types } => {
+            connected_component_executions(ocel, object_types.as_deref())
+        }
+        ProcessExecutionOptions::LeadingType {
+            object_type,
+            max_hops,
+        } => leading_type_executions(ocel, object_type, *max_hops),
+    }
+}
+
+///
+/// Connected components of the object graph, only traversing objects of the given types (or all objects)
+///
+pub fn connected_component_executions(
+    ocel: &IndexLinkedOCEL,
+    object_types: Option<&[String]>,
+) -> Vec<ProcessExecution> {
+    let allowed = |ob_index: &ObjectIndex| {
+        object_types.is_none_or(|types| types.contains(&ocel.ocel.objects[ob_index.0].object_type))
+    };
+    let mut visited: HashSet<ObjectIndex> = HashSet::new();
+    let mut components = Vec::new();
+    for ob_index in (0..ocel.ocel.objects.len()).map(ObjectIndex) {
+        if !allowed(&ob_index) || !visited.insert(ob_index) {
+            continue;
+        }
+        let mut component = vec![ob_index];
+        let mut queue = VecDeque::from([ob_index]);
+        while let Some(current) = queue.pop_front() {
+            for other in related_objects(ocel, current) {
+                if allowed(&other) && visited.insert(other) {
+                    component.push(other);
+                    queue.push_back(other);
+                }
+            }
+        }
+        components.push(component);
+    }
+    components
+        .into_par_iter()
+        .map(|objects| ProcessExecution::from_objects(ocel, None, objects))
+        .collect()
+}
+
+///
+/// One process execution per object of the leading type (see [`ProcessExecutionOptions::LeadingType`])
+///
+pub fn leading_type_executions(
+    ocel: &IndexLinkedOCEL,
+    leading_type: &String,
+    max_hops: Option<usize>,
+) -> Vec<ProcessExecution> {
+    ocel.objects_of_type
+        .get(leading_type)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .par_iter()
+        .map(|ob_index| leading_type_execution(ocel, *ob_index, max_hops))
+        .collect()
+}
+
+/// Leading type process execution of a single leading object
+pub fn leading_type_execution(
+    ocel: &IndexLinkedOCEL,
+    leading_object: ObjectIndex,
+    max_hops: Option<usize>,
+) -> ProcessExecution {
+    let mut type_distance: HashMap<&String, usize> = HashMap::new();
+    let mut visited: HashSet<ObjectIndex> = HashSet::from([leading_object]);
+    let mut included = vec![leading_object];
+    let mut queue = VecDeque::from([(leading_object, 0)]);
+    type_distance.insert(&ocel.ocel.objects[leading_object.0].object_type, 0);
+    while let Some((ob_index, distance)) = queue.pop_front() {
+        if max_hops.is_some_and(|max| distance >= max) {
+            continue;
+        }
+        for other in related_objects(ocel, ob_index) {
+            if !visited.insert(other) {
+                continue;
+            }
+            let other_type = &ocel.ocel.objects[other.0].object_type;
+            let min_distance = *type_distance.entry(other_type).or_insert(distance + 1);
+            if min_distance == distance + 1 {
+                included.push(other);
+                queue.push_back((other, distance + 1));
+            }
+        }
+    }
+    ProcessExecution::from_objects(ocel, Some(leading_object), included)
+}
+
+/// Objects related to the given object via O2O relationships or a shared event
+pub fn related_objects(ocel: &IndexLinkedOCEL, ob_index: ObjectIndex) -> HashSet<ObjectIndex> {
+    let mut ret = HashSet::new();
+    for (other, _reversed, _qualifier) in ocel
+        .symmetric_rels
+        .get(&EventOrObjectIndex::Object(ob_index))
+        .into_iter()
+        .flatten()
+    {
+        match other {
+            EventOrObjectIndex::Object(other_ob) => {
+                ret.insert(*other_ob);
+            }
+            EventOrObjectIndex::Event(ev_index) => {
+                ret.extend(
+                    ocel.symmetric_rels
+                        .get(&EventOrObjectIndex::Event(*ev_index))
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|(o, _, _)| match o {
+                            EventOrObjectIndex::Object(o) => Some(*o),
+                            EventOrObjectIndex::Event(_) => None,
+                        }),
+                );
+            }
+        }
+    }
+    ret.remove(&ob_index);
+    ret
+}
+
+///
+/// Evaluate the tree with the process execution as root scope
+///
+/// Only root bindings whose events and objects are all part of the process execution are evaluated
+/// (child nodes are not restricted).
+///
+pub fn evaluate_in_execution(
+    tree: &BindingBoxTree,
+    ocel: &IndexLinkedOCEL,
+    execution: &ProcessExecution,
+) -> EvaluationResults {
+    let root = match tree.nodes.first() {
+        Some(root) => root,
+        None => return vec![],
+    };
+    let root_bindings = root
+        .expand(Binding::default(), ocel)
+        .into_iter()
+        .filter(|b| execution.contains_binding(b))
+        .collect();
+    root.evaluate_expanded(0, root_bindings, tree, ocel).0
+}
+
+///
+/// Indices of the bindings contained in each process execution (see [`ProcessExecution::contains_binding`])
+///
+/// A binding can be contained in multiple (e.g., leading type) process executions.
+///
+pub fn assign_bindings_to_executions<B: Borrow<Binding>>(
+    executions: &[ProcessExecution],
+    bindings: &[B],
+) -> Vec<Vec<usize>> {
+    // Candidate process executions of a binding are those containing one of its bound objects/events
+    let mut executions_of_object: HashMap<ObjectIndex, Vec<usize>> = HashMap::new();
+    let mut executions_of_event: HashMap<EventIndex, Vec<usize>> = HashMap::new();
+    for (i, execution) in executions.iter().enumerate() {
+        for ob_index in &execution.objects {
+            executions_of_object.entry(*ob_index).or_default().push(i);
+        }
+        for ev_index in &execution.events {
+            executions_of_event.entry(*ev_index).or_default().push(i);
+        }
+    }
+    let all_executions = (0..executions.len()).collect_vec();
+    let mut ret = vec![Vec::new(); executions.len()];
+    for (binding_index, binding) in bindings.iter().enumerate() {
+        let binding = binding.borrow();
+        let candidates = match (
+            binding.object_map.values().next(),
+            binding.event_map.values().next(),
+        ) {
+            (Some(ob_index), _) => executions_of_object.get(ob_index),
+            (None, Some(ev_index)) => executions_of_event.get(ev_index),
+            (None, None) => Some(&all_executions),
+        };
+        for i in candidates.into_iter().flatten() {
+            if executions[*i].contains_binding(binding) {
+                ret[*i].push(binding_index);
+            }
+        }
+    }
+    ret
+}
+
+///
+/// Evaluate the tree separately for all process executions (see [`evaluate_in_execution`])
+///
+/// The root node is only expanded once, and its bindings are then assigned to the process executions.
+///
+pub fn evaluate_per_execution(
+    tree: &BindingBoxTree,
+    ocel: &IndexLinkedOCEL,
+    executions: Vec<ProcessExecution>,
+) -> Vec<ExecutionEvaluationResult> {
+    let root = match tree.nodes.first() {
+        Some(root) => root,
+        None => return vec![],
+    };
+    let root_bindings = root.expand(Binding::default(), ocel);
+    let bindings_per_execution = assign_bindings_to_executions(&executions, &root_bindings)
+        .into_iter()
+        .map(|binding_indices| {
+            binding_indices
+                .into_iter()
+                .map(|i| root_bindings[i].clone())
+                .collect_vec()
+        })
+        .collect_vec();
+    executions
+        .into_par_iter()
+        .zip(bindings_per_execution)
+        .map(|(execution, bindings)| {
+            let (_, root_results) = root.evaluate_expanded(0, bindings, tree, ocel);
+            ExecutionEvaluationResult {
+                situation_count: root_results.len(),
+                situation_violated_count: root_results
+                    .iter()
+                    .filter(|(_, viol)| viol.is_some())
+                    .count(),
+                execution,
+            }
+        })
+        .collect()
+}
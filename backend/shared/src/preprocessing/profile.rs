@@ -0,0 +1,400 @@
+//! Statistics and schema profiling of OCELs
+//!
+//! The profile of an [`IndexLinkedOCEL`] is computed lazily (in parallel) and cached on the linked OCEL,
+//! see [`IndexLinkedOCEL::profile`].
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use process_mining::ocel::ocel_struct::OCELAttributeValue;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use super::linked_ocel::{EventOrObjectIndex, IndexLinkedOCEL, ObjectIndex};
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OCELProfile {
+    pub num_events: usize,
+    pub num_objects: usize,
+    /// Timestamp range of all events (`None` if there are no events)
+    pub time_range: Option<TimeSummary>,
+    pub event_types: Vec<TypeProfile>,
+    pub object_types: Vec<TypeProfile>,
+    /// Cardinalities of E2O relationships (source type is an event type)
+    pub e2o_cardinalities: Vec<RelationshipCardinality>,
+    /// Cardinalities of O2O relationships (source type is an object type)
+    pub o2o_cardinalities: Vec<RelationshipCardinality>,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeProfile {
+    pub name: String,
+    pub count: usize,
+    pub attributes: Vec<AttributeProfile>,
+    /// Timestamp range of the events of this type (always `None` for object types)
+    pub time_range: Option<TimeSummary>,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ObservedValueType {
+    Time,
+    Integer,
+    Float,
+    Boolean,
+    String,
+    Null,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributeProfile {
+    pub name: String,
+    /// Number of observed values (objects can have multiple values for one attribute over time)
+    pub value_count: usize,
+    /// Number of observed values per value type
+    pub value_types: Vec<ValueTypeCount>,
+    /// Fraction of events/objects of the type without any non-null value for this attribute
+    pub null_rate: f64,
+    /// Summary of all integer and float values (`None` if there are none)
+    pub numeric: Option<NumericSummary>,
+    /// Summary of all time values (`None` if there are none)
+    pub time: Option<TimeSummary>,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValueTypeCount {
+    pub value_type: ObservedValueType,
+    pub count: usize,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NumericSummary {
+    pub min: f64,
+    pub q1: f64,
+    pub median: f64,
+    pub q3: f64,
+    pub max: f64,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeSummary {
+    pub min: DateTime<Utc>,
+    pub q1: DateTime<Utc>,
+    pub median: DateTime<Utc>,
+    pub q3: DateTime<Utc>,
+    pub max: DateTime<Utc>,
+}
+
+///
+/// Number of related objects of the target type (with the qualifier) per instance of the source type
+///
+/// Instances without such relationships are counted with 0.
+///
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelationshipCardinality {
+    pub source_type: String,
+    pub qualifier: String,
+    pub target_type: String,
+    pub min: usize,
+    pub avg: f64,
+    pub max: usize,
+}
+
+///
+/// Compute the profile of an OCEL
+///
+/// Prefer [`IndexLinkedOCEL::profile`], which caches the result.
+///
+pub fn profile_ocel(ocel: &IndexLinkedOCEL) -> OCELProfile {
+    let event_types = ocel
+        .ocel
+        .event_types
+        .par_iter()
+        .map(|et| {
+            let evs = ocel
+                .events_of_type
+                .get(&et.name)
+                .map(|evs| {
+                    evs.iter()
+                        .map(|ev_index| &ocel.ocel.events[ev_index.0])
+                        .collect_vec()
+                })
+                .unwrap_or_default();
+            let attributes = attribute_profiles(
+                evs.len(),
+                evs.iter()
+                    .enumerate()
+                    .flat_map(|(i, ev)| ev.attributes.iter().map(move |a| (i, &a.name, &a.value))),
+            );
+            TypeProfile {
+                name: et.name.clone(),
+                count: evs.len(),
+                attributes,
+                time_range: time_summary(
+                    evs.iter().map(|ev| ev.time.with_timezone(&Utc)).collect(),
+                ),
+            }
+        })
+        .collect();
+    let object_types = ocel
+        .ocel
+        .object_types
+        .par_iter()
+        .map(|ot| {
+            let obs = ocel
+                .objects_of_type
+                .get(&ot.name)
+                .map(|obs| {
+                    obs.iter()
+                        .map(|ob_index| &ocel.ocel.objects[ob_index.0])
+                        .collect_vec()
+                })
+                .unwrap_or_default();
+            let attributes = attribute_profiles(
+                obs.len(),
+                obs.iter()
+                    .enumerate()
+                    .flat_map(|(i, ob)| ob.attributes.iter().map(move |a| (i, &a.name, &a.value))),
+            );
+            TypeProfile {
+                name: ot.name.clone(),
+                count: obs.len(),
+                attributes,
+                time_range: None,
+            }
+        })
+        .collect();
+    let (e2o_cardinalities, o2o_cardinalities) = rayon::join(
+        || {
+            relationship_cardinalities(
+                ocel,
+                ocel.events_of_type.iter().map(|(et, evs)| {
+                    (
+                        et,
+                        evs.iter()
+                            .map(|ev| EventOrObjectIndex::Event(*ev))
+                            .collect(),
+                    )
+                }),
+            )
+        },
+        || {
+            relationship_cardinalities(
+                ocel,
+                ocel.objects_of_type.iter().map(|(ot, obs)| {
+                    (
+                        ot,
+                        obs.iter()
+                            .map(|ob| EventOrObjectIndex::Object(*ob))
+                            .collect(),
+                    )
+                }),
+            )
+        },
+    );
+    OCELProfile {
+        num_events: ocel.ocel.events.len(),
+        num_objects: ocel.ocel.objects.len(),
+        time_range: time_summary(
+            ocel.ocel
+                .events
+                .par_iter()
+                .map(|ev| ev.time.with_timezone(&Utc))
+                .collect(),
+        ),
+        event_types,
+        object_types,
+        e2o_cardinalities,
+        o2o_cardinalities,
+    }
+}
+
+/// Profiles of all attributes, given `(instance number, attribute name, value)` triples
+fn attribute_profiles<'a>(
+    num_instances: usize,
+    values: impl Iterator<Item = (usize, &'a String, &'a OCELAttributeValue)>,
+) -> Vec<AttributeProfile> {
+    let mut values_per_attr: BTreeMap<&String, Vec<(usize, &OCELAttributeValue)>> = BTreeMap::new();
+    for (i, name, value) in values {
+        values_per_attr.entry(name).or_default().push((i, value));
+    }
+    values_per_attr
+        .into_iter()
+        .map(|(name, values)| {
+            let value_types = values
+                .iter()
+                .map(|(_, v)| value_type(v))
+                .counts()
+                .into_iter()
+                .sorted()
+                .map(|(value_type, count)| ValueTypeCount { value_type, count })
+                .collect();
+            let non_null_instances = values
+                .iter()
+                .filter(|(_, v)| !matches!(v, OCELAttributeValue::Null))
+                .map(|(i, _)| i)
+                .unique()
+                .count();
+            let numeric_values = values
+                .iter()
+                .filter_map(|(_, v)| match v {
+                    OCELAttributeValue::Integer(i) => Some(*i as f64),
+                    OCELAttributeValue::Float(f) if !f.is_nan() => Some(*f),
+                    _ => None,
+                })
+                .collect();
+            let time_values = values
+                .iter()
+                .filter_map(|(_, v)| match v {
+                    OCELAttributeValue::Time(t) => Some(t.with_timezone(&Utc)),
+                    _ => None,
+                })
+                .collect();
+            AttributeProfile {
+                name: name.clone(),
+                value_count: values.len(),
+                value_types,
+                null_rate: if num_instances > 0 {
+                    1.0 - non_null_instances as f64 / num_instances as f64
+                } else {
+                    0.0
+                },
+                numeric: numeric_summary(numeric_values),
+                time: time_summary(time_values),
+            }
+        })
+        .collect()
+}
+
+fn value_type(value: &OCELAttributeValue) -> ObservedValueType {
+    match value {
+        OCELAttributeValue::Time(_) => ObservedValueType::Time,
+        OCELAttributeValue::Integer(_) => ObservedValueType::Integer,
+        OCELAttributeValue::Float(_) => ObservedValueType::Float,
+        OCELAttributeValue::Boolean(_) => ObservedValueType::Boolean,
+        OCELAttributeValue::String(_) => ObservedValueType::String,
+        OCELAttributeValue::Null => ObservedValueType::Null,
+    }
+}
+
+/// Value at the given quantile of a sorted, non-empty slice (nearest rank)
+fn quantile<T: Copy>(sorted: &[T], q: f64) -> T {
+    let rank = (q * (sorted.len() - 1) as f64).round() as usize;
+    sorted[rank.min(sorted.len() - 1)]
+}
+
+fn numeric_summary(mut values: Vec<f64>) -> Option<NumericSummary> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    Some(NumericSummary {
+        min: values[0],
+        q1: quantile(&values, 0.25),
+        median: quantile(&values, 0.5),
+        q3: quantile(&values, 0.75),
+        max: values[values.len() - 1],
+    })
+}
+
+fn time_summary(mut values: Vec<DateTime<Utc>>) -> Option<TimeSummary> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort();
+    Some(TimeSummary {
+        min: values[0],
+        q1: quantile(&values, 0.25),
+        median: quantile(&values, 0.5),
+        q3: quantile(&values, 0.75),
+        max: values[values.len() - 1],
+    })
+}
+
+/// Cardinalities of the outgoing relationships (see [`IndexLinkedOCEL::rels`]) of all instances, grouped by type
+fn relationship_cardinalities<'a>(
+    ocel: &IndexLinkedOCEL,
+    instances_per_type: impl Iterator<Item = (&'a String, Vec<EventOrObjectIndex>)>,
+) -> Vec<RelationshipCardinality> {
+    let instances_per_type = instances_per_type.collect_vec();
+    instances_per_type
+        .into_par_iter()
+        .flat_map(|(source_type, instances)| {
+            let counts_per_instance = instances
+                .iter()
+                .map(|index| {
+                    ocel.rels
+                        .get(index)
+                        .into_iter()
+                        .flatten()
+                        .map(|(ObjectIndex(ob), qualifier)| {
+                            (qualifier, &ocel.ocel.objects[*ob].object_type)
+                        })
+                        .counts()
+                })
+                .collect_vec();
+            let mut per_rel: HashMap<(&String, &String), (usize, usize, usize)> = HashMap::new();
+            for counts in &counts_per_instance {
+                for (rel, count) in counts {
+                    let (min, sum, max) = per_rel.entry(*rel).or_insert((usize::MAX, 0, 0));
+                    *min = (*min).min(*count);
+                    *sum += count;
+                    *max = (*max).max(*count);
+                }
+            }
+            per_rel
+                .into_iter()
+                .map(|((qualifier, target_type), (min, sum, max))| {
+                    let instances_with_rel = counts_per_instance
+                        .iter()
+                        .filter(|counts| counts.contains_key(&(qualifier, target_type)))
+                        .count();
+                    RelationshipCardinality {
+                        source_type: source_type.clone(),
+                        qualifier: qualifier.clone(),
+                        target_type: target_type.clone(),
+                        min: if instances_with_rel < instances.len() {
+                            0
+                        } else {
+                            min
+                        },
+                        avg: sum as f64 / instances.len() as f64,
+                        max,
+                    }
+                })
+                .collect_vec()
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .sorted_by(|a, b| {
+            (&a.source_type, &a.qualifier, &a.target_type).cmp(&(
+                &b.source_type,
+                &b.qualifier,
+                &b.target_type,
+            ))
+        })
+        .collect()
+}
@@ -0,0 +1,458 @@
+//! Object-centric variant analysis
+//!
+//! Process executions (see [`super::process_executions`]) are grouped into variants by structural equivalence.
+//! Each process execution is represented as a graph with event and object nodes (labeled by their type),
+//! E2O/O2O relationship edges (labeled by their qualifier) and directly-follows edges between the consecutive events of each object.
+//!
+//! Two process executions belong to the same variant if their graphs are isomorphic.
+//! As a first step, the graphs are grouped by their fingerprint after color refinement
+//! (1-dimensional Weisfeiler-Lehman test): Isomorphic graphs always have the same fingerprint.
+//! As some non-isomorphic graphs (e.g., highly regular ones) also share a fingerprint,
+//! graphs with the same fingerprint are then checked for isomorphism exactly
+//! (backtracking search, only mapping nodes with the same refined color onto each other).
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+};
+
+use itertools::Itertools;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::binding_box::EvaluateBoxTreeResult;
+
+use super::{
+    linked_ocel::{EventOrObjectIndex, IndexLinkedOCEL},
+    process_executions::{
+        assign_bindings_to_executions, get_process_executions, ProcessExecution,
+        ProcessExecutionOptions,
+    },
+};
+
+const DEFAULT_MAX_EXAMPLES: usize = 3;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariantAnalysisRequest {
+    pub executions: ProcessExecutionOptions,
+    /// Evaluation results of a tree on the loaded OCEL, used to compute violation rates per variant
+    pub evaluation: Option<EvaluateBoxTreeResult>,
+    /// Index of the node whose situations are considered (defaults to the root node)
+    pub node_index: Option<usize>,
+    /// Maximum number of example process executions per variant (defaults to 3)
+    pub max_examples: Option<usize>,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessVariant {
+    /// Fingerprint of the variant (hex), with a suffix (e.g., `-1`) if non-isomorphic process executions share the fingerprint
+    pub id: String,
+    pub num_executions: usize,
+    /// Fraction of all process executions belonging to this variant
+    pub frequency: f64,
+    /// Number of events per event type (of a single process execution)
+    pub event_types: Vec<(String, usize)>,
+    /// Number of objects per object type (of a single process execution)
+    pub object_types: Vec<(String, usize)>,
+    pub examples: Vec<ProcessExecution>,
+    /// Number of situations (of the evaluated node) contained in process executions of this variant
+    pub situation_count: usize,
+    pub situation_violated_count: usize,
+    /// `None` if no evaluation was provided or there are no situations
+    pub violation_rate: Option<f64>,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../../../frontend/src/types/generated/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariantAnalysisResult {
+    pub num_executions: usize,
+    /// Variants, sorted by descending frequency
+    pub variants: Vec<ProcessVariant>,
+}
+
+///
+/// Group the process executions into variants (see [module-level documentation](self))
+///
+pub fn analyze_variants(
+    ocel: &IndexLinkedOCEL,
+    req: &VariantAnalysisRequest,
+) -> VariantAnalysisResult {
+    let executions = get_process_executions(ocel, &req.executions);
+    let graphs: Vec<ExecutionGraph> = executions
+        .par_iter()
+        .map(|execution| ExecutionGraph::new(ocel, execution))
+        .collect();
+
+    // Number of situations and violated situations per process execution
+    let mut situation_counts = vec![(0, 0); executions.len()];
+    if let Some(evaluation) = &req.evaluation {
+        if let Some(node_result) = evaluation
+            .evaluation_results
+            .get(req.node_index.unwrap_or(0))
+        {
+            let bindings = node_result.situations.iter().map(|(b, _)| b).collect_vec();
+            for (execution_index, situation_indices) in
+                assign_bindings_to_executions(&executions, &bindings)
+                    .into_iter()
+                    .enumerate()
+            {
+                situation_counts[execution_index] = (
+                    situation_indices.len(),
+                    situation_indices
+                        .iter()
+                        .filter(|i| node_result.situations[**i].1.is_some())
+                        .count(),
+                );
+            }
+        }
+    }
+
+    let num_executions = executions.len();
+    let max_examples = req.max_examples.unwrap_or(DEFAULT_MAX_EXAMPLES);
+    // Process executions of the same variant (per fingerprint)
+    let mut per_fingerprint: HashMap<u64, Vec<Vec<usize>>> = HashMap::new();
+    for (i, graph) in graphs.iter().enumerate() {
+        let variants = per_fingerprint.entry(graph.fingerprint).or_default();
+        match variants
+            .iter_mut()
+            .find(|execution_indices| graphs[execution_indices[0]].is_isomorphic_to(graph))
+        {
+            Some(execution_indices) => execution_indices.push(i),
+            None => variants.push(vec![i]),
+        }
+    }
+    let variants = per_fingerprint
+        .into_iter()
+        .flat_map(|(fingerprint, variants)| {
+            variants
+                .into_iter()
+                .enumerate()
+                .map(move |(i, execution_indices)| match i {
+                    0 => (format!("{fingerprint:016x}"), execution_indices),
+                    _ => (format!("{fingerprint:016x}-{i}"), execution_indices),
+                })
+        })
+        .map(|(id, execution_indices)| {
+            let (situation_count, situation_violated_count) = execution_indices
+                .iter()
+                .map(|i| situation_counts[*i])
+                .fold((0, 0), |(a, b), (c, d)| (a + c, b + d));
+            let first = &executions[execution_indices[0]];
+            ProcessVariant {
+                id,
+                num_executions: execution_indices.len(),
+                frequency: execution_indices.len() as f64 / num_executions as f64,
+                event_types: type_counts(
+                    first
+                        .events
+                        .iter()
+                        .map(|ev_index| &ocel.ocel.events[ev_index.0].event_type),
+                ),
+                object_types: type_counts(
+                    first
+                        .objects
+                        .iter()
+                        .map(|ob_index| &ocel.ocel.objects[ob_index.0].object_type),
+                ),
+                examples: execution_indices
+                    .iter()
+                    .take(max_examples)
+                    .map(|i| executions[*i].clone())
+                    .collect(),
+                situation_count,
+                situation_violated_count,
+                violation_rate: (req.evaluation.is_some() && situation_count > 0)
+                    .then(|| situation_violated_count as f64 / situation_count as f64),
+            }
+        })
+        .sorted_by(|a, b| {
+            b.num_executions
+                .cmp(&a.num_executions)
+                .then_with(|| a.id.cmp(&b.id))
+        })
+        .collect();
+    VariantAnalysisResult {
+        num_executions,
+        variants,
+    }
+}
+
+fn type_counts<'a>(types: impl Iterator<Item = &'a String>) -> Vec<(String, usize)> {
+    types
+        .counts()
+        .into_iter()
+        .map(|(t, count)| (t.clone(), count))
+        .sorted()
+        .collect()
+}
+
+fn hash_of<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Edge of the process execution graph (label hash, whether the edge is incoming, position of the other node)
+type LabeledEdge = (u64, bool, usize);
+
+/// Graph of a process execution (see [module-level documentation](self)), with its nodes colored by color refinement
+struct ExecutionGraph {
+    /// Refined color of each node
+    colors: Vec<u64>,
+    /// Sorted label hashes of the edges between two nodes (from, to)
+    edge_labels: HashMap<(usize, usize), Vec<u64>>,
+    fingerprint: u64,
+}
+
+impl ExecutionGraph {
+    fn new(ocel: &IndexLinkedOCEL, execution: &ProcessExecution) -> Self {
+        let nodes = execution
+            .events
+            .iter()
+            .map(|ev| EventOrObjectIndex::Event(*ev))
+            .chain(
+                execution
+                    .objects
+                    .iter()
+                    .map(|ob| EventOrObjectIndex::Object(*ob)),
+            )
+            .collect_vec();
+        let node_pos: HashMap<EventOrObjectIndex, usize> =
+            nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+        let mut edges: Vec<Vec<LabeledEdge>> = vec![Vec::new(); nodes.len()];
+        let mut edge_labels: HashMap<(usize, usize), Vec<u64>> = HashMap::new();
+        let mut add_edge = |from: usize, to: usize, label: u64| {
+            edges[from].push((label, false, to));
+            edges[to].push((label, true, from));
+            edge_labels.entry((from, to)).or_default().push(label);
+        };
+        // E2O and O2O relationships
+        for (from, from_node) in nodes.iter().enumerate() {
+            for (ob_index, qualifier) in ocel.rels.get(from_node).into_iter().flatten() {
+                if let Some(to) = node_pos.get(&EventOrObjectIndex::Object(*ob_index)) {
+                    add_edge(from, *to, hash_of(&("rel", qualifier)));
+                }
+            }
+        }
+        // Directly-follows relation of the events of each object
+        for ob_index in &execution.objects {
+            let events = ocel
+                .object_events_map
+                .get(ob_index)
+                .into_iter()
+                .flatten()
+                .sorted_by_key(|ev_index| (ocel.ocel.events[ev_index.0].time, ev_index.0))
+                .collect_vec();
+            for (a, b) in events.into_iter().tuple_windows() {
+                if let (Some(from), Some(to)) = (
+                    node_pos.get(&EventOrObjectIndex::Event(*a)),
+                    node_pos.get(&EventOrObjectIndex::Event(*b)),
+                ) {
+                    add_edge(*from, *to, hash_of(&"df"));
+                }
+            }
+        }
+        edge_labels
+            .values_mut()
+            .for_each(|labels| labels.sort_unstable());
+
+        let initial_colors: Vec<u64> = nodes
+            .iter()
+            .map(|node| match node {
+                EventOrObjectIndex::Event(ev) => {
+                    hash_of(&("event", &ocel.ocel.events[ev.0].event_type))
+                }
+                EventOrObjectIndex::Object(ob) => {
+                    hash_of(&("object", &ocel.ocel.objects[ob.0].object_type))
+                }
+            })
+            .collect();
+        let colors = refine_colors(initial_colors, &edges);
+        let fingerprint = hash_of(&colors.iter().sorted_unstable().collect_vec());
+        Self {
+            colors,
+            edge_labels,
+            fingerprint,
+        }
+    }
+
+    fn labels_between(&self, from: usize, to: usize) -> &[u64] {
+        self.edge_labels.get(&(from, to)).map_or(&[], Vec::as_slice)
+    }
+
+    ///
+    /// Whether the graphs are isomorphic (preserving node and edge labels)
+    ///
+    /// Nodes are only mapped onto nodes with the same refined color, starting with the nodes of the rarest colors.
+    ///
+    fn is_isomorphic_to(&self, other: &ExecutionGraph) -> bool {
+        if self.fingerprint != other.fingerprint
+            || self.colors.len() != other.colors.len()
+            || self.edge_labels.values().map(Vec::len).sum::<usize>()
+                != other.edge_labels.values().map(Vec::len).sum::<usize>()
+        {
+            return false;
+        }
+        let color_counts = self.colors.iter().counts();
+        let order = (0..self.colors.len())
+            .sorted_by_key(|i| (color_counts[&self.colors[*i]], *i))
+            .collect_vec();
+        let mut mapping = vec![0; self.colors.len()];
+        let mut used = vec![false; self.colors.len()];
+        self.extend_isomorphism(other, &order, &mut mapping, &mut used)
+    }
+
+    /// Try to extend the mapping of the nodes `order[..k]` (with `k` being the number of used nodes) to all nodes
+    fn extend_isomorphism(
+        &self,
+        other: &ExecutionGraph,
+        order: &[usize],
+        mapping: &mut [usize],
+        used: &mut [bool],
+    ) -> bool {
+        let num_mapped = used.iter().filter(|u| **u).count();
+        let Some(&node) = order.get(num_mapped) else {
+            return true;
+        };
+        for candidate in 0..other.colors.len() {
+            if used[candidate] || other.colors[candidate] != self.colors[node] {
+                continue;
+            }
+            mapping[node] = candidate;
+            let consistent = order[..=num_mapped].iter().all(|prev| {
+                self.labels_between(node, *prev) == other.labels_between(candidate, mapping[*prev])
+                    && self.labels_between(*prev, node)
+                        == other.labels_between(mapping[*prev], candidate)
+            });
+            if consistent {
+                used[candidate] = true;
+                if self.extend_isomorphism(other, order, mapping, used) {
+                    return true;
+                }
+                used[candidate] = false;
+            }
+        }
+        false
+    }
+}
+
+///
+/// Refine the node colors based on the colors of their neighbors until the number of colors is stable
+///
+fn refine_colors(mut colors: Vec<u64>, edges: &[Vec<LabeledEdge>]) -> Vec<u64> {
+    let mut num_colors = colors.iter().unique().count();
+    for _ in 0..colors.len() {
+        let refined: Vec<u64> = colors
+            .iter()
+            .zip(edges)
+            .map(|(color, node_edges)| {
+                let neighborhood = node_edges
+                    .iter()
+                    .map(|(label, incoming, to)| (*label, *incoming, colors[*to]))
+                    .sorted_unstable()
+                    .collect_vec();
+                hash_of(&(color, neighborhood))
+            })
+            .collect();
+        let refined_num_colors = refined.iter().unique().count();
+        colors = refined;
+        if refined_num_colors == num_colors {
+            break;
+        }
+        num_colors = refined_num_colors;
+    }
+    colors
+}
+
+///
+/// Isomorphism-invariant fingerprint of the graph of a process execution
+///
+/// Node colors are refined based on the colors of their neighbors until the number of colors is stable.
+/// The fingerprint is the hash of the resulting multiset of colors.
+///
+pub fn execution_fingerprint(ocel: &IndexLinkedOCEL, execution: &ProcessExecution) -> u64 {
+    ExecutionGraph::new(ocel, execution).fingerprint
+}
+
+#[cfg(test)]
+mod tests {
+    use process_mining::{ocel::ocel_struct::OCELObject, OCEL};
+
+    use crate::{
+        binding_box::test::{ocel_type, relationship},
+        preprocessing::linked_ocel::link_ocel_info,
+    };
+
+    use super::*;
+
+    /// Objects `{prefix}0`, `{prefix}1`, ... with each object related to the next one in the given order (as a cycle)
+    fn cycle(prefix: &str, order: &[usize]) -> Vec<OCELObject> {
+        order
+            .iter()
+            .enumerate()
+            .map(|(pos, i)| OCELObject {
+                id: format!("{prefix}{i}"),
+                object_type: "nodes".to_string(),
+                attributes: Vec::new(),
+                relationships: vec![relationship(
+                    &format!("{prefix}{}", order[(pos + 1) % order.len()]),
+                    "next",
+                )],
+            })
+            .collect()
+    }
+
+    fn graphs_of(ocel: &IndexLinkedOCEL, prefixes: &[&[&str]]) -> Vec<ExecutionGraph> {
+        prefixes
+            .iter()
+            .map(|prefixes| {
+                let execution = ProcessExecution {
+                    leading_object: None,
+                    objects: ocel
+                        .ocel
+                        .objects
+                        .iter()
+                        .filter(|o| prefixes.iter().any(|p| o.id.starts_with(p)))
+                        .map(|o| *ocel.index_of_ob(&o.id).unwrap())
+                        .sorted_by_key(|ob| ob.0)
+                        .collect(),
+                    events: Vec::new(),
+                };
+                ExecutionGraph::new(ocel, &execution)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn isomorphic_and_non_isomorphic_executions() {
+        let objects = [
+            cycle("a", &[0, 1, 2, 3, 4, 5]),
+            cycle("b", &[0, 3, 1, 4, 2, 5]),
+            cycle("c", &[0, 1, 2]),
+            cycle("d", &[0, 1, 2]),
+        ]
+        .concat();
+        let ocel = link_ocel_info(OCEL {
+            event_types: Vec::new(),
+            object_types: vec![ocel_type("nodes", &[])],
+            events: Vec::new(),
+            objects,
+        });
+        let graphs = graphs_of(&ocel, &[&["a"], &["b"], &["c", "d"]]);
+        // Two 6-cycles (with differently ordered objects)
+        assert_eq!(graphs[0].fingerprint, graphs[1].fingerprint);
+        assert!(graphs[0].is_isomorphic_to(&graphs[1]));
+        assert!(graphs[1].is_isomorphic_to(&graphs[0]));
+        // A 6-cycle and two 3-cycles can not be distinguished by color refinement
+        assert_eq!(graphs[0].fingerprint, graphs[2].fingerprint);
+        assert!(!graphs[0].is_isomorphic_to(&graphs[2]));
+        assert!(!graphs[2].is_isomorphic_to(&graphs[0]));
+        assert!(graphs[2].is_isomorphic_to(&graphs[2]));
+    }
+}
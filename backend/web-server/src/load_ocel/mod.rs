@@ -4,11 +4,15 @@ use std::{
 };
 
 use axum::{extract::State, http::StatusCode, Json};
-use ocedeclare_shared::{preprocessing::linked_ocel::IndexLinkedOCEL, OCELInfo};
+use ocedeclare_shared::{
+    preprocessing::{cache::load_linked_ocel_with_cache, linked_ocel::IndexLinkedOCEL},
+    OCELInfo,
+};
 use serde::{Deserialize, Serialize};
 
 use process_mining::{
     event_log::ocel::ocel_struct::OCEL,
+    import_ocel_sqlite_from_path,
     ocel::xml_ocel_import::{import_ocel_xml_file_with, OCELImportOptions},
 };
 
@@ -34,7 +38,7 @@ pub async fn get_available_ocels() -> (StatusCode, Json<Option<Vec<String>>>) {
         for dir_entry in paths.flatten() {
             let path_buf = dir_entry.path();
             let path = path_buf.as_os_str().to_str().unwrap();
-            if path.ends_with(".json") || path.ends_with(".xml") {
+            if path.ends_with(".json") || path.ends_with(".xml") || path.ends_with(".sqlite") {
                 ocel_names.push(path.split('/').last().unwrap().to_string())
             }
         }
@@ -53,11 +57,11 @@ pub async fn load_ocel_file_req(
 }
 
 pub fn load_ocel_file_to_state(name: &str, state: &AppState) -> Option<OCELInfo> {
-    match load_ocel_file(name) {
-        Ok(ocel) => {
-            let ocel_info: OCELInfo = (&ocel).into();
+    match load_linked_ocel_file(name) {
+        Ok(linked_ocel) => {
+            let ocel_info: OCELInfo = (&linked_ocel.ocel).into();
             let mut x = state.ocel.write().unwrap();
-            *x = Some(IndexLinkedOCEL::new(ocel));
+            *x = Some(linked_ocel);
             Some(ocel_info)
         }
         Err(e) => {
@@ -67,6 +71,12 @@ pub fn load_ocel_file_to_state(name: &str, state: &AppState) -> Option<OCELInfo>
     }
 }
 
+/// Load and link an OCEL file from the data directory (using the on-disk cache)
+pub fn load_linked_ocel_file(name: &str) -> Result<IndexLinkedOCEL, std::io::Error> {
+    let path = format!("{DATA_PATH}{name}");
+    load_linked_ocel_with_cache(&path, || load_ocel_file(name))
+}
+
 pub fn load_ocel_file(name: &str) -> Result<OCEL, std::io::Error> {
     let path = format!("{DATA_PATH}{name}");
     if name.ends_with(".json") {
@@ -74,6 +84,8 @@ pub fn load_ocel_file(name: &str) -> Result<OCEL, std::io::Error> {
         let reader = BufReader::new(file);
         let ocel: OCEL = serde_json::from_reader(reader)?;
         Ok(ocel)
+    } else if name.ends_with(".sqlite") {
+        import_ocel_sqlite_from_path(&path).map_err(std::io::Error::other)
     } else {
         let ocel = import_ocel_xml_file_with(&path, OCELImportOptions::default());
         Ok(ocel)
@@ -15,17 +15,50 @@ use std::{
 };
 
 use ocedeclare_shared::{
-    binding_box::{evaluate_box_tree, CheckWithBoxTreeRequest, EvaluateBoxTreeResult},
+    binding_box::{
+        dsl::{box_tree_from_text, box_tree_to_text, BoxTreeFromTextRequest, BoxTreeParseError},
+        evaluate_box_tree,
+        sqlite::{
+            evaluate_box_tree_sqlite, CheckSqliteWithBoxTreeRequest, SqliteEvaluateBoxTreeResult,
+        },
+        structs::BindingBoxTree,
+        trends::{analyze_trend, TrendAnalysisRequest, TrendAnalysisResult},
+        CheckWithBoxTreeRequest, EvaluateBoxTreeResult,
+    },
+    comparison::{compare_ocels, CompareOCELsRequest, OCELComparisonResult},
     discovery::{
-        auto_discover_constraints_with_options, AutoDiscoverConstraintsRequest,
-        AutoDiscoverConstraintsResponse,
+        auto_discover_constraints_with_options,
+        dfg::{
+            dfg_arc_to_constraint, discover_ocdfg, ocdfg_to_dot, DFGArcConstraintRequest,
+            DiscoverOCDFGRequest, OCDFG,
+        },
+        example_discovery::{
+            learn_constraints_from_examples, LearnFromExamplesRequest, LearnFromExamplesResponse,
+        },
+        AutoDiscoverConstraintsRequest, AutoDiscoverConstraintsResponse,
     },
     get_event_info, get_object_info,
-    ocel_graph::{get_ocel_graph, OCELGraph, OCELGraphOptions},
+    ocel_graph::{
+        export::{export_ocel_graph, ExportOCELGraphRequest},
+        get_ocel_graph, get_ocel_paths,
+        schema::{get_schema_graph, schema_graph_to_dot, OCELSchemaGraph},
+        OCELGraph, OCELGraphOptions, OCELPathOptions, OCELPaths,
+    },
     ocel_qualifiers::qualifiers::{
         get_qualifiers_for_event_types, QualifierAndObjectType, QualifiersForEventType,
     },
-    preprocessing::{linked_ocel::IndexLinkedOCEL, preprocess::link_ocel_info},
+    preprocessing::{
+        filter::{export_ocel_by_extension, filter_linked_ocel, filter_ocel, FilterOCELRequest},
+        flatten::{export_flat_log_by_extension, flatten_ocel, FlattenOCELRequest},
+        linked_ocel::IndexLinkedOCEL,
+        preprocess::link_ocel_info,
+        process_executions::{
+            evaluate_per_execution, get_process_executions, CheckWithBoxTreePerExecutionRequest,
+            ExecutionEvaluationResult, ProcessExecution, ProcessExecutionOptions,
+        },
+        profile::OCELProfile,
+        variants::{analyze_variants, VariantAnalysisRequest, VariantAnalysisResult},
+    },
     EventWithIndex, IndexOrID, OCELInfo, ObjectWithIndex,
 };
 use process_mining::{
@@ -34,7 +67,8 @@ use process_mining::{
 use tower_http::cors::CorsLayer;
 
 use crate::load_ocel::{
-    get_available_ocels, load_ocel_file_req, load_ocel_file_to_state, DEFAULT_OCEL_FILE,
+    get_available_ocels, load_linked_ocel_file, load_ocel_file_req, load_ocel_file_to_state,
+    DATA_PATH, DEFAULT_OCEL_FILE,
 };
 pub mod load_ocel;
 
@@ -61,6 +95,9 @@ async fn main() {
     let app = Router::new()
         .route("/ocel/load", post(load_ocel_file_req))
         .route("/ocel/info", get(get_loaded_ocel_info))
+        .route("/ocel/profile", get(get_loaded_ocel_profile))
+        .route("/ocel/schema-graph", get(get_schema_graph_req))
+        .route("/ocel/schema-graph-dot", get(get_schema_graph_dot_req))
         .route(
             "/ocel/upload-json",
             post(upload_ocel_json).layer(DefaultBodyLimit::disable()),
@@ -83,11 +120,33 @@ async fn main() {
             get(get_qualifers_for_object_types),
         )
         .route("/ocel/graph", post(ocel_graph_req))
+        .route("/ocel/graph-paths", post(ocel_paths_req))
+        .route("/ocel/graph-export", post(ocel_graph_export_req))
         .route("/ocel/check-constraints-box", post(check_with_box_tree_req))
+        .route(
+            "/ocel/check-constraints-box-sqlite",
+            post(check_sqlite_with_box_tree_req),
+        )
+        .route(
+            "/ocel/check-constraints-box-per-execution",
+            post(check_with_box_tree_per_execution_req),
+        )
+        .route("/ocel/process-executions", post(process_executions_req))
+        .route("/ocel/variants", post(analyze_variants_req))
+        .route("/ocel/trend-analysis", post(trend_analysis_req))
+        .route("/ocel/compare", post(compare_ocels_req))
+        .route("/ocel/filter", post(filter_ocel_req))
+        .route("/ocel/flatten", post(flatten_ocel_req))
+        .route("/box-tree/to-text", post(box_tree_to_text_req))
+        .route("/box-tree/from-text", post(box_tree_from_text_req))
         .route(
             "/ocel/discover-constraints",
             post(auto_discover_constraints_handler),
         )
+        .route("/ocel/learn-from-examples", post(learn_from_examples_req))
+        .route("/ocel/dfg", post(discover_ocdfg_req))
+        .route("/ocel/dfg-dot", post(discover_ocdfg_dot_req))
+        .route("/dfg/arc-constraint", post(dfg_arc_constraint_req))
         .route("/ocel/event/:event_id", get(get_event_info_req))
         .route("/ocel/object/:object_id", get(get_object_info_req))
         .route("/ocel/get-event", post(get_event_req))
@@ -111,6 +170,35 @@ pub async fn get_loaded_ocel_info(
     }
 }
 
+pub async fn get_loaded_ocel_profile(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<Option<OCELProfile>>) {
+    match with_ocel_from_state(&State(state), |ocel| ocel.profile().clone()) {
+        Some(profile) => (StatusCode::OK, Json(Some(profile))),
+        None => (StatusCode::NOT_FOUND, Json(None)),
+    }
+}
+
+pub async fn get_schema_graph_req(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<Option<OCELSchemaGraph>>) {
+    match with_ocel_from_state(&State(state), get_schema_graph) {
+        Some(graph) => (StatusCode::OK, Json(Some(graph))),
+        None => (StatusCode::NOT_FOUND, Json(None)),
+    }
+}
+
+pub async fn get_schema_graph_dot_req(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<Option<String>>) {
+    match with_ocel_from_state(&State(state), |ocel| {
+        schema_graph_to_dot(&get_schema_graph(ocel))
+    }) {
+        Some(dot) => (StatusCode::OK, Json(Some(dot))),
+        None => (StatusCode::NOT_FOUND, Json(None)),
+    }
+}
+
 async fn upload_ocel_xml<'a>(
     State(state): State<AppState>,
     ocel_bytes: Bytes,
@@ -198,6 +286,31 @@ pub async fn ocel_graph_req<'a>(
     }
 }
 
+pub async fn ocel_graph_export_req(
+    State(state): State<AppState>,
+    Json(req): Json<ExportOCELGraphRequest>,
+) -> (StatusCode, Json<Option<String>>) {
+    let graph = with_ocel_from_state(&State(state), |ocel| get_ocel_graph(ocel, req.options));
+    match graph.flatten() {
+        Some(graph) => (
+            StatusCode::OK,
+            Json(Some(export_ocel_graph(&graph, req.format))),
+        ),
+        None => (StatusCode::BAD_REQUEST, Json(None)),
+    }
+}
+
+pub async fn ocel_paths_req(
+    State(state): State<AppState>,
+    Json(options): Json<OCELPathOptions>,
+) -> (StatusCode, Json<Option<OCELPaths>>) {
+    let paths = with_ocel_from_state(&State(state), |ocel| get_ocel_paths(ocel, &options));
+    match paths.flatten() {
+        Some(x) => (StatusCode::OK, Json(Some(x))),
+        None => (StatusCode::BAD_REQUEST, Json(None)),
+    }
+}
+
 pub async fn check_with_box_tree_req<'a>(
     state: State<AppState>,
     Json(req): Json<CheckWithBoxTreeRequest>,
@@ -209,12 +322,146 @@ pub async fn check_with_box_tree_req<'a>(
                 req.tree,
                 ocel,
                 req.measure_performance.unwrap_or(false),
+                req.sampling,
             ))),
         )
     })
     .unwrap_or((StatusCode::INTERNAL_SERVER_ERROR, Json(None)))
 }
 
+pub async fn trend_analysis_req(
+    state: State<AppState>,
+    Json(req): Json<TrendAnalysisRequest>,
+) -> (StatusCode, Json<Option<TrendAnalysisResult>>) {
+    with_ocel_from_state(&state, |ocel| {
+        (StatusCode::OK, Json(Some(analyze_trend(&req, ocel))))
+    })
+    .unwrap_or((StatusCode::INTERNAL_SERVER_ERROR, Json(None)))
+}
+
+/// Compare the loaded OCEL to another OCEL file in the data directory
+pub async fn compare_ocels_req(
+    state: State<AppState>,
+    Json(req): Json<CompareOCELsRequest>,
+) -> (StatusCode, Json<Option<OCELComparisonResult>>) {
+    let file_name = match std::path::Path::new(&req.other_ocel_path).file_name() {
+        Some(file_name) => file_name.to_string_lossy().to_string(),
+        None => return (StatusCode::BAD_REQUEST, Json(None)),
+    };
+    let other_ocel = match load_linked_ocel_file(&file_name) {
+        Ok(ocel) => ocel,
+        Err(e) => {
+            eprintln!("Error importing OCEL: {:?}", e);
+            return (StatusCode::BAD_REQUEST, Json(None));
+        }
+    };
+    with_ocel_from_state(&state, |ocel| {
+        (
+            StatusCode::OK,
+            Json(Some(compare_ocels(ocel, &other_ocel, &req.options))),
+        )
+    })
+    .unwrap_or((StatusCode::INTERNAL_SERVER_ERROR, Json(None)))
+}
+
+/// Filter the loaded OCEL, either replacing it or saving the result as a new file in the data directory
+pub async fn filter_ocel_req(
+    State(state): State<AppState>,
+    Json(req): Json<FilterOCELRequest>,
+) -> (StatusCode, Json<Option<OCELInfo>>) {
+    match req.fork_path {
+        Some(fork_path) => {
+            let file_name = match std::path::Path::new(&fork_path).file_name() {
+                Some(file_name) => file_name.to_owned(),
+                None => return (StatusCode::BAD_REQUEST, Json(None)),
+            };
+            let path = std::path::Path::new(DATA_PATH).join(file_name);
+            let filtered = match with_ocel_from_state(&State(state), |ocel| {
+                filter_ocel(ocel, &req.filter)
+            }) {
+                Some(filtered) => filtered,
+                None => return (StatusCode::INTERNAL_SERVER_ERROR, Json(None)),
+            };
+            match export_ocel_by_extension(&filtered, path) {
+                Ok(()) => (StatusCode::OK, Json(Some((&filtered).into()))),
+                Err(e) => {
+                    eprintln!("Error exporting filtered OCEL: {e}");
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(None))
+                }
+            }
+        }
+        None => {
+            let mut x = state.ocel.write().unwrap();
+            match x.as_ref() {
+                Some(ocel) => {
+                    let filtered = filter_linked_ocel(ocel, &req.filter);
+                    let ocel_info: OCELInfo = (&filtered.ocel).into();
+                    *x = Some(filtered);
+                    (StatusCode::OK, Json(Some(ocel_info)))
+                }
+                None => (StatusCode::INTERNAL_SERVER_ERROR, Json(None)),
+            }
+        }
+    }
+}
+
+/// Flatten the loaded OCEL and save the resulting event log in the data directory
+///
+/// Returns the number of cases
+pub async fn flatten_ocel_req(
+    State(state): State<AppState>,
+    Json(req): Json<FlattenOCELRequest>,
+) -> (StatusCode, Json<Option<usize>>) {
+    let file_name = match std::path::Path::new(&req.export_path).file_name() {
+        Some(file_name) => file_name.to_owned(),
+        None => return (StatusCode::BAD_REQUEST, Json(None)),
+    };
+    let path = std::path::Path::new(DATA_PATH).join(file_name);
+    let log = match with_ocel_from_state(&State(state), |ocel| {
+        flatten_ocel(ocel, &req.case_notion, req.constraint_outcome.as_ref())
+    }) {
+        Some(log) => log,
+        None => return (StatusCode::INTERNAL_SERVER_ERROR, Json(None)),
+    };
+    match export_flat_log_by_extension(&log, path) {
+        Ok(()) => (StatusCode::OK, Json(Some(log.traces.len()))),
+        Err(e) => {
+            eprintln!("Error exporting flattened log: {e}");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(None))
+        }
+    }
+}
+
+pub async fn box_tree_to_text_req(Json(tree): Json<BindingBoxTree>) -> Json<String> {
+    Json(box_tree_to_text(&tree))
+}
+
+pub async fn box_tree_from_text_req(
+    Json(req): Json<BoxTreeFromTextRequest>,
+) -> Result<Json<BindingBoxTree>, (StatusCode, Json<BoxTreeParseError>)> {
+    box_tree_from_text(&req.text)
+        .map(Json)
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(e)))
+}
+
+/// Evaluate directly on an SQLite OCEL file in the data directory (without loading it into memory)
+pub async fn check_sqlite_with_box_tree_req(
+    Json(req): Json<CheckSqliteWithBoxTreeRequest>,
+) -> (StatusCode, Json<Option<SqliteEvaluateBoxTreeResult>>) {
+    let file_name = match std::path::Path::new(&req.ocel_path).file_name() {
+        Some(file_name) => file_name.to_owned(),
+        None => return (StatusCode::BAD_REQUEST, Json(None)),
+    };
+    let path = std::path::Path::new(DATA_PATH).join(file_name);
+    match evaluate_box_tree_sqlite(&req.tree, path) {
+        Ok(res) => (StatusCode::OK, Json(Some(res))),
+        Err(e) => {
+            eprintln!("Error evaluating on SQLite OCEL: {e}");
+            (StatusCode::BAD_REQUEST, Json(None))
+        }
+    }
+}
+
 pub async fn auto_discover_constraints_handler<'a>(
     state: State<AppState>,
     Json(req): Json<AutoDiscoverConstraintsRequest>,
@@ -224,6 +471,67 @@ pub async fn auto_discover_constraints_handler<'a>(
     }))
 }
 
+pub async fn learn_from_examples_req(
+    state: State<AppState>,
+    Json(req): Json<LearnFromExamplesRequest>,
+) -> (StatusCode, Json<Option<LearnFromExamplesResponse>>) {
+    let res = with_ocel_from_state(&state, |ocel| learn_constraints_from_examples(ocel, &req));
+    match res.flatten() {
+        Some(x) => (StatusCode::OK, Json(Some(x))),
+        None => (StatusCode::BAD_REQUEST, Json(None)),
+    }
+}
+
+pub async fn process_executions_req(
+    state: State<AppState>,
+    Json(options): Json<ProcessExecutionOptions>,
+) -> Json<Option<Vec<ProcessExecution>>> {
+    Json(with_ocel_from_state(&state, |ocel| {
+        get_process_executions(ocel, &options)
+    }))
+}
+
+pub async fn check_with_box_tree_per_execution_req(
+    state: State<AppState>,
+    Json(req): Json<CheckWithBoxTreePerExecutionRequest>,
+) -> Json<Option<Vec<ExecutionEvaluationResult>>> {
+    Json(with_ocel_from_state(&state, |ocel| {
+        let executions = get_process_executions(ocel, &req.executions);
+        evaluate_per_execution(&req.tree, ocel, executions)
+    }))
+}
+
+pub async fn analyze_variants_req(
+    state: State<AppState>,
+    Json(req): Json<VariantAnalysisRequest>,
+) -> Json<Option<VariantAnalysisResult>> {
+    Json(with_ocel_from_state(&state, |ocel| analyze_variants(ocel, &req)))
+}
+
+pub async fn discover_ocdfg_req(
+    state: State<AppState>,
+    Json(req): Json<DiscoverOCDFGRequest>,
+) -> Json<Option<OCDFG>> {
+    Json(with_ocel_from_state(&state, |ocel| {
+        discover_ocdfg(ocel, req.object_types.as_deref())
+    }))
+}
+
+pub async fn discover_ocdfg_dot_req(
+    state: State<AppState>,
+    Json(req): Json<DiscoverOCDFGRequest>,
+) -> Json<Option<String>> {
+    Json(with_ocel_from_state(&state, |ocel| {
+        ocdfg_to_dot(&discover_ocdfg(ocel, req.object_types.as_deref()))
+    }))
+}
+
+pub async fn dfg_arc_constraint_req(
+    Json(req): Json<DFGArcConstraintRequest>,
+) -> Json<(String, BindingBoxTree)> {
+    Json(dfg_arc_to_constraint(&req))
+}
+
 pub async fn get_event_info_req<'a>(
     state: State<AppState>,
     Path(event_id): Path<String>,
@@ -336,6 +336,13 @@ function GraphOptions({
     rootIsObject: initialGrapOptions?.type !== "event",
     root: initialGrapOptions?.id ?? ocelInfo.object_ids[0],
     spanningTree: false,
+    eventTypes: null,
+    excludeEventTypes: null,
+    objectTypes: null,
+    excludeObjectTypes: null,
+    qualifiers: null,
+    excludeQualifiers: null,
+    maxNodes: null,
   });
 
   useEffect(() => {
@@ -0,0 +1,28 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { NumericSummary } from "./NumericSummary";
+import type { TimeSummary } from "./TimeSummary";
+import type { ValueTypeCount } from "./ValueTypeCount";
+
+export type AttributeProfile = {
+  name: string;
+  /**
+   * Number of observed values (objects can have multiple values for one attribute over time)
+   */
+  valueCount: number;
+  /**
+   * Number of observed values per value type
+   */
+  valueTypes: Array<ValueTypeCount>;
+  /**
+   * Fraction of events/objects of the type without any non-null value for this attribute
+   */
+  nullRate: number;
+  /**
+   * Summary of all integer and float values (`None` if there are none)
+   */
+  numeric: NumericSummary | null;
+  /**
+   * Summary of all time values (`None` if there are none)
+   */
+  time: TimeSummary | null;
+};
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Error while parsing the textual representation of a [`BindingBoxTree`]
+ *
+ * Lines and columns start at 1
+ */
+export type BoxTreeParseError = { line: number; column: number; message: string };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ConnectedObjectsFilter = {
+  objectIds: Array<string>;
+  /**
+   * Maximum number of E2O/O2O relationships between a kept event/object and one of the given objects
+   * (unbounded if not set)
+   */
+  maxDistance: number | null;
+};
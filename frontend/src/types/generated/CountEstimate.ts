@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type CountEstimate = {
+  estimate: number;
+  /**
+   * Lower bound of the 95% confidence interval
+   */
+  lower: number;
+  /**
+   * Upper bound of the 95% confidence interval
+   */
+  upper: number;
+};
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type DFGArc = {
+  from: string;
+  to: string;
+  count: number;
+  meanDurationSec: number;
+  medianDurationSec: number;
+  minDurationSec: number;
+  maxDurationSec: number;
+};
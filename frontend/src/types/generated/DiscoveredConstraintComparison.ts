@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BindingBoxTree } from "./BindingBoxTree";
+import type { ComparedOCEL } from "./ComparedOCEL";
+
+export type DiscoveredConstraintComparison = {
+  name: string;
+  tree: BindingBoxTree;
+  discoveredIn: ComparedOCEL;
+  /**
+   * Fraction of satisfied root situations in OCEL A (`None` if there are no root situations)
+   */
+  satisfiedFractionA: number | null;
+  /**
+   * Fraction of satisfied root situations in OCEL B (`None` if there are no root situations)
+   */
+  satisfiedFractionB: number | null;
+  holdsInA: boolean;
+  holdsInB: boolean;
+};
@@ -1,8 +1,14 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { EvaluationResultWithCount } from "./EvaluationResultWithCount";
+import type { SampledEvaluation } from "./SampledEvaluation";
 
 export type EvaluateBoxTreeResult = {
   evaluationResults: Array<EvaluationResultWithCount>;
   objectIds: Array<string>;
   eventIds: Array<string>;
+  /**
+   * Only set for approximate evaluations (see [`sampling::evaluate_sampled`])
+   * In that case, `evaluation_results` only contains the sampled situations
+   */
+  sampledEvaluation: SampledEvaluation | null;
 };
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type EvaluationSamplingOptions = {
+  /**
+   * Fraction of root node bindings to evaluate (between 0 and 1)
+   */
+  fraction: number;
+  /**
+   * Seed for selecting the sample (defaults to [`RNG_SEED`])
+   */
+  seed: number | null;
+};
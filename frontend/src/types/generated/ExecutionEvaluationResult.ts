@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ProcessExecution } from "./ProcessExecution";
+
+export type ExecutionEvaluationResult = {
+  execution: ProcessExecution;
+  /**
+   * Number of root situations in the process execution
+   */
+  situationCount: number;
+  situationViolatedCount: number;
+};
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type GraphLink = {
+  source: string;
+  target: string;
+  qualifier: string;
+  /**
+   * Whether the source is an object (otherwise, an event); the target is always an object
+   */
+  sourceIsObject: boolean;
+};
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { EventVariable } from "./EventVariable";
+import type { ObjectVariable } from "./ObjectVariable";
+
+export type IdBinding = {
+  eventMap: { [key: EventVariable]: string };
+  objectMap: { [key: ObjectVariable]: string };
+};
@@ -0,0 +1,31 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type NodeComparison = {
+  situationCountA: number;
+  situationCountB: number;
+  situationViolatedCountA: number;
+  situationViolatedCountB: number;
+  /**
+   * `None` if there are no situations for this node in OCEL A
+   */
+  violationRateA: number | null;
+  /**
+   * `None` if there are no situations for this node in OCEL B
+   */
+  violationRateB: number | null;
+  /**
+   * z-score of the two-proportion z-test (positive if the violation rate is higher in OCEL B)
+   */
+  zScore: number | null;
+  /**
+   * Two-sided p-value of the two-proportion z-test
+   */
+  pValue: number | null;
+  /**
+   * Whether the p-value is below the significance level
+   *
+   * Only set for the root node: The situations of child nodes are nested in (and depend on) the root situations,
+   * so the test assumptions do not hold for them and their z-score and p-value are only indicative
+   */
+  significant: boolean;
+};
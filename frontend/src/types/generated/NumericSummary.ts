@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type NumericSummary = {
+  min: number;
+  q1: number;
+  median: number;
+  q3: number;
+  max: number;
+};
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DiscoveredConstraintComparison } from "./DiscoveredConstraintComparison";
+import type { TreeComparison } from "./TreeComparison";
+
+export type OCELComparisonResult = {
+  trees: Array<TreeComparison>;
+  /**
+   * Discovered constraints which hold in exactly one of the two OCELs
+   */
+  changedConstraints: Array<DiscoveredConstraintComparison>;
+};
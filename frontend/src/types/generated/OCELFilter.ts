@@ -0,0 +1,27 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConnectedObjectsFilter } from "./ConnectedObjectsFilter";
+import type { ObjectAttributeFilter } from "./ObjectAttributeFilter";
+
+export type OCELFilter = {
+  /**
+   * Only keep events at or after this time
+   */
+  from: string | null;
+  /**
+   * Only keep events at or before this time
+   */
+  to: string | null;
+  /**
+   * Only keep events of these types (all types if not set)
+   */
+  eventTypes: Array<string> | null;
+  /**
+   * Only keep objects of these types (all types if not set)
+   */
+  objectTypes: Array<string> | null;
+  objectAttributeFilters: Array<ObjectAttributeFilter>;
+  /**
+   * Only keep events and objects connected to the given objects
+   */
+  connectedTo: ConnectedObjectsFilter | null;
+};
@@ -6,4 +6,34 @@ export type OCELGraphOptions = {
   rootIsObject: boolean;
   relsSizeIgnoreThreshold: number;
   spanningTree: boolean;
+  /**
+   * Only include events of these types (all if not set)
+   */
+  eventTypes: Array<string> | null;
+  /**
+   * Exclude events of these types
+   */
+  excludeEventTypes: Array<string> | null;
+  /**
+   * Only include objects of these types (all if not set)
+   */
+  objectTypes: Array<string> | null;
+  /**
+   * Exclude objects of these types
+   */
+  excludeObjectTypes: Array<string> | null;
+  /**
+   * Only include relationships with these qualifiers (all if not set)
+   */
+  qualifiers: Array<string> | null;
+  /**
+   * Exclude relationships with these qualifiers
+   */
+  excludeQualifiers: Array<string> | null;
+  /**
+   * Maximal number of nodes (unbounded if not set)
+   *
+   * Nodes closer to the root are included first
+   */
+  maxNodes: number | null;
 };
@@ -0,0 +1,33 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type OCELPathOptions = {
+  from: string;
+  fromIsObject: boolean;
+  to: string;
+  toIsObject: boolean;
+  /**
+   * Maximal number of links of a path
+   */
+  maxLength: number;
+  /**
+   * If true, all paths (without repeated nodes) of at most `max_length` links are returned;
+   * Otherwise, only the shortest paths are returned
+   */
+  allPaths: boolean;
+  /**
+   * Only traverse relationships with one of these qualifiers (all if not set)
+   */
+  qualifiers: Array<string> | null;
+  /**
+   * Only traverse intermediate events of these types (all if not set)
+   */
+  eventTypes: Array<string> | null;
+  /**
+   * Only traverse intermediate objects of these types (all if not set)
+   */
+  objectTypes: Array<string> | null;
+  /**
+   * Maximal number of returned paths (defaults to 100)
+   */
+  maxPaths: number | null;
+};
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OCELGraph } from "./OCELGraph";
+
+export type OCELPaths = {
+  /**
+   * Paths, sorted by length
+   *
+   * The nodes of a path are ordered from start to end, and the i-th link connects the i-th and (i+1)-th node
+   * (links keep the direction of the relationship in the OCEL)
+   */
+  paths: Array<OCELGraph>;
+  /**
+   * Union of all paths (e.g., for displaying them in a single graph)
+   */
+  graph: OCELGraph;
+};
@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RelationshipCardinality } from "./RelationshipCardinality";
+import type { TimeSummary } from "./TimeSummary";
+import type { TypeProfile } from "./TypeProfile";
+
+export type OCELProfile = {
+  numEvents: number;
+  numObjects: number;
+  /**
+   * Timestamp range of all events (`None` if there are no events)
+   */
+  timeRange: TimeSummary | null;
+  eventTypes: Array<TypeProfile>;
+  objectTypes: Array<TypeProfile>;
+  /**
+   * Cardinalities of E2O relationships (source type is an event type)
+   */
+  e2oCardinalities: Array<RelationshipCardinality>;
+  /**
+   * Cardinalities of O2O relationships (source type is an object type)
+   */
+  o2oCardinalities: Array<RelationshipCardinality>;
+};
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SchemaEdge } from "./SchemaEdge";
+import type { SchemaNode } from "./SchemaNode";
+
+export type OCELSchemaGraph = {
+  /**
+   * Event types (in the order of the OCEL), followed by object types
+   */
+  nodes: Array<SchemaNode>;
+  /**
+   * Edges, sorted by kind, source, qualifier and target
+   */
+  edges: Array<SchemaEdge>;
+};
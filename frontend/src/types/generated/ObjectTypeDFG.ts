@@ -0,0 +1,21 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ActivityCount } from "./ActivityCount";
+import type { DFGArc } from "./DFGArc";
+
+export type ObjectTypeDFG = {
+  objectType: string;
+  numObjects: number;
+  /**
+   * Number of events per event type (counted once per related object)
+   */
+  activities: Array<ActivityCount>;
+  /**
+   * Number of objects starting with an event of the event type
+   */
+  startActivities: Array<ActivityCount>;
+  /**
+   * Number of objects ending with an event of the event type
+   */
+  endActivities: Array<ActivityCount>;
+  arcs: Array<DFGArc>;
+};
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { EventIndex } from "./EventIndex";
+import type { ObjectIndex } from "./ObjectIndex";
+
+export type ProcessExecution = {
+  /**
+   * The leading object (only set for leading type process executions)
+   */
+  leadingObject: ObjectIndex | null;
+  /**
+   * Objects of the process execution (sorted by index)
+   */
+  objects: Array<ObjectIndex>;
+  /**
+   * All events related to at least one of the objects (sorted by index)
+   */
+  events: Array<EventIndex>;
+};
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ProcessExecutionOptions =
+  | { type: "ConnectedComponents"; object_types: Array<string> | null }
+  | { type: "LeadingType"; object_type: string; max_hops: number | null };
@@ -0,0 +1,32 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ProcessExecution } from "./ProcessExecution";
+
+export type ProcessVariant = {
+  /**
+   * Fingerprint of the variant (hex), with a suffix (e.g., `-1`) if non-isomorphic process executions share the fingerprint
+   */
+  id: string;
+  numExecutions: number;
+  /**
+   * Fraction of all process executions belonging to this variant
+   */
+  frequency: number;
+  /**
+   * Number of events per event type (of a single process execution)
+   */
+  eventTypes: Array<[string, number]>;
+  /**
+   * Number of objects per object type (of a single process execution)
+   */
+  objectTypes: Array<[string, number]>;
+  examples: Array<ProcessExecution>;
+  /**
+   * Number of situations (of the evaluated node) contained in process executions of this variant
+   */
+  situationCount: number;
+  situationViolatedCount: number;
+  /**
+   * `None` if no evaluation was provided or there are no situations
+   */
+  violationRate: number | null;
+};
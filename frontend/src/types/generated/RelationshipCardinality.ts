@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type RelationshipCardinality = {
+  sourceType: string;
+  qualifier: string;
+  targetType: string;
+  min: number;
+  avg: number;
+  max: number;
+};
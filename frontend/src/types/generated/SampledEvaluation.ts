@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { NodeEvaluationEstimate } from "./NodeEvaluationEstimate";
+
+export type SampledEvaluation = {
+  numRootBindings: number;
+  numSampledRootBindings: number;
+  /**
+   * Estimated counts for all nodes of the tree (by node index)
+   */
+  nodeEstimates: Array<NodeEvaluationEstimate>;
+};
@@ -0,0 +1,35 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SchemaRelationshipKind } from "./SchemaRelationshipKind";
+
+export type SchemaEdge = {
+  kind: SchemaRelationshipKind;
+  /**
+   * Event type (E2O) or object type (O2O)
+   */
+  source: string;
+  /**
+   * Object type
+   */
+  target: string;
+  qualifier: string;
+  /**
+   * Total number of relationships
+   */
+  count: number;
+  /**
+   * Min. number of relationships per event/object of the source type
+   */
+  min: number;
+  /**
+   * Avg. number of relationships per event/object of the source type
+   */
+  avg: number;
+  /**
+   * Max. number of relationships per event/object of the source type
+   */
+  max: number;
+  /**
+   * Fraction of events/objects of the source type with at least one such relationship
+   */
+  shareWithAny: number;
+};
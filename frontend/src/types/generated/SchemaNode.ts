@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type SchemaNode = {
+  name: string;
+  isObjectType: boolean;
+  /**
+   * Number of events/objects of the type
+   */
+  count: number;
+  /**
+   * Average number of E2O/O2O relationships an event/object of the type is involved in
+   */
+  avgRelationships: number;
+};
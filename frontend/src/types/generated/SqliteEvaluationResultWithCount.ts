@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { IdBinding } from "./IdBinding";
+import type { ViolationReason } from "./ViolationReason";
+
+export type SqliteEvaluationResultWithCount = {
+  violatedSituations: Array<[IdBinding, ViolationReason]>;
+  situationCount: number;
+  situationViolatedCount: number;
+};
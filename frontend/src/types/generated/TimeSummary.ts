@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type TimeSummary = {
+  min: string;
+  q1: string;
+  median: string;
+  q3: string;
+  max: string;
+};
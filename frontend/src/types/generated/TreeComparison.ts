@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { NodeComparison } from "./NodeComparison";
+
+export type TreeComparison = {
+  name: string;
+  /**
+   * Comparison for all nodes of the tree (by node index)
+   */
+  nodes: Array<NodeComparison>;
+};
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TrendBucket } from "./TrendBucket";
+
+export type TrendAnalysisResult = {
+  /**
+   * Consecutive buckets from the first to the last situation (including empty buckets)
+   */
+  buckets: Array<TrendBucket>;
+  /**
+   * Number of root situations for which no time reference could be determined
+   * (e.g., because the variable is not bound by the root node)
+   */
+  unassignedSituationCount: number;
+  plotHtml: string | null;
+};
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type TrendBucket = {
+  start: string;
+  end: string;
+  situationCount: number;
+  situationViolatedCount: number;
+  /**
+   * `None` if there are no situations in this bucket
+   */
+  violationRate: number | null;
+};
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { EventVariable } from "./EventVariable";
+import type { ObjectVariable } from "./ObjectVariable";
+
+export type TrendTimeReference =
+  | { type: "Event"; event: EventVariable }
+  | { type: "ObjectFirstEvent"; object: ObjectVariable };
@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AttributeProfile } from "./AttributeProfile";
+import type { TimeSummary } from "./TimeSummary";
+
+export type TypeProfile = {
+  name: string;
+  count: number;
+  attributes: Array<AttributeProfile>;
+  /**
+   * Timestamp range of the events of this type (always `None` for object types)
+   */
+  timeRange: TimeSummary | null;
+};
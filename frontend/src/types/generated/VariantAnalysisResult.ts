@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ProcessVariant } from "./ProcessVariant";
+
+export type VariantAnalysisResult = {
+  numExecutions: number;
+  /**
+   * Variants, sorted by descending frequency
+   */
+  variants: Array<ProcessVariant>;
+};
@@ -7,15 +7,48 @@ use std::{
 };
 
 use ocedeclare_shared::{
-    binding_box::{evaluate_box_tree, CheckWithBoxTreeRequest, EvaluateBoxTreeResult},
+    binding_box::{
+        dsl::{self, BoxTreeParseError},
+        evaluate_box_tree,
+        sqlite::{
+            evaluate_box_tree_sqlite, CheckSqliteWithBoxTreeRequest, SqliteEvaluateBoxTreeResult,
+        },
+        structs::BindingBoxTree,
+        trends::{analyze_trend, TrendAnalysisRequest, TrendAnalysisResult},
+        CheckWithBoxTreeRequest, EvaluateBoxTreeResult,
+    },
+    comparison::{compare_ocels, CompareOCELsRequest, OCELComparisonResult},
     discovery::{
-        auto_discover_constraints_with_options, AutoDiscoverConstraintsRequest,
-        AutoDiscoverConstraintsResponse,
+        auto_discover_constraints_with_options,
+        dfg::{
+            self, discover_ocdfg, ocdfg_to_dot, DFGArcConstraintRequest, DiscoverOCDFGRequest,
+            OCDFG,
+        },
+        example_discovery::{
+            learn_constraints_from_examples, LearnFromExamplesRequest, LearnFromExamplesResponse,
+        },
+        AutoDiscoverConstraintsRequest, AutoDiscoverConstraintsResponse,
     },
     get_event_info, get_object_info,
-    ocel_graph::{get_ocel_graph, OCELGraph, OCELGraphOptions},
+    ocel_graph::{
+        export::{export_ocel_graph, ExportOCELGraphRequest},
+        get_ocel_graph, get_ocel_paths,
+        schema::{get_schema_graph, schema_graph_to_dot, OCELSchemaGraph},
+        OCELGraph, OCELGraphOptions, OCELPathOptions, OCELPaths,
+    },
     ocel_qualifiers::qualifiers::{get_qualifiers_for_event_types, QualifiersForEventType},
-    preprocessing::linked_ocel::{link_ocel_info, IndexLinkedOCEL},
+    preprocessing::{
+        cache::load_linked_ocel_with_cache,
+        filter::{export_ocel_by_extension, filter_linked_ocel, filter_ocel, FilterOCELRequest},
+        flatten::{export_flat_log_by_extension, flatten_ocel, FlattenOCELRequest},
+        linked_ocel::IndexLinkedOCEL,
+        process_executions::{
+            evaluate_per_execution, get_process_executions, CheckWithBoxTreePerExecutionRequest,
+            ExecutionEvaluationResult, ProcessExecution, ProcessExecutionOptions,
+        },
+        profile::OCELProfile,
+        variants::{self, VariantAnalysisRequest, VariantAnalysisResult},
+    },
     EventWithIndex, IndexOrID, OCELInfo, ObjectWithIndex,
 };
 use process_mining::{import_ocel_json_from_path, import_ocel_sqlite_from_path, import_ocel_xml_file};
@@ -23,21 +56,64 @@ use tauri::State;
 
 type OCELStore = Mutex<Option<IndexLinkedOCEL>>;
 
-#[tauri::command(async)]
-fn import_ocel(path: &str, state: tauri::State<OCELStore>) -> Result<OCELInfo, String> {
-    let ocel = match path.ends_with(".json") {
-        true => import_ocel_json_from_path(path).map_err(|e| format!("{:?}", e))?,
+fn load_linked_ocel(path: &str) -> Result<IndexLinkedOCEL, String> {
+    load_linked_ocel_with_cache(path, || match path.ends_with(".json") {
+        true => import_ocel_json_from_path(path).map_err(|e| format!("{:?}", e)),
         false => match path.ends_with(".xml") {
-            true => import_ocel_xml_file(path),
-            false => import_ocel_sqlite_from_path(path).map_err(|e| format!("{:?}", e))?,
+            true => Ok(import_ocel_xml_file(path)),
+            false => import_ocel_sqlite_from_path(path).map_err(|e| format!("{:?}", e)),
         },
-    };
-    let ocel_info: OCELInfo = (&ocel).into();
+    })
+}
+
+#[tauri::command(async)]
+fn import_ocel(path: &str, state: tauri::State<OCELStore>) -> Result<OCELInfo, String> {
+    let linked_ocel = load_linked_ocel(path)?;
+    let ocel_info: OCELInfo = (&linked_ocel.ocel).into();
     let mut state_guard = state.lock().unwrap();
-    *state_guard = Some(link_ocel_info(ocel));
+    *state_guard = Some(linked_ocel);
     Ok(ocel_info)
 }
 
+/// Filter the current OCEL, either replacing it or exporting the result to `fork_path`
+#[tauri::command(async)]
+fn filter_current_ocel(
+    req: FilterOCELRequest,
+    state: tauri::State<OCELStore>,
+) -> Result<OCELInfo, String> {
+    let mut state_guard = state.lock().unwrap();
+    let ocel = state_guard.as_ref().ok_or("No OCEL loaded".to_string())?;
+    match req.fork_path {
+        Some(fork_path) => {
+            let filtered = filter_ocel(ocel, &req.filter);
+            export_ocel_by_extension(&filtered, fork_path).map_err(|e| e.to_string())?;
+            Ok((&filtered).into())
+        }
+        None => {
+            let filtered = filter_linked_ocel(ocel, &req.filter);
+            let ocel_info: OCELInfo = (&filtered.ocel).into();
+            *state_guard = Some(filtered);
+            Ok(ocel_info)
+        }
+    }
+}
+
+/// Flatten the current OCEL and export the resulting event log to `export_path`
+///
+/// Returns the number of cases
+#[tauri::command(async)]
+fn flatten_current_ocel(
+    req: FlattenOCELRequest,
+    state: tauri::State<OCELStore>,
+) -> Result<usize, String> {
+    let log = match state.lock().unwrap().as_ref() {
+        Some(ocel) => flatten_ocel(ocel, &req.case_notion, req.constraint_outcome.as_ref()),
+        None => return Err("No OCEL loaded".to_string()),
+    };
+    export_flat_log_by_extension(&log, &req.export_path).map_err(|e| e.to_string())?;
+    Ok(log.traces.len())
+}
+
 #[tauri::command(async)]
 fn get_current_ocel_info(state: tauri::State<OCELStore>) -> Result<OCELInfo, String> {
     let res: Result<OCELInfo, String> = match state.lock().unwrap().as_ref() {
@@ -47,6 +123,30 @@ fn get_current_ocel_info(state: tauri::State<OCELStore>) -> Result<OCELInfo, Str
     res
 }
 
+#[tauri::command(async)]
+fn get_current_ocel_profile(state: tauri::State<OCELStore>) -> Result<OCELProfile, String> {
+    match state.lock().unwrap().as_ref() {
+        Some(ocel) => Ok(ocel.profile().clone()),
+        None => Err("No OCEL loaded".to_string()),
+    }
+}
+
+#[tauri::command(async)]
+fn get_current_schema_graph(state: tauri::State<OCELStore>) -> Result<OCELSchemaGraph, String> {
+    match state.lock().unwrap().as_ref() {
+        Some(ocel) => Ok(get_schema_graph(ocel)),
+        None => Err("No OCEL loaded".to_string()),
+    }
+}
+
+#[tauri::command(async)]
+fn get_current_schema_graph_dot(state: tauri::State<OCELStore>) -> Result<String, String> {
+    match state.lock().unwrap().as_ref() {
+        Some(ocel) => Ok(schema_graph_to_dot(&get_schema_graph(ocel))),
+        None => Err("No OCEL loaded".to_string()),
+    }
+}
+
 #[tauri::command(async)]
 fn get_event_qualifiers(
     state: State<OCELStore>,
@@ -77,11 +177,56 @@ fn check_with_box_tree(
             req.tree,
             ocel,
             req.measure_performance.unwrap_or(false),
+            req.sampling,
         )),
         None => Err("No OCEL loaded".to_string()),
     }
 }
 
+#[tauri::command(async)]
+fn trend_analysis(
+    req: TrendAnalysisRequest,
+    state: State<OCELStore>,
+) -> Result<TrendAnalysisResult, String> {
+    match state.lock().unwrap().as_ref() {
+        Some(ocel) => Ok(analyze_trend(&req, ocel)),
+        None => Err("No OCEL loaded".to_string()),
+    }
+}
+
+#[tauri::command(async)]
+fn compare_with_ocel(
+    req: CompareOCELsRequest,
+    state: State<OCELStore>,
+) -> Result<OCELComparisonResult, String> {
+    // Check before (potentially slowly) loading the other OCEL
+    if state.lock().unwrap().is_none() {
+        return Err("No OCEL loaded".to_string());
+    }
+    let other_ocel = load_linked_ocel(&req.other_ocel_path)?;
+    match state.lock().unwrap().as_ref() {
+        Some(ocel) => Ok(compare_ocels(ocel, &other_ocel, &req.options)),
+        None => Err("No OCEL loaded".to_string()),
+    }
+}
+
+#[tauri::command(async)]
+fn box_tree_to_text(tree: BindingBoxTree) -> String {
+    dsl::box_tree_to_text(&tree)
+}
+
+#[tauri::command(async)]
+fn box_tree_from_text(text: String) -> Result<BindingBoxTree, BoxTreeParseError> {
+    dsl::box_tree_from_text(&text)
+}
+
+#[tauri::command(async)]
+fn check_sqlite_with_box_tree(
+    req: CheckSqliteWithBoxTreeRequest,
+) -> Result<SqliteEvaluateBoxTreeResult, String> {
+    evaluate_box_tree_sqlite(&req.tree, &req.ocel_path).map_err(|e| e.to_string())
+}
+
 #[tauri::command(async)]
 fn auto_discover_constraints(
     options: AutoDiscoverConstraintsRequest,
@@ -93,6 +238,83 @@ fn auto_discover_constraints(
     }
 }
 
+#[tauri::command(async)]
+fn learn_from_examples(
+    req: LearnFromExamplesRequest,
+    state: State<OCELStore>,
+) -> Result<LearnFromExamplesResponse, String> {
+    match state.lock().unwrap().as_ref() {
+        Some(ocel) => match learn_constraints_from_examples(ocel, &req) {
+            Some(res) => Ok(res),
+            None => Err("Could not learn constraints from the examples".to_string()),
+        },
+        None => Err("No OCEL loaded".to_string()),
+    }
+}
+
+#[tauri::command(async)]
+fn process_executions(
+    options: ProcessExecutionOptions,
+    state: State<OCELStore>,
+) -> Result<Vec<ProcessExecution>, String> {
+    match state.lock().unwrap().as_ref() {
+        Some(ocel) => Ok(get_process_executions(ocel, &options)),
+        None => Err("No OCEL loaded".to_string()),
+    }
+}
+
+#[tauri::command(async)]
+fn check_with_box_tree_per_execution(
+    req: CheckWithBoxTreePerExecutionRequest,
+    state: State<OCELStore>,
+) -> Result<Vec<ExecutionEvaluationResult>, String> {
+    match state.lock().unwrap().as_ref() {
+        Some(ocel) => {
+            let executions = get_process_executions(ocel, &req.executions);
+            Ok(evaluate_per_execution(&req.tree, ocel, executions))
+        }
+        None => Err("No OCEL loaded".to_string()),
+    }
+}
+
+#[tauri::command(async)]
+fn analyze_variants(
+    req: VariantAnalysisRequest,
+    state: State<OCELStore>,
+) -> Result<VariantAnalysisResult, String> {
+    match state.lock().unwrap().as_ref() {
+        Some(ocel) => Ok(variants::analyze_variants(ocel, &req)),
+        None => Err("No OCEL loaded".to_string()),
+    }
+}
+
+#[tauri::command(async)]
+fn discover_dfg(options: DiscoverOCDFGRequest, state: State<OCELStore>) -> Result<OCDFG, String> {
+    match state.lock().unwrap().as_ref() {
+        Some(ocel) => Ok(discover_ocdfg(ocel, options.object_types.as_deref())),
+        None => Err("No OCEL loaded".to_string()),
+    }
+}
+
+#[tauri::command(async)]
+fn discover_dfg_dot(
+    options: DiscoverOCDFGRequest,
+    state: State<OCELStore>,
+) -> Result<String, String> {
+    match state.lock().unwrap().as_ref() {
+        Some(ocel) => Ok(ocdfg_to_dot(&discover_ocdfg(
+            ocel,
+            options.object_types.as_deref(),
+        ))),
+        None => Err("No OCEL loaded".to_string()),
+    }
+}
+
+#[tauri::command(async)]
+fn dfg_arc_to_constraint(req: DFGArcConstraintRequest) -> (String, BindingBoxTree) {
+    dfg::dfg_arc_to_constraint(&req)
+}
+
 #[tauri::command(async)]
 fn ocel_graph(options: OCELGraphOptions, state: State<OCELStore>) -> Result<OCELGraph, String> {
     match state.lock().unwrap().as_ref() {
@@ -104,6 +326,34 @@ fn ocel_graph(options: OCELGraphOptions, state: State<OCELStore>) -> Result<OCEL
     }
 }
 
+#[tauri::command(async)]
+fn ocel_graph_export(
+    req: ExportOCELGraphRequest,
+    state: State<OCELStore>,
+) -> Result<String, String> {
+    match state.lock().unwrap().as_ref() {
+        Some(ocel) => match get_ocel_graph(ocel, req.options) {
+            Some(graph) => Ok(export_ocel_graph(&graph, req.format)),
+            None => Err("Could not construct OCEL Graph".to_string()),
+        },
+        None => Err("No OCEL loaded".to_string()),
+    }
+}
+
+#[tauri::command(async)]
+fn ocel_graph_paths(
+    options: OCELPathOptions,
+    state: State<OCELStore>,
+) -> Result<OCELPaths, String> {
+    match state.lock().unwrap().as_ref() {
+        Some(ocel) => match get_ocel_paths(ocel, &options) {
+            Some(paths) => Ok(paths),
+            None => Err("Could not find the given event or object".to_string()),
+        },
+        None => Err("No OCEL loaded".to_string()),
+    }
+}
+
 #[tauri::command(async)]
 fn get_event(req: IndexOrID, state: State<OCELStore>) -> Option<EventWithIndex> {
     match state.lock().unwrap().as_ref() {
@@ -126,11 +376,30 @@ fn main() {
         .invoke_handler(tauri::generate_handler![
             import_ocel,
             get_current_ocel_info,
+            get_current_ocel_profile,
+            get_current_schema_graph,
+            get_current_schema_graph_dot,
+            filter_current_ocel,
+            flatten_current_ocel,
             get_event_qualifiers,
             get_object_qualifiers,
             check_with_box_tree,
+            check_sqlite_with_box_tree,
+            trend_analysis,
+            compare_with_ocel,
+            box_tree_to_text,
+            box_tree_from_text,
             auto_discover_constraints,
+            learn_from_examples,
+            process_executions,
+            check_with_box_tree_per_execution,
+            analyze_variants,
+            discover_dfg,
+            discover_dfg_dot,
+            dfg_arc_to_constraint,
             ocel_graph,
+            ocel_graph_paths,
+            ocel_graph_export,
             get_event,
             get_object
         ])